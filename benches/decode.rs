@@ -0,0 +1,98 @@
+//! Decoding and encoding benchmarks.
+//!
+//! Run with `cargo bench`. Performance budget on a typical developer machine:
+//!
+//! * decoding a 500 tournaments index: under 10 ms;
+//! * decoding a 1000 matches index: under 10 ms;
+//! * decoding a 256 participants index: under 3 ms;
+//! * parsing and formatting 1000 `scores_csv` strings: under 1 ms;
+//! * encoding the parameters of a 256 participants bulk add, as a form or as JSON: under 1 ms.
+//!
+//! A change which makes any of these noticeably slower should be justified in its pull request.
+#[macro_use]
+extern crate criterion;
+extern crate challonge;
+extern crate serde_json;
+
+use challonge::transport::BodyEncoding;
+use challonge::{MatchIndex, MatchScores, ParticipantIndex, TournamentIndex};
+use criterion::{black_box, Criterion};
+
+const TOURNAMENT: &'static str = include_str!("fixtures/tournament.json");
+const PARTICIPANT: &'static str = include_str!("fixtures/participant.json");
+const MATCH: &'static str = include_str!("fixtures/match.json");
+
+fn index_payload(object: &str, count: usize) -> serde_json::Value {
+    let objects: Vec<&str> = (0..count).map(|_| object).collect();
+    serde_json::from_str(&format!("[{}]", objects.join(","))).unwrap()
+}
+
+fn decode_tournament_index(c: &mut Criterion) {
+    let payload = index_payload(TOURNAMENT, 500);
+    c.bench_function("decode tournament index (500)", move |b| {
+        b.iter(|| TournamentIndex::decode(black_box(&payload)).unwrap())
+    });
+}
+
+fn decode_match_index(c: &mut Criterion) {
+    let payload = index_payload(MATCH, 1000);
+    c.bench_function("decode match index (1000)", move |b| {
+        b.iter(|| MatchIndex::decode(black_box(&payload)).unwrap())
+    });
+}
+
+fn decode_participant_index(c: &mut Criterion) {
+    let payload = index_payload(PARTICIPANT, 256);
+    c.bench_function("decode participant index (256)", move |b| {
+        b.iter(|| ParticipantIndex::decode(black_box(&payload)).unwrap())
+    });
+}
+
+fn scores_round_trip(c: &mut Criterion) {
+    let scores: Vec<String> = (0..1000)
+        .map(|i| format!("{}-{},{}-{},{}-{}", i % 4, 3, 3, i % 3, i % 5, i % 2))
+        .collect();
+    c.bench_function("parse and format scores_csv (1000)", move |b| {
+        b.iter(|| {
+            for s in &scores {
+                black_box(MatchScores::decode(s.clone()).to_string());
+            }
+        })
+    });
+}
+
+fn bulk_add_params(count: usize) -> Vec<(&'static str, String)> {
+    let mut params = Vec::new();
+    for i in 0..count {
+        params.push(("participant[][name]", format!("Participant #{} (Zoë)", i)));
+        params.push(("participant[][email]", format!("player{}@example.com", i)));
+        params.push(("participant[][seed]", (i + 1).to_string()));
+        params.push(("participant[][misc]", format!("id={}&team=a b", i)));
+    }
+    params
+}
+
+fn encode_form_body(c: &mut Criterion) {
+    let params = bulk_add_params(256);
+    c.bench_function("encode bulk add form body (256)", move |b| {
+        b.iter(|| BodyEncoding::Form.encode(black_box(&params)).unwrap())
+    });
+}
+
+fn encode_json_body(c: &mut Criterion) {
+    let params = bulk_add_params(256);
+    c.bench_function("encode bulk add json body (256)", move |b| {
+        b.iter(|| BodyEncoding::Json.encode(black_box(&params)).unwrap())
+    });
+}
+
+criterion_group!(
+    benches,
+    decode_tournament_index,
+    decode_match_index,
+    decode_participant_index,
+    scores_round_trip,
+    encode_form_body,
+    encode_json_body
+);
+criterion_main!(benches);
@@ -0,0 +1,104 @@
+//! Announces signups, results and the end of a tournament from the events of a `Watcher`.
+//!
+//! A scripted organizer changes the tournament between two polls of the watcher, like a bot in a
+//! chat channel would see it. Runs against the in-memory server of the `fake` module:
+//! `cargo run --example announcer`
+
+extern crate challonge;
+
+mod fake;
+
+use challonge::{
+    Challonge, MatchScore, MatchScores, MatchState, MatchUpdate, ParticipantCreate,
+    TournamentCreate, TournamentId, TournamentIncludes, TournamentStatus, Watcher, WatcherEvent,
+};
+use fake::FakeChallonge;
+
+/// Makes the next change to the tournament, returns `false` when there is nothing left to do.
+fn organize(c: &Challonge, id: &TournamentId, step: usize) -> bool {
+    const SIGNUPS: [&'static str; 4] = ["Ada", "Grace", "Linus", "Barbara"];
+    if let Some(name) = SIGNUPS.get(step) {
+        c.create_participant(
+            id,
+            ParticipantCreate::new().name(*name).seed(step as u64 + 1),
+        )
+        .unwrap();
+        return true;
+    }
+    let t = c.get_tournament(id, &TournamentIncludes::All).unwrap();
+    match t.state {
+        TournamentStatus::Pending => c.tournament_start(id, &TournamentIncludes::All).unwrap(),
+        TournamentStatus::Underway => {
+            let open = c.match_index(id, Some(MatchState::Open), None).unwrap();
+            let m = &open.0[0];
+            c.update_match(
+                id,
+                &m.id,
+                MatchUpdate::new()
+                    .scores_csv(MatchScores(vec![MatchScore(3, 1)]))
+                    .winner_id(m.player2.id.clone().unwrap()),
+            )
+            .unwrap();
+        }
+        TournamentStatus::AwaitingReview => {
+            c.tournament_finalize(id, &TournamentIncludes::All).unwrap()
+        }
+        _ => return false,
+    }
+    true
+}
+
+fn main() {
+    let server = FakeChallonge::new();
+    let c = server.client();
+    let t = c
+        .create_tournament(
+            TournamentCreate::new()
+                .name("Weekly Cup")
+                .url("weekly_cup")
+                .signup_cap(4),
+        )
+        .unwrap();
+
+    let mut watcher = Watcher::new(&c, t.id.clone());
+    // the first poll only primes the watcher with the current state
+    watcher.poll().unwrap();
+
+    let mut results = 0;
+    let mut finished = false;
+    let mut step = 0;
+    while organize(&c, &t.id, step) {
+        step += 1;
+        for event in watcher.poll().unwrap() {
+            match event {
+                WatcherEvent::SignupReceived(p) => {
+                    println!("Welcome {}, seeded #{}!", p.display_name(), p.seed)
+                }
+                WatcherEvent::SignupCapReached => println!("Registration is full."),
+                WatcherEvent::MatchUpdated(ref m) if m.state == MatchState::Open => println!(
+                    "Match {} is ready: {} vs {}",
+                    m.identifier,
+                    server.name(m.player1.id.as_ref().unwrap()),
+                    server.name(m.player2.id.as_ref().unwrap())
+                ),
+                WatcherEvent::MatchUpdated(ref m) if m.state == MatchState::Complete => {
+                    results += 1;
+                    println!(
+                        "{} wins match {} ({})",
+                        server.name(m.winner_id.as_ref().unwrap()),
+                        m.identifier,
+                        m.scores_csv
+                    );
+                }
+                WatcherEvent::TournamentUpdated(ref t) if t.state == TournamentStatus::Complete => {
+                    finished = true;
+                    println!("{} is over, thanks for playing!", t.name);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    assert_eq!(results, 3);
+    assert!(finished);
+}
@@ -0,0 +1,90 @@
+//! Creates a single elimination bracket, plays it to the end and prints the final standings.
+//!
+//! Runs against the in-memory server of the `fake` module, no account needed:
+//! `cargo run --example bracket`
+
+extern crate challonge;
+
+mod fake;
+
+use challonge::{
+    MatchScore, MatchScores, MatchState, MatchUpdate, ParticipantCreate, TournamentCreate,
+    TournamentIncludes, TournamentStatus,
+};
+use fake::FakeChallonge;
+
+const PLAYERS: [&'static str; 6] = ["Ada", "Grace", "Linus", "Barbara", "Ken", "Margaret"];
+
+fn main() {
+    let server = FakeChallonge::new();
+    let c = server.client();
+
+    let t = c
+        .create_tournament(
+            TournamentCreate::new()
+                .name("Friday Night Table Tennis")
+                .url("friday_table_tennis")
+                .signup_cap(8),
+        )
+        .unwrap();
+    println!("Created {} ({})", t.name, t.full_challonge_url);
+
+    for (i, name) in PLAYERS.iter().enumerate() {
+        let p = c
+            .create_participant(
+                &t.id,
+                ParticipantCreate::new().name(*name).seed(i as u64 + 1),
+            )
+            .unwrap();
+        println!("  #{} {}", p.seed, p.display_name());
+    }
+
+    c.tournament_start(&t.id, &TournamentIncludes::Matches)
+        .unwrap();
+
+    // report the open matches round by round, the first player wins 2-1
+    loop {
+        let open = c.match_index(&t.id, Some(MatchState::Open), None).unwrap();
+        if open.0.is_empty() {
+            break;
+        }
+        for m in open.0 {
+            let winner = m.player1.id.clone().unwrap();
+            let loser = m.player2.id.clone().unwrap();
+            let reported = c
+                .update_match(
+                    &t.id,
+                    &m.id,
+                    MatchUpdate::new()
+                        .scores_csv(MatchScores(vec![
+                            MatchScore(11, 7),
+                            MatchScore(9, 11),
+                            MatchScore(11, 5),
+                        ]))
+                        .winner_id(winner.clone()),
+                )
+                .unwrap();
+            println!(
+                "Round {} match {}: {} beat {} {}",
+                reported.round,
+                reported.identifier,
+                server.name(&winner),
+                server.name(&loser),
+                reported.scores_csv
+            );
+        }
+    }
+
+    c.tournament_finalize(&t.id, &TournamentIncludes::All)
+        .unwrap();
+    let t = c.get_tournament(&t.id, &TournamentIncludes::All).unwrap();
+    assert_eq!(t.state, TournamentStatus::Complete);
+
+    let mut standings = c.participant_index(&t.id).unwrap().0;
+    standings.sort_by_key(|p| p.final_rank);
+    println!("Final standings of {}:", t.name);
+    for p in &standings {
+        println!("  {}. {}", p.final_rank.unwrap(), p.display_name());
+    }
+    assert_eq!(standings[0].final_rank, Some(1));
+}
@@ -0,0 +1,89 @@
+//! Imports a roster from CSV into a new tournament and starts it.
+//!
+//! Rows which do not fit under the signup cap are reported instead of being put on the waiting
+//! list. Runs against the in-memory server of the `fake` module:
+//! `cargo run --example csv_import`
+
+extern crate challonge;
+
+mod fake;
+
+use challonge::{
+    MatchState, ParticipantCreate, TournamentCreate, TournamentIncludes, TournamentStatus,
+};
+use fake::FakeChallonge;
+
+const ROSTER: &'static str = "name,email,club
+Ada,ada@example.com,Analytical
+Grace,grace@example.com,Compilers
+
+Linus,linus@example.com,Kernel
+Barbara,barbara@example.com,Abstraction
+Ken,ken@example.com,Unix
+Margaret,margaret@example.com,Apollo
+Dennis,dennis@example.com,Unix
+";
+
+/// Reads `name,email,club` rows, skipping the header and blank lines. Rows are seeded in order.
+fn parse_roster(csv: &str) -> Result<Vec<ParticipantCreate>, String> {
+    let mut participants = Vec::new();
+    for (number, line) in csv.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let columns: Vec<&str> = line.split(',').map(str::trim).collect();
+        if columns.len() != 3 || columns[0].is_empty() {
+            return Err(format!("Malformed row {}: {:?}", number + 1, line));
+        }
+        let mut p = ParticipantCreate::new();
+        p.name(columns[0])
+            .email(columns[1])
+            .misc(columns[2])
+            .seed(participants.len() as u64 + 1);
+        participants.push(p);
+    }
+    Ok(participants)
+}
+
+fn main() {
+    let server = FakeChallonge::new();
+    let c = server.client();
+    let t = c
+        .create_tournament(
+            TournamentCreate::new()
+                .name("Club Championship")
+                .url("club_championship")
+                .signup_cap(6),
+        )
+        .unwrap();
+
+    let roster = parse_roster(ROSTER).unwrap();
+    println!("Importing {} participants into {}", roster.len(), t.name);
+    let report = c
+        .create_participant_bulk_capped(&t.id, roster, true)
+        .unwrap();
+    for p in &report.deferred {
+        println!("  no slot left for {}", p.name.as_ref().unwrap());
+    }
+
+    let participants = c.participant_index(&t.id).unwrap();
+    assert_eq!(participants.0.len(), report.accepted.len());
+
+    c.tournament_start(&t.id, &TournamentIncludes::All).unwrap();
+    let t = c.get_tournament(&t.id, &TournamentIncludes::All).unwrap();
+    assert_eq!(t.state, TournamentStatus::Underway);
+
+    println!("First round of {}:", t.name);
+    for m in c
+        .match_index(&t.id, Some(MatchState::Open), None)
+        .unwrap()
+        .0
+    {
+        println!(
+            "  {}: {} vs {}",
+            m.identifier,
+            server.name(m.player1.id.as_ref().unwrap()),
+            server.name(m.player2.id.as_ref().unwrap())
+        );
+    }
+}
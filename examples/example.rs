@@ -9,7 +9,10 @@ use challonge::ParticipantCreate;
 use chrono::*;
 
 fn main() {
-    let c = Challonge::new("myusername", "myapi_key");
+    let c = Challonge::builder()
+        .credentials("myusername", "myapi_key")
+        .build()
+        .unwrap();
     let i = c.tournament_index(
         &TournamentState::All,
         &TournamentType::DoubleElimination,
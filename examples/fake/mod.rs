@@ -0,0 +1,549 @@
+//! In-memory Challonge server the examples run against.
+//!
+//! `FakeChallonge` implements `Transport`, so a client built with `FakeChallonge::client` goes
+//! through its whole request pipeline without network access or an account. It keeps
+//! tournaments, participants and matches in memory and understands the calls of the examples:
+//! creating tournaments and participants, starting, reporting and finalizing single elimination
+//! brackets. The matches of a round are created once the previous round is complete and every
+//! write advances a logical clock by a second, so runs are deterministic. Records are built from
+//! the fixtures of the benchmarks and look like real API responses.
+
+#![allow(dead_code)]
+
+extern crate chrono;
+extern crate serde_json;
+extern crate url;
+
+use std::sync::{Arc, Mutex};
+
+use self::chrono::Duration;
+use self::serde_json::Value;
+
+use challonge::error::Error;
+use challonge::matches::Player;
+use challonge::redaction::RedactionPolicy;
+use challonge::{
+    Challonge, ChallongeDateTime, Match, MatchId, MatchScores, MatchState, Method, Participant,
+    ParticipantId, Request, Response, Tournament, TournamentId, TournamentStatus, TournamentType,
+    Transport,
+};
+
+/// Root of the API served by the fake.
+pub const BASE_URL: &'static str = "https://fake.challonge.test/v1";
+
+const TOURNAMENT: &'static str = include_str!("../../benches/fixtures/tournament.json");
+const PARTICIPANT: &'static str = include_str!("../../benches/fixtures/participant.json");
+const MATCH: &'static str = include_str!("../../benches/fixtures/match.json");
+
+/// Status code and message of a rejected request.
+type Rejection = (u16, String);
+
+fn reject<T>(status: u16, message: &str) -> Result<T, Rejection> {
+    Err((status, message.to_owned()))
+}
+
+struct Bracket {
+    tournament: Tournament,
+    participants: Vec<Participant>,
+    matches: Vec<Match>,
+    bye: Option<ParticipantId>,
+}
+impl Bracket {
+    fn player(&self, id: &ParticipantId) -> Option<&Participant> {
+        self.participants.iter().find(|p| p.id == *id)
+    }
+
+    fn seeded(&self) -> Vec<ParticipantId> {
+        let mut seeded: Vec<&Participant> = self
+            .participants
+            .iter()
+            .filter(|p| p.active && !p.on_waiting_list)
+            .collect();
+        seeded.sort_by_key(|p| p.seed);
+        seeded.into_iter().map(|p| p.id.clone()).collect()
+    }
+}
+
+struct Server {
+    next_id: u64,
+    now: ChallongeDateTime,
+    brackets: Vec<Bracket>,
+}
+impl Server {
+    fn id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn tick(&mut self) -> ChallongeDateTime {
+        self.now = ChallongeDateTime(self.now.0 + Duration::seconds(1));
+        self.now
+    }
+
+    fn bracket(&mut self, key: &str) -> Result<&mut Bracket, Rejection> {
+        let id = key.parse::<u64>().ok().map(TournamentId::Id);
+        match self
+            .brackets
+            .iter_mut()
+            .find(|b| Some(&b.tournament.id) == id.as_ref() || b.tournament.url == key)
+        {
+            Some(b) => Ok(b),
+            None => reject(404, "Tournament not found"),
+        }
+    }
+
+    fn create_tournament(&mut self, fields: &[(String, String)]) -> Result<Value, Rejection> {
+        let name = field(fields, "tournament[name]").unwrap_or("");
+        let url = field(fields, "tournament[url]").unwrap_or("");
+        if name.is_empty() {
+            return reject(422, "Name can't be blank");
+        }
+        if self.brackets.iter().any(|b| b.tournament.url == url) {
+            return reject(422, "URL has already been taken");
+        }
+        let tournament_type = field(fields, "tournament[tournament_type]")
+            .and_then(|t| t.parse::<TournamentType>().ok())
+            .unwrap_or(TournamentType::SingleElimination);
+        if tournament_type != TournamentType::SingleElimination {
+            return reject(422, "The fake server only runs single elimination brackets");
+        }
+
+        let id = self.id();
+        let now = self.tick();
+        let mut t = template(TOURNAMENT, Tournament::decode);
+        t.id = TournamentId::Id(id);
+        t.name = name.to_owned();
+        t.url = url.to_owned();
+        t.description = field(fields, "tournament[description]")
+            .unwrap_or("")
+            .to_owned();
+        t.tournament_type = tournament_type;
+        t.state = TournamentStatus::Pending;
+        t.signup_cap = field(fields, "tournament[signup_cap]")
+            .and_then(|c| c.parse().ok())
+            .filter(|c| *c > 0);
+        t.participants_count = 0;
+        t.progress_meter = 0;
+        t.created_by_api = true;
+        t.participants_locked = false;
+        t.created_at = now;
+        t.updated_at = now;
+        t.started_at = None;
+        t.full_challonge_url = format!("http://challonge.com/{}", url);
+        t.live_image_url = format!("http://images.challonge.com/{}.png", url);
+        let value = t.encode();
+        self.brackets.push(Bracket {
+            tournament: t,
+            participants: Vec::new(),
+            matches: Vec::new(),
+            bye: None,
+        });
+        Ok(value)
+    }
+
+    fn add_participant(
+        &mut self,
+        key: &str,
+        fields: &[(String, String)],
+        prefix: &str,
+    ) -> Result<Participant, Rejection> {
+        let get = |name: &str| field(fields, &format!("{}[{}]", prefix, name)).unwrap_or("");
+        let name = if get("name").is_empty() {
+            get("email").to_owned()
+        } else {
+            get("name").to_owned()
+        };
+        if name.is_empty() {
+            return reject(422, "Name can't be blank");
+        }
+        let id = self.id();
+        let now = self.tick();
+        let b = try!(self.bracket(key));
+        if b.tournament.state != TournamentStatus::Pending {
+            return reject(422, "Participants can't be added to a started tournament");
+        }
+        if b.participants.iter().any(|p| p.name == name) {
+            return reject(422, "Name has already been taken");
+        }
+
+        let listed = b.participants.iter().filter(|p| !p.on_waiting_list).count() as u64;
+        let waiting = b.tournament.signup_cap.map_or(false, |cap| listed >= cap);
+        let seed = if waiting {
+            b.participants.len() as u64 + 1
+        } else {
+            let seed = get("seed").parse::<u64>().unwrap_or(listed + 1);
+            let seed = seed.max(1).min(listed + 1);
+            for p in b.participants.iter_mut().filter(|p| p.seed >= seed) {
+                p.seed += 1;
+            }
+            seed
+        };
+
+        let mut p = template(PARTICIPANT, Participant::decode);
+        p.id = ParticipantId(id);
+        p.tournament_id = match b.tournament.id {
+            TournamentId::Id(id) => id,
+            TournamentId::Url(..) => 0,
+        };
+        p.name = name.clone();
+        p.display_name_with_invitation_email_address = name;
+        p.invite_email = get("email").to_owned();
+        p.misc = get("misc").to_owned();
+        p.seed = seed;
+        p.on_waiting_list = waiting;
+        p.created_at = now;
+        p.updated_at = now;
+        b.participants.push(p.clone());
+        if !waiting {
+            b.tournament.participants_count += 1;
+        }
+        b.tournament.updated_at = now;
+        Ok(p)
+    }
+
+    fn bulk_add(&mut self, key: &str, fields: &[(String, String)]) -> Result<Value, Rejection> {
+        // every participant starts with its email, see `create_participant_bulk`
+        let mut groups: Vec<Vec<(String, String)>> = Vec::new();
+        for &(ref name, ref value) in fields {
+            if name == "participant[][email]" || groups.is_empty() {
+                groups.push(Vec::new());
+            }
+            let name = name.replace("participant[]", "participant");
+            groups.last_mut().unwrap().push((name, value.clone()));
+        }
+        let mut added = Vec::new();
+        for group in groups {
+            let p = try!(self.add_participant(key, &group, "participant"));
+            added.push(p.encode(&RedactionPolicy::none()));
+        }
+        Ok(Value::Array(added))
+    }
+
+    fn start(&mut self, key: &str) -> Result<Value, Rejection> {
+        let now = self.tick();
+        let seeded = {
+            let b = try!(self.bracket(key));
+            if b.tournament.state != TournamentStatus::Pending {
+                return reject(422, "Tournament has already been started");
+            }
+            b.seeded()
+        };
+        if seeded.len() < 2 {
+            return reject(422, "Tournaments need at least 2 participants to start");
+        }
+        // the first round pairs the best seed with the worst one, 1 vs n, 2 vs n - 1, ...
+        // with an odd number of players the best seed gets the bye
+        let mut players = Vec::new();
+        let rest = if seeded.len() % 2 == 1 {
+            players.push(seeded[0].clone());
+            &seeded[1..]
+        } else {
+            &seeded[..]
+        };
+        for (best, worst) in rest.iter().zip(rest.iter().rev()).take(rest.len() / 2) {
+            players.push(best.clone());
+            players.push(worst.clone());
+        }
+        try!(self.create_round(key, 1, players));
+
+        let b = try!(self.bracket(key));
+        b.tournament.state = TournamentStatus::Underway;
+        b.tournament.started_at = Some(now);
+        b.tournament.participants_locked = true;
+        b.tournament.updated_at = now;
+        Ok(b.tournament.encode())
+    }
+
+    /// Pairs neighbouring players, an odd one out at the front gets a bye into the next round.
+    fn create_round(
+        &mut self,
+        key: &str,
+        round: u64,
+        mut players: Vec<ParticipantId>,
+    ) -> Result<(), Rejection> {
+        let bye = if players.len() % 2 == 1 {
+            Some(players.remove(0))
+        } else {
+            None
+        };
+        let mut ids = Vec::new();
+        for _ in 0..players.len() / 2 {
+            ids.push(self.id());
+        }
+        let now = self.tick();
+        let b = try!(self.bracket(key));
+        b.bye = bye;
+        for (pair, id) in players.chunks(2).zip(ids) {
+            let mut m = template(MATCH, Match::decode);
+            m.id = MatchId(id);
+            m.identifier = identifier(b.matches.len());
+            m.tournament_id = b.tournament.id.clone();
+            m.round = round;
+            m.player1 = player(&pair[0]);
+            m.player2 = player(&pair[1]);
+            m.state = MatchState::Open;
+            m.scores_csv = MatchScores(Vec::new());
+            m.winner_id = None;
+            m.loser_id = None;
+            m.prerequisite_match_ids_csv = String::new();
+            m.created_at = now;
+            m.started_at = Some(now);
+            m.updated_at = now;
+            b.matches.push(m);
+        }
+        Ok(())
+    }
+
+    fn report(
+        &mut self,
+        key: &str,
+        match_id: &str,
+        fields: &[(String, String)],
+    ) -> Result<Value, Rejection> {
+        let now = self.tick();
+        let (round, value) = {
+            let b = try!(self.bracket(key));
+            let m = match b
+                .matches
+                .iter_mut()
+                .find(|m| m.id.0.to_string() == match_id)
+            {
+                Some(m) => m,
+                None => return reject(404, "Match not found"),
+            };
+            if m.state != MatchState::Open {
+                return reject(422, "Match is not open");
+            }
+            if let Some(scores) = field(fields, "match[scores_csv]") {
+                m.scores_csv = MatchScores::decode(scores.to_owned());
+            }
+            if let Some(winner) = field(fields, "match[winner_id]") {
+                let winner = ParticipantId(winner.parse().unwrap_or(0));
+                let loser = if m.player1.id.as_ref() == Some(&winner) {
+                    m.player2.id.clone()
+                } else if m.player2.id.as_ref() == Some(&winner) {
+                    m.player1.id.clone()
+                } else {
+                    return reject(422, "Winner must be a player of the match");
+                };
+                m.winner_id = Some(winner);
+                m.loser_id = loser;
+                m.state = MatchState::Complete;
+            }
+            m.updated_at = now;
+            (m.round, m.encode())
+        };
+        try!(self.advance(key, round));
+        Ok(value)
+    }
+
+    /// Creates the next round once all matches of `round` are complete.
+    fn advance(&mut self, key: &str, round: u64) -> Result<(), Rejection> {
+        let now = self.tick();
+        let winners = {
+            let b = try!(self.bracket(key));
+            b.tournament.updated_at = now;
+            let complete = b
+                .matches
+                .iter()
+                .filter(|m| m.state == MatchState::Complete)
+                .count() as u64;
+            b.tournament.progress_meter =
+                complete * 100 / b.tournament.participants_count.saturating_sub(1).max(1);
+
+            let this_round: Vec<&Match> = b.matches.iter().filter(|m| m.round == round).collect();
+            if this_round.iter().any(|m| m.state != MatchState::Complete) {
+                return Ok(());
+            }
+            let mut winners: Vec<ParticipantId> = b.bye.take().into_iter().collect();
+            winners.extend(this_round.iter().filter_map(|m| m.winner_id.clone()));
+            if winners.len() < 2 {
+                b.tournament.state = TournamentStatus::AwaitingReview;
+                return Ok(());
+            }
+            winners
+        };
+        self.create_round(key, round + 1, winners)
+    }
+
+    fn finalize(&mut self, key: &str) -> Result<Value, Rejection> {
+        let now = self.tick();
+        let b = try!(self.bracket(key));
+        if b.tournament.state != TournamentStatus::AwaitingReview {
+            return reject(
+                422,
+                "All matches must be complete to finalize the tournament",
+            );
+        }
+        // losers share the rank below the players which went further, 2, 3, 5, 9, ...
+        let rounds = b.matches.iter().map(|m| m.round).max().unwrap_or(0);
+        let mut ranks = Vec::new();
+        for m in &b.matches {
+            if let Some(loser) = m.loser_id.clone() {
+                ranks.push((loser, (1u64 << (rounds - m.round)) + 1));
+            }
+            if m.round == rounds {
+                ranks.extend(m.winner_id.clone().map(|w| (w, 1)));
+            }
+        }
+        for p in b.participants.iter_mut() {
+            p.final_rank = ranks.iter().find(|r| r.0 == p.id).map(|r| r.1);
+            p.updated_at = now;
+        }
+        b.tournament.state = TournamentStatus::Complete;
+        b.tournament.progress_meter = 100;
+        b.tournament.updated_at = now;
+        Ok(b.tournament.encode())
+    }
+
+    fn handle(
+        &mut self,
+        method: Method,
+        path: &[&str],
+        query: &[(String, String)],
+        fields: &[(String, String)],
+    ) -> Result<Value, Rejection> {
+        let policy = RedactionPolicy::none();
+        match (method, path) {
+            (Method::Post, ["tournaments"]) => self.create_tournament(fields),
+            (Method::Get, ["tournaments", t]) => Ok(try!(self.bracket(t)).tournament.encode()),
+            (Method::Post, ["tournaments", t, "start"]) => self.start(t),
+            (Method::Post, ["tournaments", t, "finalize"]) => self.finalize(t),
+            (Method::Get, ["tournaments", t, "participants"]) => {
+                let b = try!(self.bracket(t));
+                Ok(Value::Array(
+                    b.participants.iter().map(|p| p.encode(&policy)).collect(),
+                ))
+            }
+            (Method::Post, ["tournaments", t, "participants"]) => self
+                .add_participant(t, fields, "participant")
+                .map(|p| p.encode(&policy)),
+            (Method::Post, ["tournaments", t, "participants", "bulk_add"]) => {
+                self.bulk_add(t, fields)
+            }
+            (Method::Get, ["tournaments", t, "matches"]) => {
+                let state = field(query, "state").unwrap_or("all");
+                let b = try!(self.bracket(t));
+                Ok(Value::Array(
+                    b.matches
+                        .iter()
+                        .filter(|m| state == "all" || m.state.to_string() == state)
+                        .map(|m| m.encode())
+                        .collect(),
+                ))
+            }
+            (Method::Get, ["tournaments", t, "matches", m]) => {
+                let b = try!(self.bracket(t));
+                match b.matches.iter().find(|x| x.id.0.to_string() == *m) {
+                    Some(m) => Ok(m.encode()),
+                    None => reject(404, "Match not found"),
+                }
+            }
+            (Method::Put, ["tournaments", t, "matches", m]) => self.report(t, m, fields),
+            _ => reject(404, "Not found"),
+        }
+    }
+}
+
+/// Stateful fake of the Challonge API, clones share the state.
+#[derive(Clone)]
+pub struct FakeChallonge {
+    server: Arc<Mutex<Server>>,
+}
+impl FakeChallonge {
+    /// Creates a server without tournaments.
+    pub fn new() -> FakeChallonge {
+        let epoch = template(TOURNAMENT, Tournament::decode).created_at;
+        FakeChallonge {
+            server: Arc::new(Mutex::new(Server {
+                next_id: 1000,
+                now: epoch,
+                brackets: Vec::new(),
+            })),
+        }
+    }
+
+    /// Builds a client talking to this server.
+    pub fn client(&self) -> Challonge {
+        Challonge::builder()
+            .credentials("example", "fake-api-key")
+            .base_url(BASE_URL)
+            .transport(self.clone())
+            .build()
+            .unwrap()
+    }
+
+    /// Returns the display name of a participant of any tournament.
+    pub fn name(&self, id: &ParticipantId) -> String {
+        let server = self.server.lock().unwrap();
+        server
+            .brackets
+            .iter()
+            .filter_map(|b| b.player(id))
+            .map(|p| p.display_name().to_owned())
+            .next()
+            .unwrap_or_else(|| format!("#{}", id.0))
+    }
+}
+impl Transport for FakeChallonge {
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        let url = try!(url::Url::parse(&request.url).map_err(|e| Error::Transport(e.to_string())));
+        let segments: Vec<&str> = url
+            .path_segments()
+            .map_or(Vec::new(), |s| s.collect())
+            .into_iter()
+            .skip(1)
+            .map(|s| s.trim_end_matches(".json"))
+            .collect();
+        let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        let body = request.body.as_ref().map_or("", |b| &b[..]);
+        let fields: Vec<(String, String)> = url::form_urlencoded::parse(body.as_bytes())
+            .into_owned()
+            .collect();
+
+        let mut server = self.server.lock().unwrap();
+        let (status, value) = match server.handle(request.method, &segments, &query, &fields) {
+            Ok(value) => (200, value),
+            Err((status, message)) => {
+                let mut errors = ::std::collections::BTreeMap::new();
+                errors.insert(
+                    "errors".to_owned(),
+                    Value::Array(vec![Value::String(message)]),
+                );
+                (status, Value::Object(errors))
+            }
+        };
+        Ok(Response {
+            status: status,
+            headers: vec![("Content-Type".to_owned(), "application/json".to_owned())],
+            body: serde_json::to_vec(&value).unwrap(),
+        })
+    }
+}
+
+fn template<T>(json: &str, decode: fn(&Value) -> Result<T, Error>) -> T {
+    decode(&serde_json::from_str(json).unwrap()).unwrap()
+}
+
+fn field<'a>(fields: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    fields.iter().find(|f| f.0 == name).map(|f| &f.1[..])
+}
+
+fn player(id: &ParticipantId) -> Player {
+    Player {
+        id: Some(id.clone()),
+        is_prereq_match_loser: false,
+        prereq_match_id: None,
+        votes: 0,
+    }
+}
+
+/// Match identifiers of Challonge: A to Z, then AA, AB and so on.
+fn identifier(index: usize) -> String {
+    let letter = (b'A' + (index % 26) as u8) as char;
+    if index < 26 {
+        letter.to_string()
+    } else {
+        format!("{}{}", identifier(index / 26 - 1), letter)
+    }
+}
@@ -0,0 +1,37 @@
+/* C ABI of the challonge crate, built with the `ffi` feature. */
+
+#ifndef CHALLONGE_H
+#define CHALLONGE_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct Challonge Challonge;
+
+/* Creates a client, NULL on failure. Release it with challonge_client_free. */
+Challonge *challonge_client_new(const char *user_name, const char *api_key);
+
+void challonge_client_free(Challonge *client);
+
+/* Tournament with participants and matches as JSON, NULL on failure.
+ * Release the string with challonge_string_free. */
+char *challonge_tournament_json(const Challonge *client, const char *tournament);
+
+/* Reports match scores such as "3-1,2-3". A winner_id of 0 only updates the scores.
+ * Returns 0 on success and -1 on failure. */
+int challonge_report_score(const Challonge *client, const char *tournament, uint64_t match_id,
+                           const char *scores_csv, uint64_t winner_id);
+
+/* Description of the last error of the calling thread, NULL if there was none. */
+const char *challonge_last_error(void);
+
+void challonge_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,142 @@
+//! Anonymization of personal data in exported participants.
+//!
+//! Emails and usernames are replaced with stable pseudonyms: the same value anonymized
+//! with the same salt always gets the same pseudonym, so exports can still be joined
+//! across tournaments. `misc` fields and avatar urls are stripped.
+
+use serde_json::Value;
+
+use participants::Participant;
+
+/// Json keys replaced with a pseudonymous email.
+const EMAIL_KEYS: &'static [&'static str] = &[
+    "invite_email",
+    "email",
+    "display_name_with_invitation_email_address",
+];
+
+/// Json keys replaced with a pseudonymous username.
+const USERNAME_KEYS: &'static [&'static str] = &["challonge_username", "username"];
+
+/// Json keys whose values are removed.
+const STRIPPED_KEYS: &'static [&'static str] = &[
+    "misc",
+    "email_hash",
+    "attached_participatable_portrait_url",
+    "challonge_email_address_verified",
+];
+
+/// Replaces personal data with stable pseudonyms.
+#[derive(Debug, Clone)]
+pub struct Anonymizer {
+    salt: String,
+}
+impl Anonymizer {
+    /// Creates an anonymizer. Keep the salt secret, otherwise pseudonyms of known emails can be recomputed.
+    pub fn new<S: Into<String>>(salt: S) -> Anonymizer {
+        Anonymizer { salt: salt.into() }
+    }
+
+    /// Returns the stable pseudonym of a value, e.g. `anon-1f2e3d4c5b6a7988`.
+    pub fn pseudonym(&self, value: &str) -> String {
+        // FNV-1a, unlike the std hasher its output never changes between releases.
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for b in self.salt.bytes().chain(0..1u8).chain(value.bytes()) {
+            hash ^= b as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        format!("anon-{:016x}", hash)
+    }
+
+    fn email(&self, value: &str) -> String {
+        if value.is_empty() {
+            String::new()
+        } else {
+            format!("{}@example.invalid", self.pseudonym(&value.to_lowercase()))
+        }
+    }
+
+    fn username(&self, value: &str) -> String {
+        if value.is_empty() {
+            String::new()
+        } else {
+            self.pseudonym(&value.to_lowercase())
+        }
+    }
+
+    /// Anonymizes a participant.
+    pub fn participant(&self, p: &mut Participant) {
+        p.invite_email = self.email(&p.invite_email);
+        p.display_name_with_invitation_email_address =
+            self.email(&p.display_name_with_invitation_email_address);
+        p.challonge_username = self.username(&p.challonge_username);
+        p.username = self.username(&p.username);
+        p.misc.clear();
+        p.email_hash.clear();
+        p.attached_participatable_portrait_url.clear();
+        p.challonge_email_address_verified.clear();
+    }
+
+    /// Anonymizes a json document as returned by the API, e.g. a tournament with its participants.
+    pub fn json(&self, value: &mut Value) {
+        match *value {
+            Value::Object(ref mut map) => {
+                for (key, v) in map.iter_mut() {
+                    let replacement = match v.as_string() {
+                        Some(s) if EMAIL_KEYS.contains(&&key[..]) => Value::String(self.email(s)),
+                        Some(s) if USERNAME_KEYS.contains(&&key[..]) => {
+                            Value::String(self.username(s))
+                        }
+                        Some(_) if STRIPPED_KEYS.contains(&&key[..]) => Value::Null,
+                        _ => {
+                            self.json(v);
+                            continue;
+                        }
+                    };
+                    *v = replacement;
+                }
+            }
+            Value::Array(ref mut items) => {
+                for item in items.iter_mut() {
+                    self.json(item);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use anonymize::Anonymizer;
+
+    #[test]
+    fn test_anonymize_json() {
+        let mut value: serde_json::Value = serde_json::from_str(
+            r#"{"tournament": {"name": "Weekly", "participants": [
+                {"participant": {"name": "Bob", "invite_email": "Bob@example.com",
+                                 "challonge_username": "bob", "misc": "user:12"}},
+                {"participant": {"name": "Bob 2", "invite_email": "bob@example.com",
+                                 "challonge_username": null, "misc": null}}
+            ]}}"#,
+        )
+        .unwrap();
+        let a = Anonymizer::new("secret");
+        a.json(&mut value);
+
+        let text = serde_json::to_string(&value).unwrap();
+        assert!(!text.contains("example.com"));
+        assert!(!text.contains("user:12"));
+        assert!(text.contains("\"Weekly\""));
+        let email = format!("{}@example.invalid", a.pseudonym("bob@example.com"));
+        assert_eq!(text.matches(&email[..]).count(), 2);
+
+        assert_eq!(
+            a.pseudonym("bob"),
+            Anonymizer::new("secret").pseudonym("bob")
+        );
+        assert!(a.pseudonym("bob") != Anonymizer::new("other").pseudonym("bob"));
+    }
+}
@@ -0,0 +1,485 @@
+//! Conversions between the payloads of the Challonge API v2.1 and the model types of this crate.
+//!
+//! The v2 API returns JSON:API documents, e.g.
+//! `{"data": {"id": "1086875", "type": "tournament", "attributes": {...}, "relationships": {...}}}`,
+//! with timestamps and options grouped into nested objects. `Resource` holds one such record.
+//! Tournaments, participants and matches convert into `Tournament`, `Participant` and `Match`
+//! with `TryFrom`, so a `Backend` talking to the v2 API hands out the same types as the v1 client.
+//! Fields which only one of the versions has are left at their defaults.
+//!
+//! A `ParticipantCreate` converts into a v2 participant resource to send.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+use error::Error;
+use matches::Match;
+use participants::{Participant, ParticipantCreate};
+use tournament::Tournament;
+use util::{as_map, field};
+
+/// v1 participant fields the v2 API has no counterpart for, `null` after conversion.
+const PARTICIPANT_FIELDS: &'static [&'static str] = &[
+    "active",
+    "attached_participatable_portrait_url",
+    "can_check_in",
+    "challonge_email_address_verified",
+    "challonge_username",
+    "checked_in",
+    "checked_in_at",
+    "confirm_remove",
+    "display_name_with_invitation_email_address",
+    "email_hash",
+    "final_rank",
+    "group_id",
+    "icon",
+    "invitation_id",
+    "invitation_pending",
+    "invite_email",
+    "misc",
+    "on_waiting_list",
+    "participatable_or_invitation_attached",
+    "reactivatable",
+    "removable",
+    "username",
+];
+
+/// v1 match fields the v2 API has no counterpart for, `null` after conversion.
+const MATCH_FIELDS: &'static [&'static str] = &[
+    "attachment_count",
+    "has_attachment",
+    "identifier",
+    "loser_id",
+    "player1_id",
+    "player1_is_prereq_match_loser",
+    "player1_prereq_match_id",
+    "player1_votes",
+    "player2_id",
+    "player2_is_prereq_match_loser",
+    "player2_prereq_match_id",
+    "player2_votes",
+    "prerequisite_match_ids_csv",
+    "scores_csv",
+    "started_at",
+    "winner_id",
+];
+
+/// A JSON:API resource object of the v2 API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resource {
+    /// Identifier of the record, a string in JSON:API
+    pub id: String,
+
+    /// Type of the record, e.g. `tournament`
+    pub kind: String,
+
+    /// Attributes of the record
+    pub attributes: BTreeMap<String, Value>,
+
+    /// Related records, each `{"data": {"id": ..., "type": ...}}`
+    pub relationships: BTreeMap<String, Value>,
+}
+impl Resource {
+    /// Decodes the resource of a document, or a bare resource object.
+    pub fn decode(value: &Value) -> Result<Resource, Error> {
+        let map = try!(as_map(value));
+        let map = match map.get("data") {
+            Some(data) => try!(as_map(data)),
+            None => map,
+        };
+        let id = match *try!(field(map, "id")) {
+            Value::String(ref id) => id.clone(),
+            Value::U64(id) => id.to_string(),
+            ref other => return Err(Error::Decode("Expected resource id", other.clone())),
+        };
+        let object = |key: &str| match map.get(key) {
+            Some(&Value::Object(ref o)) => o.clone(),
+            _ => BTreeMap::new(),
+        };
+        Ok(Resource {
+            id: id,
+            kind: try!(field(map, "type"))
+                .as_string()
+                .unwrap_or("")
+                .to_owned(),
+            attributes: object("attributes"),
+            relationships: object("relationships"),
+        })
+    }
+
+    /// Decodes the resources of a document whose `data` is an array.
+    pub fn decode_all(value: &Value) -> Result<Vec<Resource>, Error> {
+        let map = try!(as_map(value));
+        match *try!(field(map, "data")) {
+            Value::Array(ref data) => data.iter().map(Resource::decode).collect(),
+            ref other => Err(Error::Decode("Expected resource array", other.clone())),
+        }
+    }
+
+    /// Encodes the resource into a JSON:API document.
+    pub fn encode(&self) -> Value {
+        let mut data = BTreeMap::new();
+        data.insert("id".to_owned(), Value::String(self.id.clone()));
+        data.insert("type".to_owned(), Value::String(self.kind.clone()));
+        data.insert(
+            "attributes".to_owned(),
+            Value::Object(self.attributes.clone()),
+        );
+        if !self.relationships.is_empty() {
+            data.insert(
+                "relationships".to_owned(),
+                Value::Object(self.relationships.clone()),
+            );
+        }
+        let mut document = BTreeMap::new();
+        document.insert("data".to_owned(), Value::Object(data));
+        Value::Object(document)
+    }
+
+    /// Returns the id of a related record, e.g. `player1` of a match.
+    pub fn related_id(&self, name: &str) -> Option<u64> {
+        self.relationships
+            .get(name)
+            .and_then(|r| r.as_object())
+            .and_then(|r| r.get("data"))
+            .and_then(|d| d.as_object())
+            .and_then(|d| d.get("id"))
+            .and_then(number)
+    }
+
+    fn expect(&self, kind: &str) -> Result<(), Error> {
+        if self.kind.trim_end_matches('s').eq_ignore_ascii_case(kind) {
+            Ok(())
+        } else {
+            Err(Error::Decode(
+                "Unexpected resource type",
+                Value::String(self.kind.clone()),
+            ))
+        }
+    }
+
+    /// Copies the attribute, or the attribute nested in `group`, to `key` of a v1 record.
+    fn copy(&self, v1: &mut BTreeMap<String, Value>, group: Option<&str>, name: &str, key: &str) {
+        let value = match group {
+            Some(group) => self
+                .attributes
+                .get(group)
+                .and_then(|g| g.as_object())
+                .and_then(|g| g.get(name)),
+            None => self.attributes.get(name),
+        };
+        if let Some(value) = value {
+            v1.insert(key.to_owned(), value.clone());
+        }
+    }
+
+    /// Starts a v1 record with the id, the timestamps and the `counters` the v1 decoders require.
+    fn v1_record(&self, counters: &[&str]) -> Result<BTreeMap<String, Value>, Error> {
+        let mut v1 = BTreeMap::new();
+        let id = try!(number(&Value::String(self.id.clone())).ok_or(Error::Decode(
+            "Expected numeric id",
+            Value::String(self.id.clone())
+        )));
+        v1.insert("id".to_owned(), Value::U64(id));
+        for key in &["created_at", "updated_at"] {
+            self.copy(&mut v1, Some("timestamps"), key, key);
+        }
+        for key in counters {
+            match self.attributes.get(*key).and_then(number) {
+                Some(n) => v1.insert((*key).to_owned(), Value::U64(n)),
+                None => {
+                    return Err(Error::Decode(
+                        "Missing attribute",
+                        Value::String((*key).to_owned()),
+                    ))
+                }
+            };
+        }
+        Ok(v1)
+    }
+}
+
+/// Reads a number which JSON:API may return as a string.
+fn number(value: &Value) -> Option<u64> {
+    match *value {
+        Value::U64(n) => Some(n),
+        Value::String(ref s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn wrap(key: &str, record: BTreeMap<String, Value>) -> Value {
+    let mut root = BTreeMap::new();
+    root.insert(key.to_owned(), Value::Object(record));
+    Value::Object(root)
+}
+
+impl<'a> TryFrom<&'a Resource> for Tournament {
+    type Error = Error;
+
+    fn try_from(resource: &'a Resource) -> Result<Tournament, Error> {
+        try!(resource.expect("tournament"));
+        let mut t = try!(resource.v1_record(&[]));
+        for key in &[
+            "name",
+            "url",
+            "description",
+            "tournament_type",
+            "state",
+            "private",
+            "game_name",
+            "full_challonge_url",
+            "live_image_url",
+            "sign_up_url",
+        ] {
+            resource.copy(&mut t, None, key, key);
+        }
+        resource.copy(&mut t, Some("timestamps"), "starts_at", "start_at");
+        resource.copy(&mut t, Some("timestamps"), "started_at", "started_at");
+        for key in &["signup_cap", "check_in_duration", "open_signup"] {
+            resource.copy(&mut t, Some("registration_options"), key, key);
+        }
+        for key in &["hide_seeds", "sequential_pairings"] {
+            resource.copy(&mut t, Some("seeding_options"), key, key);
+        }
+        resource.copy(
+            &mut t,
+            Some("match_options"),
+            "accept_attachments",
+            "accept_attachments",
+        );
+        Tournament::decode_legacy(&wrap("tournament", t))
+    }
+}
+
+impl<'a> TryFrom<&'a Resource> for Participant {
+    type Error = Error;
+
+    fn try_from(resource: &'a Resource) -> Result<Participant, Error> {
+        try!(resource.expect("participant"));
+        let mut p = try!(resource.v1_record(&["seed", "tournament_id"]));
+        for key in &["name", "group_id", "final_rank", "misc", "username"] {
+            resource.copy(&mut p, None, key, key);
+        }
+        resource.copy(&mut p, Some("states"), "active", "active");
+        resource.copy(&mut p, Some("timestamps"), "checked_in_at", "checked_in_at");
+        for key in PARTICIPANT_FIELDS {
+            p.entry((*key).to_owned()).or_insert(Value::Null);
+        }
+        if p["active"] == Value::Null {
+            p.insert("active".to_owned(), Value::Bool(true));
+        }
+        Participant::decode(&wrap("participant", p))
+    }
+}
+
+impl<'a> TryFrom<&'a Resource> for Match {
+    type Error = Error;
+
+    fn try_from(resource: &'a Resource) -> Result<Match, Error> {
+        try!(resource.expect("match"));
+        let mut m = try!(resource.v1_record(&["round", "tournament_id"]));
+        for key in &["state", "identifier", "winner_id"] {
+            resource.copy(&mut m, None, key, key);
+        }
+        resource.copy(&mut m, Some("timestamps"), "started_at", "started_at");
+        let players = [
+            resource.related_id("player1"),
+            resource.related_id("player2"),
+        ];
+        for (prefix, id) in ["player1_", "player2_"].iter().zip(players.iter()) {
+            if let Some(id) = *id {
+                m.insert(format!("{}id", prefix), Value::U64(id));
+            }
+        }
+        if let Some(sets) = resource
+            .attributes
+            .get("score_in_sets")
+            .and_then(|s| s.as_array())
+        {
+            let scores: Vec<String> = sets
+                .iter()
+                .filter_map(|set| set.as_array())
+                .filter_map(|set| {
+                    match (set.get(0).and_then(number), set.get(1).and_then(number)) {
+                        (Some(a), Some(b)) => Some(format!("{}-{}", a, b)),
+                        _ => None,
+                    }
+                })
+                .collect();
+            m.insert("scores_csv".to_owned(), Value::String(scores.join(",")));
+        }
+        if let Some(winner) = m.get("winner_id").and_then(number) {
+            m.insert("winner_id".to_owned(), Value::U64(winner));
+            let loser = players.iter().filter_map(|p| *p).find(|p| *p != winner);
+            if let Some(loser) = loser {
+                m.insert("loser_id".to_owned(), Value::U64(loser));
+            }
+        }
+        for key in MATCH_FIELDS {
+            m.entry((*key).to_owned()).or_insert(Value::Null);
+        }
+        Match::decode(&wrap("match", m))
+    }
+}
+
+impl<'a> From<&'a ParticipantCreate> for Resource {
+    fn from(participant: &'a ParticipantCreate) -> Resource {
+        let mut attributes = BTreeMap::new();
+        let mut text = |key: &str, value: &str| {
+            if !value.is_empty() {
+                attributes.insert(key.to_owned(), Value::String(value.to_owned()));
+            }
+        };
+        text("name", participant.name.as_ref().map_or("", |n| n.as_str()));
+        text(
+            "username",
+            participant
+                .challonge_username
+                .as_ref()
+                .map_or("", |n| n.as_str()),
+        );
+        text("email", &participant.email);
+        text("misc", &participant.misc);
+        attributes.insert("seed".to_owned(), Value::U64(participant.seed));
+        Resource {
+            id: String::new(),
+            kind: "Participants".to_owned(),
+            attributes: attributes,
+            relationships: BTreeMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use std::convert::TryFrom;
+
+    use api_v2::Resource;
+    use matches::{Match, MatchState};
+    use participants::{Participant, ParticipantCreate, ParticipantId};
+    use tournament::{Tournament, TournamentId, TournamentStatus, TournamentType};
+
+    #[test]
+    fn test_tournament() {
+        let document = serde_json::from_str(
+            r#"{
+              "data": {
+                "id": "1086875",
+                "type": "tournament",
+                "attributes": {
+                  "name": "Sample Tournament 1",
+                  "url": "sample_tournament_1",
+                  "tournament_type": "single elimination",
+                  "state": "underway",
+                  "private": false,
+                  "description": "sample description",
+                  "timestamps": {
+                    "created_at": "2015-01-19T16:47:30-05:00",
+                    "updated_at": "2015-01-19T16:57:17-05:00",
+                    "starts_at": "2015-01-19T17:00:00-05:00",
+                    "started_at": "2015-01-19T16:57:17-05:00",
+                    "completed_at": null
+                  },
+                  "registration_options": {"signup_cap": 8, "open_signup": true},
+                  "seeding_options": {"hide_seeds": true}
+                }
+              }
+            }"#,
+        )
+        .unwrap();
+        let t = Tournament::try_from(&Resource::decode(&document).unwrap()).unwrap();
+        assert_eq!(t.id, TournamentId::Id(1086875));
+        assert_eq!(t.name, "Sample Tournament 1");
+        assert_eq!(t.tournament_type, TournamentType::SingleElimination);
+        assert_eq!(t.state, TournamentStatus::Underway);
+        assert_eq!(t.signup_cap, Some(8));
+        assert!(t.open_signup);
+        assert!(t.hide_seeds);
+        assert!(t.start_at.is_some());
+        assert!(t.started_at.is_some());
+    }
+
+    #[test]
+    fn test_participant_and_match() {
+        let document = serde_json::from_str(
+            r#"{
+              "data": [{
+                "id": "16543993",
+                "type": "participant",
+                "attributes": {
+                  "name": "Participant #1",
+                  "seed": 1,
+                  "tournament_id": 1086875,
+                  "misc": "id=7",
+                  "states": {"active": false},
+                  "timestamps": {
+                    "created_at": "2015-01-19T16:54:40-05:00",
+                    "updated_at": "2015-01-19T16:54:40-05:00"
+                  }
+                }
+              }]
+            }"#,
+        )
+        .unwrap();
+        let resources = Resource::decode_all(&document).unwrap();
+        let p = Participant::try_from(&resources[0]).unwrap();
+        assert_eq!(p.id, ParticipantId(16543993));
+        assert_eq!(p.name, "Participant #1");
+        assert_eq!(p.misc, "id=7");
+        assert!(!p.active);
+        // a participant is not a match
+        assert!(Match::try_from(&resources[0]).is_err());
+
+        let document = serde_json::from_str(
+            r#"{
+              "data": {
+                "id": "23575258",
+                "type": "match",
+                "attributes": {
+                  "state": "complete",
+                  "round": 1,
+                  "identifier": "A",
+                  "tournament_id": "1086875",
+                  "winner_id": 16543993,
+                  "score_in_sets": [[3, 1], [2, 2]],
+                  "timestamps": {
+                    "created_at": "2015-01-19T16:57:17-05:00",
+                    "updated_at": "2015-01-19T17:12:40-05:00",
+                    "started_at": "2015-01-19T16:57:17-05:00"
+                  }
+                },
+                "relationships": {
+                  "player1": {"data": {"id": "16543993", "type": "participant"}},
+                  "player2": {"data": {"id": "16543997", "type": "participant"}}
+                }
+              }
+            }"#,
+        )
+        .unwrap();
+        let m = Match::try_from(&Resource::decode(&document).unwrap()).unwrap();
+        assert_eq!(m.state, MatchState::Complete);
+        assert_eq!(m.tournament_id, TournamentId::Id(1086875));
+        assert_eq!(m.player2.id, Some(ParticipantId(16543997)));
+        assert_eq!(m.winner_id, Some(ParticipantId(16543993)));
+        assert_eq!(m.loser_id, Some(ParticipantId(16543997)));
+        assert_eq!(m.scores_csv.to_string(), "3-1,2-2");
+    }
+
+    #[test]
+    fn test_participant_create() {
+        let mut create = ParticipantCreate::new();
+        create.name("Participant #3").seed(3);
+        let encoded = Resource::from(&create).encode();
+        let decoded = Resource::decode(&encoded).unwrap();
+        assert_eq!(decoded.kind, "Participants");
+        assert_eq!(
+            decoded.attributes.get("name").and_then(|n| n.as_string()),
+            Some("Participant #3")
+        );
+        assert!(decoded.attributes.get("email").is_none());
+    }
+}
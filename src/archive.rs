@@ -0,0 +1,323 @@
+//! Archiving of a whole account to a directory.
+//!
+//! Every tournament is written with its participants and matches as returned by the API
+//! to `tournaments/<id>.json`, and `manifest.json` lists the archived tournaments.
+//! Running the archive again into the same directory only fetches tournaments
+//! which were updated since the previous run or failed in it.
+//! The index is decoded with `Tournament::decode_legacy`, tolerating tournaments from the first years of Challonge.
+//! Archives can be anonymized for publishing, see `ArchiveOptions::anonymize`.
+
+extern crate serde_json;
+
+use serde_json::Value;
+use std::cmp;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use anonymize::Anonymizer;
+use error::Error;
+use tournament::{Tournament, TournamentId, TournamentIncludes, TournamentState};
+use Challonge;
+
+/// Name of the manifest file in the archive directory.
+pub const MANIFEST_FILE: &'static str = "manifest.json";
+
+/// Directory of the tournament files inside the archive directory.
+pub const TOURNAMENTS_DIR: &'static str = "tournaments";
+
+/// Options of an archive run.
+#[derive(Debug, Clone)]
+pub struct ArchiveOptions {
+    /// Maximum number of tournaments fetched at a time
+    pub concurrency: usize,
+
+    /// If set, personal data of participants is replaced with pseudonyms before it is written
+    pub anonymizer: Option<Anonymizer>,
+}
+impl ArchiveOptions {
+    /// Creates options with the given concurrency and no anonymization.
+    pub fn new(concurrency: usize) -> ArchiveOptions {
+        ArchiveOptions {
+            concurrency: concurrency,
+            anonymizer: None,
+        }
+    }
+
+    builder!(concurrency, usize);
+
+    /// Anonymizes archived participants with the given salt.
+    pub fn anonymize<'a, S: Into<String>>(&'a mut self, salt: S) -> &'a mut Self {
+        self.anonymizer = Some(Anonymizer::new(salt));
+        self
+    }
+}
+
+/// Result of an archive run.
+#[derive(Debug)]
+pub struct ArchiveReport {
+    /// Tournaments fetched and written during this run
+    pub archived: Vec<TournamentId>,
+
+    /// Tournaments not updated since the previous run
+    pub skipped: Vec<TournamentId>,
+
+    /// Tournaments which could not be archived
+    pub failed: Vec<(TournamentId, Error)>,
+}
+impl ArchiveReport {
+    /// Returns `true` if every tournament was archived.
+    pub fn is_ok(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// A tournament listed in the manifest.
+#[derive(Debug, Clone, PartialEq)]
+struct ManifestEntry {
+    id: String,
+    name: String,
+    url: String,
+    updated_at: String,
+    file: String,
+}
+impl ManifestEntry {
+    fn new(t: &Tournament) -> ManifestEntry {
+        let id = t.id.to_string();
+        ManifestEntry {
+            file: format!("{}/{}.json", TOURNAMENTS_DIR, id),
+            id: id,
+            name: t.name.clone(),
+            url: t.url.clone(),
+            updated_at: t.updated_at.to_rfc3339(),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let mut map = BTreeMap::new();
+        map.insert("id".to_owned(), Value::String(self.id.clone()));
+        map.insert("name".to_owned(), Value::String(self.name.clone()));
+        map.insert("url".to_owned(), Value::String(self.url.clone()));
+        map.insert(
+            "updated_at".to_owned(),
+            Value::String(self.updated_at.clone()),
+        );
+        map.insert("file".to_owned(), Value::String(self.file.clone()));
+        Value::Object(map)
+    }
+}
+
+/// Archives every tournament of the account into `output_dir`,
+/// fetching no more than `concurrency` tournaments at a time.
+///
+/// Failures of single tournaments are collected into the report, they are retried by the next run.
+/// # Example
+/// ```ignore
+/// extern crate challonge;
+///
+/// use self::challonge::{archive_account, Challonge};
+///
+/// let c = Challonge::builder()
+///     .credentials("myusername", "myapikey")
+///     .build()
+///     .unwrap();
+/// let report = archive_account(&c, "backup-2016", 4).unwrap();
+/// println!("{} archived, {} failed", report.archived.len(), report.failed.len());
+/// ```
+pub fn archive_account<P: AsRef<Path>>(
+    client: &Challonge,
+    output_dir: P,
+    concurrency: usize,
+) -> Result<ArchiveReport, Error> {
+    archive_account_with(client, output_dir, &ArchiveOptions::new(concurrency))
+}
+
+/// Archives every tournament of the account into `output_dir` with the given options.
+/// # Example
+/// ```ignore
+/// extern crate challonge;
+///
+/// use self::challonge::Challonge;
+/// use self::challonge::archive::{archive_account_with, ArchiveOptions};
+///
+/// let c = Challonge::builder()
+///     .credentials("myusername", "myapikey")
+///     .build()
+///     .unwrap();
+/// let mut options = ArchiveOptions::new(4);
+/// options.anonymize("keep this salt secret");
+/// let report = archive_account_with(&c, "public-stats", &options).unwrap();
+/// ```
+pub fn archive_account_with<P: AsRef<Path>>(
+    client: &Challonge,
+    output_dir: P,
+    options: &ArchiveOptions,
+) -> Result<ArchiveReport, Error> {
+    let dir = output_dir.as_ref().to_path_buf();
+    try!(fs::create_dir_all(dir.join(TOURNAMENTS_DIR)));
+    let previous = read_manifest(&dir.join(MANIFEST_FILE));
+    let index =
+        try!(client.fetch_legacy_tournament_index(&[("state", TournamentState::All.to_string())]));
+
+    let mut report = ArchiveReport {
+        archived: Vec::new(),
+        skipped: Vec::new(),
+        failed: Vec::new(),
+    };
+    let mut entries = Vec::new();
+    let mut queue = VecDeque::new();
+    for t in &index.0 {
+        let entry = ManifestEntry::new(t);
+        let unchanged = previous.get(&entry.id) == Some(&entry.updated_at);
+        if unchanged && dir.join(&entry.file).exists() {
+            report.skipped.push(t.id.clone());
+        } else {
+            queue.push_back(t.id.clone());
+        }
+        entries.push(entry);
+    }
+
+    let queue = Arc::new(Mutex::new(queue));
+    let (sender, receiver) = mpsc::channel();
+    let workers: Vec<_> = (0..cmp::max(options.concurrency, 1))
+        .map(|_| {
+            let client = client.clone();
+            let anonymizer = options.anonymizer.clone();
+            let queue = queue.clone();
+            let sender = sender.clone();
+            let dir = dir.clone();
+            thread::spawn(move || loop {
+                let id = match queue.lock().unwrap().pop_front() {
+                    Some(id) => id,
+                    None => break,
+                };
+                let result = archive_tournament(&client, &dir, &id, anonymizer.as_ref());
+                if sender.send((id, result)).is_err() {
+                    break;
+                }
+            })
+        })
+        .collect();
+    drop(sender);
+    for (id, result) in receiver {
+        match result {
+            Ok(()) => report.archived.push(id),
+            Err(e) => report.failed.push((id, e)),
+        }
+    }
+    for worker in workers {
+        let _ = worker.join();
+    }
+
+    let failed: Vec<String> = report.failed.iter().map(|f| f.0.to_string()).collect();
+    entries.retain(|e| !failed.contains(&e.id));
+    try!(write_manifest(&dir.join(MANIFEST_FILE), &entries, &failed));
+    Ok(report)
+}
+
+fn archive_tournament(
+    client: &Challonge,
+    dir: &Path,
+    id: &TournamentId,
+    anonymizer: Option<&Anonymizer>,
+) -> Result<(), Error> {
+    let mut value = try!(client.get_tournament_json(id, &TournamentIncludes::All));
+    if let Some(a) = anonymizer {
+        a.json(&mut value);
+    }
+    let path = dir.join(TOURNAMENTS_DIR).join(format!("{}.json", id));
+    write_atomically(&path, &try!(serde_json::to_string_pretty(&value)))
+}
+
+/// Writes the file next to its destination first, so an interrupted run never leaves a truncated file.
+fn write_atomically(path: &Path, contents: &str) -> Result<(), Error> {
+    let mut tmp = PathBuf::from(path);
+    tmp.set_extension("json.tmp");
+    {
+        let mut file = try!(fs::File::create(&tmp));
+        try!(file.write_all(contents.as_bytes()));
+        try!(file.sync_all());
+    }
+    try!(fs::rename(&tmp, path));
+    Ok(())
+}
+
+fn write_manifest(path: &Path, entries: &[ManifestEntry], failed: &[String]) -> Result<(), Error> {
+    let mut map = BTreeMap::new();
+    map.insert(
+        "tournaments".to_owned(),
+        Value::Array(entries.iter().map(|e| e.to_json()).collect()),
+    );
+    map.insert(
+        "failed".to_owned(),
+        Value::Array(failed.iter().map(|f| Value::String(f.clone())).collect()),
+    );
+    let contents = try!(serde_json::to_string_pretty(&Value::Object(map)));
+    write_atomically(path, &contents)
+}
+
+/// Reads `updated_at` of every tournament listed in a manifest, an unreadable manifest is treated as empty.
+fn read_manifest(path: &Path) -> HashMap<String, String> {
+    let mut contents = String::new();
+    let read = fs::File::open(path).and_then(|mut f| f.read_to_string(&mut contents));
+    let value: Value = match read.ok().and_then(|_| serde_json::from_str(&contents).ok()) {
+        Some(v) => v,
+        None => return HashMap::new(),
+    };
+    let entries = match value.as_object().and_then(|m| m.get("tournaments")) {
+        Some(&Value::Array(ref entries)) => entries.clone(),
+        _ => return HashMap::new(),
+    };
+    entries
+        .iter()
+        .filter_map(|e| {
+            let e = match e.as_object() {
+                Some(e) => e,
+                None => return None,
+            };
+            match (
+                e.get("id").and_then(|v| v.as_string()),
+                e.get("updated_at").and_then(|v| v.as_string()),
+            ) {
+                (Some(id), Some(updated_at)) => Some((id.to_owned(), updated_at.to_owned())),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use archive::{read_manifest, write_manifest, ManifestEntry, MANIFEST_FILE};
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let dir = env::temp_dir().join("challonge-rs-archive-test");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(MANIFEST_FILE);
+
+        let entry = ManifestEntry {
+            id: "1086875".to_owned(),
+            name: "Sample Tournament 1".to_owned(),
+            url: "sample_tournament_1".to_owned(),
+            updated_at: "2015-01-19T16:57:17-05:00".to_owned(),
+            file: "tournaments/1086875.json".to_owned(),
+        };
+        write_manifest(&path, &[entry], &["42".to_owned()]).unwrap();
+
+        let previous = read_manifest(&path);
+        assert_eq!(previous.len(), 1);
+        assert_eq!(
+            previous.get("1086875").map(|s| &s[..]),
+            Some("2015-01-19T16:57:17-05:00")
+        );
+        assert!(read_manifest(&dir.join("missing.json")).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,296 @@
+//! Futures-returning client for tokio applications (`async` feature).
+//!
+//! `AsyncChallonge` wraps a `Challonge` client and runs each call on the blocking thread pool of
+//! tokio, so awaiting it never stalls the worker threads of the runtime. The client keeps all its
+//! settings, guards and statistics, which are shared with the wrapped client.
+//!
+//! ```ignore
+//! extern crate challonge;
+//!
+//! use self::challonge::{AsyncChallonge, Challonge, TournamentId, TournamentIncludes};
+//!
+//! let c = AsyncChallonge::new(
+//!     Challonge::builder()
+//!         .credentials("myusername", "myapikey")
+//!         .build()
+//!         .unwrap(),
+//! );
+//! let t = c
+//!     .get_tournament(&TournamentId::Id(2669881), &TournamentIncludes::All)
+//!     .await;
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use chrono::date::Date;
+use chrono::offset::local::Local;
+use tokio::task::{self, JoinHandle};
+
+use error::Error;
+use matches::{Index as MatchIndex, Match, MatchId, MatchIndexOptions, MatchState, MatchUpdate};
+use participants::{
+    Index as ParticipantIndex, Participant, ParticipantCreate, ParticipantId,
+    ParticipantIndexOptions,
+};
+use tournament::{
+    Index as TournamentIndex, Tournament, TournamentCreate, TournamentId, TournamentIncludes,
+    TournamentIndexOptions, TournamentState, TournamentType,
+};
+use Challonge;
+
+/// Result of a call of `AsyncChallonge`.
+#[derive(Debug)]
+pub struct Call<T> {
+    handle: JoinHandle<Result<T, Error>>,
+}
+impl<T> Future for Call<T> {
+    type Output = Result<T, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<T, Error>> {
+        match Pin::new(&mut self.handle).poll(cx) {
+            Poll::Ready(Ok(result)) => Poll::Ready(result),
+            Poll::Ready(Err(e)) => Poll::Ready(Err(Error::Transport(e.to_string()))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Client for the Challonge REST API returning futures.
+/// Must be used within a tokio runtime.
+#[derive(Debug, Clone)]
+pub struct AsyncChallonge {
+    client: Challonge,
+}
+impl AsyncChallonge {
+    /// Wraps a client.
+    pub fn new(client: Challonge) -> AsyncChallonge {
+        AsyncChallonge { client: client }
+    }
+
+    /// Returns the wrapped client.
+    pub fn blocking(&self) -> &Challonge {
+        &self.client
+    }
+
+    /// Runs `f` with the client on the blocking thread pool.
+    /// Use it for calls which have no async variant.
+    pub fn spawn<T, F>(&self, f: F) -> Call<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Challonge) -> Result<T, Error> + Send + 'static,
+    {
+        let client = self.client.clone();
+        Call {
+            handle: task::spawn_blocking(move || f(&client)),
+        }
+    }
+
+    /// Retrieve a set of tournaments created with your account.
+    pub fn tournament_index(
+        &self,
+        state: &TournamentState,
+        tournament_type: &TournamentType,
+        created_after: &Date<Local>,
+        created_before: &Date<Local>,
+        subdomain: &str,
+    ) -> Call<TournamentIndex> {
+        let (state, tournament_type) = (state.clone(), tournament_type.clone());
+        let (created_after, created_before) = (created_after.clone(), created_before.clone());
+        let subdomain = subdomain.to_owned();
+        self.spawn(move |c| {
+            c.tournament_index(
+                &state,
+                &tournament_type,
+                &created_after,
+                &created_before,
+                &subdomain,
+            )
+        })
+    }
+
+    /// Retrieve tournaments created with your account matching the options.
+    pub fn tournament_index_with(&self, options: &TournamentIndexOptions) -> Call<TournamentIndex> {
+        let options = options.clone();
+        self.spawn(move |c| c.tournament_index_with(&options))
+    }
+
+    /// Retrieve a single tournament record created with your account.
+    pub fn get_tournament(
+        &self,
+        id: &TournamentId,
+        includes: &TournamentIncludes,
+    ) -> Call<Tournament> {
+        let (id, includes) = (id.clone(), includes.clone());
+        self.spawn(move |c| c.get_tournament(&id, &includes))
+    }
+
+    /// Create a new tournament.
+    pub fn create_tournament(&self, tournament: &TournamentCreate) -> Call<Tournament> {
+        let tournament = tournament.clone();
+        self.spawn(move |c| c.create_tournament(&tournament))
+    }
+
+    /// Update a tournament's attributes.
+    pub fn update_tournament(
+        &self,
+        id: &TournamentId,
+        tournament: &TournamentCreate,
+    ) -> Call<Tournament> {
+        let (id, tournament) = (id.clone(), tournament.clone());
+        self.spawn(move |c| c.update_tournament(&id, &tournament))
+    }
+
+    /// Deletes a tournament along with all its associated records.
+    pub fn delete_tournament(&self, id: &TournamentId) -> Call<Tournament> {
+        let id = id.clone();
+        self.spawn(move |c| c.delete_tournament(&id))
+    }
+
+    /// Retrieve a tournament's participant list.
+    pub fn participant_index(&self, id: &TournamentId) -> Call<ParticipantIndex> {
+        let id = id.clone();
+        self.spawn(move |c| c.participant_index(&id))
+    }
+
+    /// Retrieve a tournament's participant list matching the options.
+    pub fn participant_index_with(
+        &self,
+        id: &TournamentId,
+        options: &ParticipantIndexOptions,
+    ) -> Call<ParticipantIndex> {
+        let (id, options) = (id.clone(), options.clone());
+        self.spawn(move |c| c.participant_index_with(&id, &options))
+    }
+
+    /// Add a participant to a tournament (up until it is started).
+    pub fn create_participant(
+        &self,
+        id: &TournamentId,
+        participant: &ParticipantCreate,
+    ) -> Call<Participant> {
+        let (id, participant) = (id.clone(), participant.clone());
+        self.spawn(move |c| c.create_participant(&id, &participant))
+    }
+
+    /// Retrieve a single participant record for a tournament.
+    pub fn get_participant(
+        &self,
+        id: &TournamentId,
+        participant_id: &ParticipantId,
+        include_matches: bool,
+    ) -> Call<Participant> {
+        let (id, participant_id) = (id.clone(), participant_id.clone());
+        self.spawn(move |c| c.get_participant(&id, &participant_id, include_matches))
+    }
+
+    /// Update the attributes of a tournament participant.
+    pub fn update_participant(
+        &self,
+        id: &TournamentId,
+        participant_id: &ParticipantId,
+        participant: &ParticipantCreate,
+    ) -> Call<()> {
+        let (id, participant_id) = (id.clone(), participant_id.clone());
+        let participant = participant.clone();
+        self.spawn(move |c| c.update_participant(&id, &participant_id, &participant))
+    }
+
+    /// Destroys or deactivates a participant.
+    pub fn delete_participant(
+        &self,
+        id: &TournamentId,
+        participant_id: &ParticipantId,
+    ) -> Call<()> {
+        let (id, participant_id) = (id.clone(), participant_id.clone());
+        self.spawn(move |c| c.delete_participant(&id, &participant_id))
+    }
+
+    /// Retrieve a tournament's match list.
+    pub fn match_index(
+        &self,
+        id: &TournamentId,
+        state: Option<MatchState>,
+        participant_id: Option<ParticipantId>,
+    ) -> Call<MatchIndex> {
+        let id = id.clone();
+        self.spawn(move |c| c.match_index(&id, state, participant_id))
+    }
+
+    /// Retrieve a tournament's match list matching the options.
+    pub fn match_index_with(
+        &self,
+        id: &TournamentId,
+        options: &MatchIndexOptions,
+    ) -> Call<MatchIndex> {
+        let (id, options) = (id.clone(), options.clone());
+        self.spawn(move |c| c.match_index_with(&id, &options))
+    }
+
+    /// Retrieve a single match record for a tournament.
+    pub fn get_match(
+        &self,
+        id: &TournamentId,
+        match_id: &MatchId,
+        include_attachments: bool,
+    ) -> Call<Match> {
+        let (id, match_id) = (id.clone(), match_id.clone());
+        self.spawn(move |c| c.get_match(&id, &match_id, include_attachments))
+    }
+
+    /// Update/submit the score(s) for a match.
+    pub fn update_match(
+        &self,
+        id: &TournamentId,
+        match_id: &MatchId,
+        match_update: &MatchUpdate,
+    ) -> Call<Match> {
+        let (id, match_id, match_update) = (id.clone(), match_id.clone(), match_update.clone());
+        self.spawn(move |c| c.update_match(&id, &match_id, &match_update))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_client::AsyncChallonge;
+    use error::Error;
+    use tokio::runtime::Builder;
+    use tournament::{TournamentId, TournamentIncludes};
+    use transport::{Request, Response, Transport};
+    use Challonge;
+
+    struct Fixture;
+
+    impl Transport for Fixture {
+        fn send(&self, request: &Request) -> Result<Response, Error> {
+            assert!(request
+                .url
+                .ends_with("/tournaments/1086875.json?include_participants=1&include_matches=1"));
+            Ok(Response {
+                status: 200,
+                headers: Vec::new(),
+                body: include_bytes!("../benches/fixtures/tournament.json").to_vec(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_get_tournament() {
+        let c = AsyncChallonge::new(
+            Challonge::builder()
+                .credentials("user", "key")
+                .transport(Fixture)
+                .build()
+                .unwrap(),
+        );
+        let runtime = Builder::new_current_thread().build().unwrap();
+        // calls are spawned on the runtime when they are made
+        let _runtime = runtime.enter();
+        let t = runtime
+            .block_on(c.get_tournament(&TournamentId::Id(1086875), &TournamentIncludes::All))
+            .unwrap();
+        assert_eq!(t.id, TournamentId::Id(1086875));
+        assert_eq!(c.blocking().stats().len(), 1);
+    }
+}
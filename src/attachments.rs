@@ -2,13 +2,15 @@
 
 extern crate serde_json;
 
-use chrono::*;
 use serde_json::Value;
-use std::collections::BTreeMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
 
+use datetime::ChallongeDateTime;
 use error::Error;
 use matches::MatchId;
-use util::{decode_array, into_map, remove};
+use util::{as_map, decode_array, field};
 
 /// Asset of a attachment
 #[derive(Debug, Clone)]
@@ -27,16 +29,16 @@ pub struct Asset {
 }
 impl Asset {
     /// Decodes `Asset` from `Attachment`'s JSON
-    pub fn decode(mut map: &mut BTreeMap<String, Value>) -> Result<Asset, Error> {
+    pub fn decode(map: &BTreeMap<String, Value>) -> Result<Asset, Error> {
         Ok(Asset {
-            file_name: try!(remove(&mut map, "asset_file_name"))
+            file_name: try!(field(map, "asset_file_name"))
                 .as_string()
                 .map_or(None, |f| Some(f.to_owned())),
-            content_type: try!(remove(&mut map, "asset_content_type"))
+            content_type: try!(field(map, "asset_content_type"))
                 .as_string()
                 .map_or(None, |f| Some(f.to_owned())),
-            file_size: try!(remove(&mut map, "asset_file_size")).as_u64(),
-            url: try!(remove(&mut map, "asset_url"))
+            file_size: try!(field(map, "asset_file_size")).as_u64(),
+            url: try!(field(map, "asset_url"))
                 .as_string()
                 .map_or(None, |f| Some(f.to_owned())),
         })
@@ -69,6 +71,71 @@ impl AttachmentCreate {
     builder_o!(asset, Vec<u8>);
     builder_so!(url);
     builder_so!(description);
+
+    /// Returns the SHA-256 digest of the uploaded file together with the description and the url,
+    /// `None` if there is no file.
+    pub fn content_hash(&self) -> Option<[u8; 32]> {
+        self.asset.as_ref().map(|asset| {
+            let mut hasher = Sha256::new();
+            let parts = [
+                Some(&asset[..]),
+                self.description.as_ref().map(|d| d.as_bytes()),
+                self.url.as_ref().map(|u| u.as_bytes()),
+            ];
+            for part in &parts {
+                // length-prefixed, so no two different uploads hash the same input
+                match *part {
+                    Some(bytes) => {
+                        hasher.update([1]);
+                        hasher.update((bytes.len() as u64).to_be_bytes());
+                        hasher.update(bytes);
+                    }
+                    None => hasher.update([0]),
+                }
+            }
+            let mut digest = [0; 32];
+            digest.copy_from_slice(&hasher.finalize());
+            digest
+        })
+    }
+}
+
+/// Attachments uploaded by a client, keyed by match and content hash,
+/// so identical uploads are not sent to the same match twice.
+#[derive(Debug, Default)]
+pub struct UploadCache {
+    uploads: Mutex<HashMap<(u64, [u8; 32]), Attachment>>,
+}
+impl UploadCache {
+    /// Creates an empty cache.
+    pub fn new() -> UploadCache {
+        UploadCache::default()
+    }
+
+    /// Returns the attachment uploaded to the match with the given content hash.
+    pub fn get(&self, match_id: &MatchId, hash: [u8; 32]) -> Option<Attachment> {
+        self.uploads
+            .lock()
+            .unwrap()
+            .get(&(match_id.0, hash))
+            .cloned()
+    }
+
+    /// Remembers an uploaded attachment.
+    pub fn insert(&self, hash: [u8; 32], attachment: Attachment) {
+        self.uploads
+            .lock()
+            .unwrap()
+            .insert((attachment.match_id.0, hash), attachment);
+    }
+
+    /// Forgets an attachment, e.g. after it was deleted.
+    pub fn remove(&self, match_id: &MatchId, attachment_id: &AttachmentId) {
+        self.uploads
+            .lock()
+            .unwrap()
+            .retain(|key, a| key.0 != match_id.0 || a.id.0 != attachment_id.0);
+    }
 }
 
 /// Unique attachment id
@@ -98,47 +165,43 @@ pub struct Attachment {
     pub original_file_name: Option<String>,
 
     /// Time when the attachment was created.
-    pub created_at: DateTime<FixedOffset>,
+    pub created_at: ChallongeDateTime,
 
     /// Time when the attachment was updated last time.
-    pub updated_at: DateTime<FixedOffset>,
+    pub updated_at: ChallongeDateTime,
 
     /// Asset information
     pub asset: Asset,
 }
 impl Attachment {
     /// Decodes `Attachment` from JSON
-    pub fn decode(value: Value) -> Result<Attachment, Error> {
-        let mut value = try!(into_map(value));
-        let t = try!(remove(&mut value, "match_attachment"));
-        let mut tv = try!(into_map(t));
+    pub fn decode(value: &Value) -> Result<Attachment, Error> {
+        let value = try!(as_map(value));
+        let t = try!(field(value, "match_attachment"));
+        let tv = try!(as_map(t));
 
         Ok(Attachment {
-            id: AttachmentId(try!(remove(&mut tv, "id")).as_u64().unwrap()),
-            match_id: MatchId(try!(remove(&mut tv, "match_id")).as_u64().unwrap()),
-            user_id: try!(remove(&mut tv, "user_id")).as_u64().unwrap(),
-            description: try!(remove(&mut tv, "description"))
+            id: AttachmentId(try!(field(tv, "id")).as_u64().unwrap()),
+            match_id: MatchId(try!(field(tv, "match_id")).as_u64().unwrap()),
+            user_id: try!(field(tv, "user_id")).as_u64().unwrap(),
+            description: try!(field(tv, "description"))
                 .as_string()
                 .map_or(None, |f| Some(f.to_owned())),
-            url: try!(remove(&mut tv, "url"))
+            url: try!(field(tv, "url"))
                 .as_string()
                 .map_or(None, |f| Some(f.to_owned())),
-            original_file_name: try!(remove(&mut tv, "original_file_name"))
+            original_file_name: try!(field(tv, "original_file_name"))
                 .as_string()
                 .map_or(None, |f| Some(f.to_owned())),
-            created_at: DateTime::parse_from_rfc3339(
-                try!(remove(&mut tv, "created_at"))
-                    .as_string()
-                    .unwrap_or(""),
-            )
-            .unwrap(),
-            updated_at: DateTime::parse_from_rfc3339(
-                try!(remove(&mut tv, "updated_at"))
-                    .as_string()
-                    .unwrap_or(""),
-            )
-            .unwrap(),
-            asset: Asset::decode(&mut tv).unwrap(),
+            created_at: try!(ChallongeDateTime::decode_required(try!(field(
+                tv,
+                "created_at"
+            )))),
+            updated_at: try!(ChallongeDateTime::decode_required(try!(field(
+                tv,
+                "updated_at"
+            )))),
+            asset: Asset::decode(tv).unwrap(),
         })
     }
 }
@@ -149,7 +212,7 @@ pub struct Index(pub Vec<Attachment>);
 
 impl Index {
     /// Decodes attachment index from JSON.
-    pub fn decode(value: Value) -> Result<Index, Error> {
+    pub fn decode(value: &Value) -> Result<Index, Error> {
         Ok(Index(try!(decode_array(value, Attachment::decode))))
     }
 }
@@ -158,7 +221,7 @@ impl Index {
 mod tests {
     extern crate serde_json;
 
-    use attachments::{Attachment, Index};
+    use attachments::{Attachment, AttachmentCreate, Index, UploadCache};
 
     #[test]
     fn test_attachment_parse() {
@@ -166,7 +229,7 @@ mod tests {
         let json_r = serde_json::from_str(string);
         assert!(json_r.is_ok());
         let json = json_r.unwrap();
-        if let Ok(m) = Attachment::decode(json) {
+        if let Ok(m) = Attachment::decode(&json) {
             assert_eq!(m.id.0, 165418);
             assert_eq!(m.match_id.0, 65187924);
             assert_eq!(m.user_id, 979950);
@@ -182,13 +245,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_upload_dedupe() {
+        let string = r#"{"match_attachment":{"id":165418,"match_id":65187924,"user_id":979950,"description":null,"url":null,"original_file_name":"shot.png","created_at":"2016-07-02T13:24:09.899-04:00","updated_at":"2016-07-02T13:24:09.899-04:00","asset_file_name":"shot.png","asset_content_type":"image/png","asset_file_size":3,"asset_url":null}}"#;
+        let attachment = Attachment::decode(&serde_json::from_str(string).unwrap()).unwrap();
+        let mut first = AttachmentCreate::new();
+        first.asset(b"png".to_vec());
+        let mut second = AttachmentCreate::new();
+        second.asset(b"png".to_vec());
+        let hash = first.content_hash().unwrap();
+        assert_eq!(second.content_hash(), Some(hash));
+        second.description("retry");
+        assert!(second.content_hash() != Some(hash));
+        assert_eq!(AttachmentCreate::new().content_hash(), None);
+
+        let cache = UploadCache::new();
+        cache.insert(hash, attachment.clone());
+        assert_eq!(cache.get(&attachment.match_id, hash).unwrap().id.0, 165418);
+        assert!(cache.get(&attachment.match_id, [0; 32]).is_none());
+        cache.remove(&attachment.match_id, &attachment.id);
+        assert!(cache.get(&attachment.match_id, hash).is_none());
+    }
+
     #[test]
     fn test_index_parse() {
         let string = r#"[{"match_attachment":{"id":165418,"match_id":65187924,"user_id":979950,"description":"discord","url":"","original_file_name":null,"created_at":"2016-07-02T13:24:09.899-04:00","updated_at":"2016-07-02T13:24:09.899-04:00","asset_file_name":null,"asset_content_type":null,"asset_file_size":null,"asset_url":null}},{"match_attachment":{"id":165417,"match_id":65187924,"user_id":979950,"description":"test description","url":"","original_file_name":null,"created_at":"2016-07-02T13:21:14.794-04:00","updated_at":"2016-07-02T13:21:14.794-04:00","asset_file_name":null,"asset_content_type":null,"asset_file_size":null,"asset_url":null}}]"#;
         let json_r = serde_json::from_str(string);
         assert!(json_r.is_ok());
         let json = json_r.unwrap();
-        if let Ok(i) = Index::decode(json) {
+        if let Ok(i) = Index::decode(&json) {
             assert_eq!(i.0.len(), 2);
         } else {
             assert!(false);
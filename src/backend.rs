@@ -0,0 +1,208 @@
+//! Backend-neutral subset of the client.
+//!
+//! Applications which hold an `Arc<dyn Backend>` instead of a `Challonge` client can switch the
+//! service behind it by configuration: the client of this crate (REST API v1), another API
+//! version, or an in-memory fake in tests. All backends use the model types of this crate and
+//! handle their payload formats internally; `api_v2` converts the records of the v2 API.
+
+use std::sync::Arc;
+
+use error::Error;
+use matches::{Match, MatchId, MatchState, MatchUpdate};
+use participants::{Participant, ParticipantCreate};
+use tournament::{Tournament, TournamentId, TournamentIncludes};
+use Challonge;
+
+/// Operations every backend supports.
+pub trait Backend: Send + Sync {
+    /// Retrieves a tournament without its participants and matches.
+    fn tournament(&self, id: &TournamentId) -> Result<Tournament, Error>;
+
+    /// Retrieves the participants of a tournament.
+    fn participants(&self, id: &TournamentId) -> Result<Vec<Participant>, Error>;
+
+    /// Retrieves the matches of a tournament.
+    fn matches(&self, id: &TournamentId) -> Result<Vec<Match>, Error>;
+
+    /// Adds a participant to a tournament.
+    fn add_participant(
+        &self,
+        id: &TournamentId,
+        participant: &ParticipantCreate,
+    ) -> Result<Participant, Error>;
+
+    /// Reports the scores and the winner of a match.
+    fn report_match(
+        &self,
+        id: &TournamentId,
+        match_id: &MatchId,
+        update: &MatchUpdate,
+    ) -> Result<Match, Error>;
+
+    /// Starts a tournament.
+    fn start(&self, id: &TournamentId) -> Result<(), Error>;
+
+    /// Finalizes a tournament whose matches are all complete.
+    fn finalize(&self, id: &TournamentId) -> Result<(), Error>;
+
+    /// Retrieves the matches of a tournament which are open for score reporting.
+    fn open_matches(&self, id: &TournamentId) -> Result<Vec<Match>, Error> {
+        let matches = try!(self.matches(id));
+        Ok(matches
+            .into_iter()
+            .filter(|m| m.state == MatchState::Open)
+            .collect())
+    }
+}
+
+impl Backend for Challonge {
+    fn tournament(&self, id: &TournamentId) -> Result<Tournament, Error> {
+        self.get_tournament(id, &TournamentIncludes::Participants)
+    }
+
+    fn participants(&self, id: &TournamentId) -> Result<Vec<Participant>, Error> {
+        self.participant_index(id).map(|index| index.0)
+    }
+
+    fn matches(&self, id: &TournamentId) -> Result<Vec<Match>, Error> {
+        self.match_index(id, None, None).map(|index| index.0)
+    }
+
+    fn add_participant(
+        &self,
+        id: &TournamentId,
+        participant: &ParticipantCreate,
+    ) -> Result<Participant, Error> {
+        self.create_participant(id, participant)
+    }
+
+    fn report_match(
+        &self,
+        id: &TournamentId,
+        match_id: &MatchId,
+        update: &MatchUpdate,
+    ) -> Result<Match, Error> {
+        self.update_match(id, match_id, update)
+    }
+
+    fn start(&self, id: &TournamentId) -> Result<(), Error> {
+        self.tournament_start(id, &TournamentIncludes::Participants)
+    }
+
+    fn finalize(&self, id: &TournamentId) -> Result<(), Error> {
+        self.tournament_finalize(id, &TournamentIncludes::Participants)
+    }
+}
+
+impl<B: Backend + ?Sized> Backend for Arc<B> {
+    fn tournament(&self, id: &TournamentId) -> Result<Tournament, Error> {
+        (**self).tournament(id)
+    }
+
+    fn participants(&self, id: &TournamentId) -> Result<Vec<Participant>, Error> {
+        (**self).participants(id)
+    }
+
+    fn matches(&self, id: &TournamentId) -> Result<Vec<Match>, Error> {
+        (**self).matches(id)
+    }
+
+    fn add_participant(
+        &self,
+        id: &TournamentId,
+        participant: &ParticipantCreate,
+    ) -> Result<Participant, Error> {
+        (**self).add_participant(id, participant)
+    }
+
+    fn report_match(
+        &self,
+        id: &TournamentId,
+        match_id: &MatchId,
+        update: &MatchUpdate,
+    ) -> Result<Match, Error> {
+        (**self).report_match(id, match_id, update)
+    }
+
+    fn start(&self, id: &TournamentId) -> Result<(), Error> {
+        (**self).start(id)
+    }
+
+    fn finalize(&self, id: &TournamentId) -> Result<(), Error> {
+        (**self).finalize(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use backend::Backend;
+    use error::Error;
+    use matches::{Match, MatchId, MatchState, MatchUpdate};
+    use participants::{Participant, ParticipantCreate};
+    use std::sync::Arc;
+    use tournament::{Tournament, TournamentId};
+
+    const MATCH: &'static str = r#"{"match": {"attachment_count": null, "created_at": "2015-01-19T16:57:17-05:00",
+        "has_attachment": false, "id": 1, "identifier": "A", "loser_id": null,
+        "player1_id": 16543993, "player1_is_prereq_match_loser": false, "player1_prereq_match_id": null,
+        "player1_votes": null, "player2_id": 16543997, "player2_is_prereq_match_loser": false,
+        "player2_prereq_match_id": null, "player2_votes": null, "round": 1, "started_at": null,
+        "state": "open", "tournament_id": 1086875, "updated_at": "2015-01-19T16:57:17-05:00",
+        "winner_id": null, "prerequisite_match_ids_csv": "", "scores_csv": ""}}"#;
+
+    struct Fake(Vec<Match>);
+
+    impl Backend for Fake {
+        fn tournament(&self, _: &TournamentId) -> Result<Tournament, Error> {
+            Err(Error::Api("Not found"))
+        }
+
+        fn participants(&self, _: &TournamentId) -> Result<Vec<Participant>, Error> {
+            Ok(Vec::new())
+        }
+
+        fn matches(&self, _: &TournamentId) -> Result<Vec<Match>, Error> {
+            Ok(self.0.clone())
+        }
+
+        fn add_participant(
+            &self,
+            _: &TournamentId,
+            _: &ParticipantCreate,
+        ) -> Result<Participant, Error> {
+            Err(Error::Api("Read only"))
+        }
+
+        fn report_match(
+            &self,
+            _: &TournamentId,
+            _: &MatchId,
+            _: &MatchUpdate,
+        ) -> Result<Match, Error> {
+            Err(Error::Api("Read only"))
+        }
+
+        fn start(&self, _: &TournamentId) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn finalize(&self, _: &TournamentId) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_open_matches() {
+        let open = Match::decode(&serde_json::from_str(MATCH).unwrap()).unwrap();
+        let mut complete = open.clone();
+        complete.id = MatchId(2);
+        complete.state = MatchState::Complete;
+
+        let backend: Arc<dyn Backend> = Arc::new(Fake(vec![open, complete]));
+        let matches = backend.open_matches(&TournamentId::Id(1086875)).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].id, MatchId(1));
+    }
+}
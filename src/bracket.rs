@@ -0,0 +1,227 @@
+//! Bracket graph of a tournament.
+//!
+//! Matches are nodes and every player slot filled from a prerequisite match is an edge
+//! from that match. The graph can be exported to Graphviz DOT or to a Mermaid flowchart,
+//! which renders directly in Markdown wikis and GitHub.
+
+use std::collections::HashMap;
+
+use matches::{Index as MatchIndex, Match, MatchState, Player};
+use participants::{Index as ParticipantIndex, ParticipantId};
+
+/// A match of the bracket.
+#[derive(Debug, Clone)]
+pub struct BracketNode {
+    /// Id of the match
+    pub id: u64,
+
+    /// Round of the match
+    pub round: u64,
+
+    /// Human readable match identifier (e.g. "A")
+    pub identifier: String,
+
+    /// Names of both players, `None` if a player is not known yet
+    pub players: (Option<String>, Option<String>),
+
+    /// Scores of the match as entered (e.g. "3-1,2-3")
+    pub scores: String,
+
+    /// State of the match
+    pub state: MatchState,
+
+    /// Winner of the match
+    pub winner: Option<ParticipantId>,
+
+    /// Deactivated participant who forfeited the match
+    pub forfeit: Option<ParticipantId>,
+}
+impl BracketNode {
+    /// Returns a one-line label of the match, e.g. "A: Alice vs Bob (3-1)".
+    pub fn label(&self) -> String {
+        let name = |n: &Option<String>| n.clone().unwrap_or_else(|| "TBD".to_owned());
+        let mut label = format!(
+            "{}: {} vs {}",
+            self.identifier,
+            name(&self.players.0),
+            name(&self.players.1)
+        );
+        if !self.scores.is_empty() {
+            label.push_str(&format!(" ({})", self.scores));
+        } else if self.forfeit.is_some() {
+            label.push_str(" (forfeit)");
+        }
+        label
+    }
+}
+
+/// A player slot of a match filled from another match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BracketEdge {
+    /// The prerequisite match
+    pub from: u64,
+
+    /// The match the player advances to
+    pub to: u64,
+
+    /// `true` if the loser of the prerequisite match advances (e.g. into the losers bracket)
+    pub loser: bool,
+}
+
+/// Matches of a tournament and the way players advance between them.
+#[derive(Debug, Clone)]
+pub struct Bracket {
+    /// Matches ordered by round and identifier
+    pub nodes: Vec<BracketNode>,
+
+    /// Advancement between matches
+    pub edges: Vec<BracketEdge>,
+}
+impl Bracket {
+    /// Builds the bracket graph, players are named after `participants`.
+    pub fn new(matches: &MatchIndex, participants: &ParticipantIndex) -> Bracket {
+        let names: HashMap<u64, &str> = participants
+            .0
+            .iter()
+            .map(|p| (p.id.0, &p.name[..]))
+            .collect();
+        let inactive = participants.inactive_ids();
+        let name = |p: &Player| {
+            p.id.as_ref()
+                .map(|id| names.get(&id.0).map_or(id.0.to_string(), |n| n.to_string()))
+        };
+
+        let mut sorted: Vec<&Match> = matches.0.iter().collect();
+        sorted.sort_by(|a, b| (a.round, &a.identifier).cmp(&(b.round, &b.identifier)));
+
+        let mut bracket = Bracket {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        };
+        for m in sorted {
+            for p in &[&m.player1, &m.player2] {
+                if let Some(ref from) = p.prereq_match_id {
+                    bracket.edges.push(BracketEdge {
+                        from: from.0,
+                        to: m.id.0,
+                        loser: p.is_prereq_match_loser,
+                    });
+                }
+            }
+            bracket.nodes.push(BracketNode {
+                id: m.id.0,
+                round: m.round,
+                identifier: m.identifier.clone(),
+                players: (name(&m.player1), name(&m.player2)),
+                scores: m.scores_csv.to_string(),
+                state: m.state.clone(),
+                winner: m.winner_id.clone(),
+                forfeit: m.forfeited_by(&inactive),
+            });
+        }
+        bracket
+    }
+
+    /// Exports the bracket to Graphviz DOT.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph bracket {\n    rankdir=LR;\n    node [shape=box];\n");
+        for n in &self.nodes {
+            out.push_str(&format!(
+                "    m{} [label=\"{}\"];\n",
+                n.id,
+                n.label().replace('\\', "\\\\").replace('"', "\\\"")
+            ));
+        }
+        for e in &self.edges {
+            if e.loser {
+                out.push_str(&format!(
+                    "    m{} -> m{} [style=dashed, label=\"loser\"];\n",
+                    e.from, e.to
+                ));
+            } else {
+                out.push_str(&format!("    m{} -> m{};\n", e.from, e.to));
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Exports the bracket to a Mermaid flowchart, e.g. for a ```` ```mermaid ```` block in Markdown.
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart LR\n");
+        for n in &self.nodes {
+            out.push_str(&format!(
+                "    m{}[\"{}\"]\n",
+                n.id,
+                mermaid_escape(&n.label())
+            ));
+        }
+        for e in &self.edges {
+            if e.loser {
+                out.push_str(&format!("    m{} -.->|loser| m{}\n", e.from, e.to));
+            } else {
+                out.push_str(&format!("    m{} --> m{}\n", e.from, e.to));
+            }
+        }
+        out
+    }
+}
+
+/// Replaces characters which break a quoted Mermaid label with entity codes.
+fn mermaid_escape(label: &str) -> String {
+    let mut out = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' => out.push_str("#quot;"),
+            '<' => out.push_str("#lt;"),
+            '>' => out.push_str("#gt;"),
+            '\n' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use bracket::Bracket;
+    use matches::{Index as MatchIndex, Match, MatchId};
+    use participants::{Index as ParticipantIndex, ParticipantId};
+
+    const MATCH: &'static str = r#"{"match": {"attachment_count": null, "created_at": "2015-01-19T16:57:17-05:00",
+        "has_attachment": false, "id": 1, "identifier": "A", "loser_id": null,
+        "player1_id": 16543993, "player1_is_prereq_match_loser": false, "player1_prereq_match_id": null,
+        "player1_votes": null, "player2_id": 16543997, "player2_is_prereq_match_loser": false,
+        "player2_prereq_match_id": null, "player2_votes": null, "round": 1, "started_at": null,
+        "state": "complete", "tournament_id": 1086875, "updated_at": "2015-01-19T16:57:17-05:00",
+        "winner_id": 16543993, "prerequisite_match_ids_csv": "", "scores_csv": "3-1"}}"#;
+
+    #[test]
+    fn test_bracket_export() {
+        let first = Match::decode(&serde_json::from_str(MATCH).unwrap()).unwrap();
+        let mut second = first.clone();
+        second.id = MatchId(2);
+        second.identifier = "B".to_owned();
+        second.round = 2;
+        second.player2.id = None;
+        second.player2.prereq_match_id = Some(MatchId(1));
+        second.player2.is_prereq_match_loser = true;
+        let bracket = Bracket::new(
+            &MatchIndex(vec![second, first]),
+            &ParticipantIndex(Vec::new()),
+        );
+
+        assert_eq!(bracket.nodes[0].id, 1);
+        assert_eq!(bracket.nodes[0].winner, Some(ParticipantId(16543993)));
+        let mermaid = bracket.to_mermaid();
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("    m1[\"A: 16543993 vs 16543997 (3-1)\"]\n"));
+        assert!(mermaid.contains("    m1 -.->|loser| m2\n"));
+
+        let dot = bracket.to_dot();
+        assert!(dot.contains("    m2 [label=\"B: 16543993 vs TBD (3-1)\"];\n"));
+        assert!(dot.contains("    m1 -> m2 [style=dashed, label=\"loser\"];\n"));
+    }
+}
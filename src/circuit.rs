@@ -0,0 +1,189 @@
+//! Circuit breaker for an unhealthy API.
+//!
+//! With `Challonge::with_circuit_breaker` the client stops sending requests after a number of
+//! consecutive server failures (5xx responses, maintenance, timeouts and transport errors) and
+//! fails fast with `Error::CircuitOpen` until a cooldown has passed. A single trial request is then
+//! let through: its success closes the circuit, its failure opens it for another cooldown.
+//! The breaker is shared by the clones of the client, so a service with many pollers backs off
+//! as a whole instead of every poller hammering the failing endpoint on its own.
+
+use chrono::{DateTime, UTC};
+use std::sync::Mutex;
+use std::time::Duration as StdDuration;
+
+use error::Error;
+
+/// State of a circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CircuitState {
+    /// Requests are sent
+    Closed,
+
+    /// Requests fail with `Error::CircuitOpen` until the cooldown is over
+    Open,
+
+    /// The cooldown is over and a trial request decides whether the circuit closes
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct Circuit {
+    failures: u32,
+    opened_at: Option<DateTime<UTC>>,
+    trial: bool,
+}
+
+/// Opens after consecutive server failures and short-circuits requests for a cooldown.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: StdDuration,
+    circuit: Mutex<Circuit>,
+}
+impl CircuitBreaker {
+    /// Creates a breaker opening after `threshold` consecutive failures for `cooldown`.
+    pub fn new(threshold: u32, cooldown: StdDuration) -> CircuitBreaker {
+        CircuitBreaker {
+            threshold: threshold.max(1),
+            cooldown: cooldown,
+            circuit: Mutex::new(Circuit {
+                failures: 0,
+                opened_at: None,
+                trial: false,
+            }),
+        }
+    }
+
+    /// Returns the number of consecutive failures which open the circuit.
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    /// Returns how long the circuit stays open.
+    pub fn cooldown(&self) -> StdDuration {
+        self.cooldown
+    }
+
+    /// Returns the state of the circuit at `now`.
+    pub fn state(&self, now: DateTime<UTC>) -> CircuitState {
+        let circuit = self.circuit.lock().unwrap();
+        match circuit.opened_at {
+            None => CircuitState::Closed,
+            Some(_) if circuit.trial => CircuitState::Open,
+            Some(at) if self.remaining(at, now).is_some() => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Checks whether a request may be sent at `now`. Once the cooldown is over, only one trial
+    /// request is let through until its outcome is recorded.
+    pub fn check(&self, now: DateTime<UTC>) -> Result<(), Error> {
+        let mut circuit = self.circuit.lock().unwrap();
+        let opened_at = match circuit.opened_at {
+            Some(at) => at,
+            None => return Ok(()),
+        };
+        if circuit.trial {
+            return Err(Error::CircuitOpen {
+                retry_after: self.cooldown,
+            });
+        }
+        match self.remaining(opened_at, now) {
+            Some(remaining) => Err(Error::CircuitOpen {
+                retry_after: remaining,
+            }),
+            None => {
+                circuit.trial = true;
+                Ok(())
+            }
+        }
+    }
+
+    /// Records the outcome of a request sent at `now`. Only server failures count,
+    /// other errors show that the API is up and reset the count like successes.
+    pub fn record<T>(&self, now: DateTime<UTC>, result: &Result<T, Error>) {
+        let mut circuit = self.circuit.lock().unwrap();
+        circuit.trial = false;
+        if !result.as_ref().err().map_or(false, is_failure) {
+            circuit.failures = 0;
+            circuit.opened_at = None;
+            return;
+        }
+        circuit.failures = circuit.failures.saturating_add(1);
+        if circuit.opened_at.is_some() || circuit.failures >= self.threshold {
+            if circuit.opened_at.is_none() {
+                warn!(
+                    "Opening the circuit after {} consecutive failures",
+                    circuit.failures
+                );
+            }
+            circuit.opened_at = Some(now);
+        }
+    }
+
+    fn remaining(&self, opened_at: DateTime<UTC>, now: DateTime<UTC>) -> Option<StdDuration> {
+        let elapsed = (now - opened_at)
+            .to_std()
+            .unwrap_or(StdDuration::from_secs(0));
+        if elapsed < self.cooldown {
+            Some(self.cooldown - elapsed)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns `true` for errors which suggest that Challonge is failing.
+fn is_failure(error: &Error) -> bool {
+    match *error {
+        Error::Status(status, _, _) => status >= 500,
+        Error::Maintenance { .. } | Error::Timeout | Error::Io(_) | Error::Transport(_) => true,
+        #[cfg(feature = "hyper")]
+        Error::Hyper(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::*;
+    use circuit::{CircuitBreaker, CircuitState};
+    use error::Error;
+    use std::time::Duration as StdDuration;
+
+    #[test]
+    fn test_circuit_breaker() {
+        let now = UTC.ymd(2016, 4, 1).and_hms(12, 0, 0);
+        let breaker = CircuitBreaker::new(2, StdDuration::from_secs(60));
+        let failure: Result<(), Error> = Err(Error::status(502, None));
+        let rejected: Result<(), Error> = Err(Error::status(422, None));
+
+        breaker.record(now, &failure);
+        breaker.record(now, &rejected);
+        breaker.record(now, &failure);
+        assert_eq!(breaker.state(now), CircuitState::Closed);
+        breaker.record(now, &failure);
+        assert_eq!(breaker.state(now), CircuitState::Open);
+
+        let later = now + Duration::seconds(20);
+        match breaker.check(later) {
+            Err(Error::CircuitOpen { retry_after }) => {
+                assert_eq!(retry_after, StdDuration::from_secs(40))
+            }
+            _ => assert!(false),
+        }
+
+        // a single trial after the cooldown, its failure opens the circuit again
+        let after = now + Duration::seconds(60);
+        assert_eq!(breaker.state(after), CircuitState::HalfOpen);
+        assert!(breaker.check(after).is_ok());
+        assert!(breaker.check(after).is_err());
+        breaker.record(after, &failure);
+        assert_eq!(breaker.state(after), CircuitState::Open);
+
+        let after = after + Duration::seconds(60);
+        assert!(breaker.check(after).is_ok());
+        breaker.record(after, &Ok::<(), Error>(()));
+        assert_eq!(breaker.state(after), CircuitState::Closed);
+    }
+}
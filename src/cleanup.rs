@@ -0,0 +1,116 @@
+//! Bulk removal of junk tournaments, e.g. the ones left behind by CI runs.
+
+use chrono::*;
+use std::time::Duration as StdDuration;
+
+use error::Error;
+use tournament::{Tournament, TournamentId, TournamentState};
+use Challonge;
+
+/// Pause between two deletions, keeps bulk cleanups well below the API rate limit.
+pub const DELETE_INTERVAL_MILLIS: u64 = 500;
+
+/// Result of a cleanup.
+#[derive(Debug)]
+pub struct CleanupReport {
+    /// Deleted tournaments
+    pub deleted: Vec<TournamentId>,
+
+    /// Matching tournaments which the confirmation callback declined
+    pub declined: Vec<TournamentId>,
+
+    /// Tournaments which could not be deleted
+    pub failed: Vec<(TournamentId, Error)>,
+}
+
+/// Returns `true` if the tournament url starts with `url_prefix` and it was created before `cutoff`.
+pub fn is_candidate(tournament: &Tournament, url_prefix: &str, cutoff: &DateTime<UTC>) -> bool {
+    tournament.url.starts_with(url_prefix) && tournament.created_at.with_timezone(&UTC) < *cutoff
+}
+
+/// Deletes tournaments whose url starts with `url_prefix` and which are older than `older_than`.
+///
+/// `confirm` is called for every matching tournament, only the confirmed ones are deleted.
+/// Each deletion also goes through the confirmation hook of the client, if it has one;
+/// tournaments declined by either are reported as declined. Pass `|_| true` to rely on the hook alone.
+/// Deletions are paced by `DELETE_INTERVAL_MILLIS` using the client clock and retried by
+/// the retry policy of the client.
+/// # Example
+/// ```ignore
+/// extern crate challonge;
+/// extern crate chrono;
+///
+/// use self::challonge::{cleanup, Challonge};
+/// use self::chrono::Duration;
+///
+/// let c = Challonge::builder()
+///     .credentials("myusername", "myapikey")
+///     .build()
+///     .unwrap();
+/// let report = cleanup::delete_matching(&c, "ci_", Duration::days(1), |t| {
+///     println!("deleting {}", t.url);
+///     true
+/// }).unwrap();
+/// ```
+pub fn delete_matching<F: FnMut(&Tournament) -> bool>(
+    client: &Challonge,
+    url_prefix: &str,
+    older_than: Duration,
+    mut confirm: F,
+) -> Result<CleanupReport, Error> {
+    let cutoff = client.clock().now() - older_than;
+    let index = try!(client.fetch_tournament_index(&[("state", TournamentState::All.to_string())]));
+
+    let mut report = CleanupReport {
+        deleted: Vec::new(),
+        declined: Vec::new(),
+        failed: Vec::new(),
+    };
+    let mut first = true;
+    for t in index
+        .0
+        .iter()
+        .filter(|t| is_candidate(t, url_prefix, &cutoff))
+    {
+        if !confirm(t) {
+            report.declined.push(t.id.clone());
+            continue;
+        }
+        if !first {
+            client
+                .clock()
+                .sleep(StdDuration::from_millis(DELETE_INTERVAL_MILLIS));
+        }
+        first = false;
+
+        match client.delete_tournament(&t.id) {
+            Ok(_) => report.deleted.push(t.id.clone()),
+            Err(Error::Cancelled(_)) => report.declined.push(t.id.clone()),
+            Err(e) => report.failed.push((t.id.clone(), e)),
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use chrono::*;
+    use cleanup::is_candidate;
+    use tournament::Tournament;
+
+    #[test]
+    fn test_cleanup_matching() {
+        let mut t = Tournament::decode(
+            &serde_json::from_str(include_str!("../benches/fixtures/tournament.json")).unwrap(),
+        )
+        .unwrap();
+        t.url = "ci_build_42".to_owned();
+        let created = t.created_at.with_timezone(&UTC);
+
+        assert!(is_candidate(&t, "ci_", &(created + Duration::days(1))));
+        assert!(!is_candidate(&t, "ci_", &(created - Duration::days(1))));
+        assert!(!is_candidate(&t, "test_", &(created + Duration::days(1))));
+    }
+}
@@ -0,0 +1,85 @@
+//! Time source used by time-dependent logic of the client.
+
+use chrono::*;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration as StdDuration;
+
+/// A source of the current time which can also wait.
+///
+/// All time-dependent logic of the client (history windows, watcher intervals, retry backoff)
+/// goes through a `Clock`, so it can be replaced in tests and simulations.
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> DateTime<UTC>;
+
+    /// Blocks for the given duration.
+    fn sleep(&self, duration: StdDuration);
+}
+
+/// The system clock.
+#[derive(Debug, Clone, Default)]
+pub struct SystemClock;
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<UTC> {
+        UTC::now()
+    }
+
+    fn sleep(&self, duration: StdDuration) {
+        thread::sleep(duration)
+    }
+}
+
+/// A clock which only moves when told to. Sleeping advances it instantly.
+#[derive(Debug)]
+pub struct ManualClock {
+    now: Mutex<DateTime<UTC>>,
+}
+impl ManualClock {
+    /// Creates a clock stopped at the given time.
+    pub fn new(now: DateTime<UTC>) -> ManualClock {
+        ManualClock {
+            now: Mutex::new(now),
+        }
+    }
+
+    /// Sets the current time.
+    pub fn set(&self, now: DateTime<UTC>) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    /// Moves the clock forward.
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock().unwrap();
+        *now = *now + duration;
+    }
+}
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<UTC> {
+        *self.now.lock().unwrap()
+    }
+
+    fn sleep(&self, duration: StdDuration) {
+        self.advance(Duration::from_std(duration).unwrap_or(Duration::zero()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::*;
+    use clock::{Clock, ManualClock};
+    use std::time::Duration as StdDuration;
+
+    #[test]
+    fn test_manual_clock() {
+        let start = UTC.ymd(2016, 4, 1).and_hms(12, 0, 0);
+        let clock = ManualClock::new(start);
+        assert_eq!(clock.now(), start);
+
+        clock.sleep(StdDuration::from_secs(30));
+        assert_eq!(clock.now(), start + Duration::seconds(30));
+
+        clock.advance(Duration::minutes(1));
+        assert_eq!(clock.now(), start + Duration::seconds(90));
+    }
+}
@@ -0,0 +1,101 @@
+//! Read-your-writes consistency after mutations.
+//!
+//! Challonge sometimes answers a mutation with slightly stale nested data and serves the old
+//! state to reads made right after it. With `Challonge::with_read_your_writes` mutation methods
+//! re-fetch the affected resource until its `updated_at` reaches the one of the mutation response.
+
+use std::time::Duration;
+
+use clock::Clock;
+use datetime::ChallongeDateTime;
+use error::Error;
+
+/// How mutations wait for their change to become visible.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReadYourWrites {
+    /// Number of re-fetches before giving up
+    pub attempts: u32,
+
+    /// Delay before the first re-fetch, doubled before every next one
+    pub backoff: Duration,
+}
+impl ReadYourWrites {
+    /// Creates a policy of 3 re-fetches starting with a 200 ms backoff.
+    pub fn new() -> ReadYourWrites {
+        ReadYourWrites {
+            attempts: 3,
+            backoff: Duration::from_millis(200),
+        }
+    }
+
+    builder!(attempts, u32);
+    builder!(backoff, Duration);
+
+    /// Re-fetches a resource until it is at least as recent as `written`, the `updated_at` of the mutation response.
+    /// The first re-fetch happens right away. Returns `None` if the change did not become visible.
+    pub fn confirm<T, F, U>(
+        &self,
+        clock: &dyn Clock,
+        written: &ChallongeDateTime,
+        mut fetch: F,
+        updated_at: U,
+    ) -> Result<Option<T>, Error>
+    where
+        F: FnMut() -> Result<T, Error>,
+        U: Fn(&T) -> ChallongeDateTime,
+    {
+        let mut delay = self.backoff;
+        for attempt in 0..self.attempts {
+            if attempt > 0 {
+                clock.sleep(delay);
+                delay = delay * 2;
+            }
+            let fetched = try!(fetch());
+            if updated_at(&fetched) >= *written {
+                return Ok(Some(fetched));
+            }
+        }
+        Ok(None)
+    }
+}
+impl Default for ReadYourWrites {
+    fn default() -> ReadYourWrites {
+        ReadYourWrites::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::*;
+    use clock::{Clock, ManualClock};
+    use consistency::ReadYourWrites;
+    use datetime::ChallongeDateTime;
+    use std::cell::Cell;
+    use std::time::Duration as StdDuration;
+
+    #[test]
+    fn test_confirm_waits_for_change() {
+        let start = UTC.ymd(2016, 4, 1).and_hms(12, 0, 0);
+        let clock = ManualClock::new(start);
+        let written = ChallongeDateTime::from(start);
+        let stale = ChallongeDateTime::from(start - Duration::seconds(5));
+        let calls = Cell::new(0);
+        let fetch = || {
+            calls.set(calls.get() + 1);
+            Ok(if calls.get() < 3 { stale } else { written })
+        };
+
+        let mut policy = ReadYourWrites::new();
+        policy.backoff(StdDuration::from_secs(1));
+        let confirmed = policy.confirm(&clock, &written, fetch, |t| *t).unwrap();
+        assert_eq!(confirmed, Some(written));
+        assert_eq!(calls.get(), 3);
+        assert_eq!(clock.now(), start + Duration::seconds(3));
+
+        policy.attempts(1);
+        let confirmed = policy
+            .confirm(&clock, &written, || Ok(stale), |t| *t)
+            .unwrap();
+        assert_eq!(confirmed, None);
+    }
+}
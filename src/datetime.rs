@@ -0,0 +1,220 @@
+//! Timestamps as returned by Challonge.
+//!
+//! The API mostly returns RFC3339 timestamps with an offset, but older records come with
+//! fractional seconds, a space instead of `T` or a `UTC` suffix. `ChallongeDateTime` accepts all of them.
+//!
+//! With the `time` feature timestamps convert to and from `time::OffsetDateTime`.
+
+use chrono::*;
+use serde::de;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+#[cfg(feature = "time")]
+use time;
+
+use error::Error;
+
+/// Formats tried after RFC3339, in order.
+const FALLBACK_FORMATS: &'static [&'static str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f%z",
+    "%Y-%m-%d %H:%M:%S%.f%z",
+    "%Y-%m-%d %H:%M:%S%.f %z",
+];
+
+/// A timestamp with the offset it was returned with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ChallongeDateTime(pub DateTime<FixedOffset>);
+impl ChallongeDateTime {
+    /// Parses a timestamp in any of the formats emitted by Challonge.
+    pub fn parse(s: &str) -> Result<ChallongeDateTime, Error> {
+        let s = s.trim();
+        if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+            return Ok(ChallongeDateTime(dt));
+        }
+        for format in FALLBACK_FORMATS {
+            if let Ok(dt) = DateTime::parse_from_str(s, format) {
+                return Ok(ChallongeDateTime(dt));
+            }
+        }
+        let naive = s.trim_end_matches(" UTC").trim_end_matches('Z');
+        if naive.len() < s.len() {
+            for format in &["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+                if let Ok(dt) = NaiveDateTime::parse_from_str(naive, format) {
+                    return Ok(ChallongeDateTime::from(UTC.from_utc_datetime(&dt)));
+                }
+            }
+        }
+        Err(Error::Decode(
+            "Invalid timestamp",
+            Value::String(s.to_owned()),
+        ))
+    }
+
+    /// Decodes an optional timestamp, `null` and empty strings are `None`.
+    pub fn decode(value: &Value) -> Result<Option<ChallongeDateTime>, Error> {
+        match *value {
+            Value::Null => Ok(None),
+            Value::String(ref s) if s.trim().is_empty() => Ok(None),
+            Value::String(ref s) => ChallongeDateTime::parse(s).map(Some),
+            _ => Err(Error::Decode("Expected timestamp", value.clone())),
+        }
+    }
+
+    /// Decodes a timestamp which must be present.
+    pub fn decode_required(value: &Value) -> Result<ChallongeDateTime, Error> {
+        match try!(ChallongeDateTime::decode(value)) {
+            Some(dt) => Ok(dt),
+            None => Err(Error::Decode("Missing timestamp", value.clone())),
+        }
+    }
+
+    /// Returns the timestamp in UTC.
+    pub fn to_utc(&self) -> DateTime<UTC> {
+        self.0.with_timezone(&UTC)
+    }
+}
+impl Deref for ChallongeDateTime {
+    type Target = DateTime<FixedOffset>;
+
+    fn deref(&self) -> &DateTime<FixedOffset> {
+        &self.0
+    }
+}
+impl From<DateTime<FixedOffset>> for ChallongeDateTime {
+    fn from(dt: DateTime<FixedOffset>) -> ChallongeDateTime {
+        ChallongeDateTime(dt)
+    }
+}
+impl From<DateTime<UTC>> for ChallongeDateTime {
+    fn from(dt: DateTime<UTC>) -> ChallongeDateTime {
+        ChallongeDateTime(dt.with_timezone(&FixedOffset::east(0)))
+    }
+}
+impl From<ChallongeDateTime> for DateTime<FixedOffset> {
+    fn from(dt: ChallongeDateTime) -> DateTime<FixedOffset> {
+        dt.0
+    }
+}
+#[cfg(feature = "time")]
+impl ChallongeDateTime {
+    /// Converts the timestamp into a `time::OffsetDateTime`, keeping its offset.
+    pub fn to_offset_date_time(&self) -> Result<time::OffsetDateTime, Error> {
+        let offset = self.0.offset().local_minus_utc().num_seconds() as i32;
+        let nanos = self.0.timestamp() as i128 * 1_000_000_000 + self.0.nanosecond() as i128;
+        let utc = try!(time::OffsetDateTime::from_unix_timestamp_nanos(nanos)
+            .map_err(|_| Error::Api("Timestamp is out of range")));
+        let offset = try!(time::UtcOffset::from_whole_seconds(offset)
+            .map_err(|_| Error::Api("Timestamp offset is out of range")));
+        Ok(utc.to_offset(offset))
+    }
+
+    /// Converts a `time::OffsetDateTime` into a timestamp, keeping its offset.
+    /// Fails on offsets of a day or more, which `chrono` can not represent.
+    pub fn from_offset_date_time(dt: time::OffsetDateTime) -> Result<ChallongeDateTime, Error> {
+        let offset = try!(FixedOffset::east_opt(dt.offset().whole_seconds())
+            .ok_or(Error::Api("Timestamp offset is out of range")));
+        offset
+            .timestamp_opt(dt.unix_timestamp(), dt.nanosecond())
+            .single()
+            .map(ChallongeDateTime)
+            .ok_or(Error::Api("Timestamp is out of range"))
+    }
+}
+impl FromStr for ChallongeDateTime {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ChallongeDateTime::parse(s)
+    }
+}
+impl fmt::Display for ChallongeDateTime {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(&self.0.to_rfc3339())
+    }
+}
+
+/// Serializes the timestamp as an RFC3339 string.
+impl Serialize for ChallongeDateTime {
+    fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.serialize_str(&self.0.to_rfc3339())
+    }
+}
+
+impl Deserialize for ChallongeDateTime {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<ChallongeDateTime, D::Error> {
+        struct Visitor;
+        impl de::Visitor for Visitor {
+            type Value = ChallongeDateTime;
+
+            fn visit_str<E: de::Error>(&mut self, value: &str) -> Result<ChallongeDateTime, E> {
+                ChallongeDateTime::parse(value).map_err(|_| E::invalid_value(value))
+            }
+        }
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use chrono::*;
+    use datetime::ChallongeDateTime;
+
+    #[test]
+    fn test_parse_variants() {
+        let expected = DateTime::parse_from_rfc3339("2015-01-19T16:57:17-05:00").unwrap();
+        for s in &[
+            "2015-01-19T16:57:17-05:00",
+            "2015-01-19T16:57:17-0500",
+            "2015-01-19 16:57:17 -0500",
+            "2015-01-19T21:57:17Z",
+            "2015-01-19 21:57:17 UTC",
+        ] {
+            assert_eq!(
+                ChallongeDateTime::parse(s).unwrap().to_utc(),
+                expected.with_timezone(&UTC)
+            );
+        }
+        let fractional = ChallongeDateTime::parse("2016-07-02T13:24:09.899-04:00").unwrap();
+        assert_eq!(fractional.nanosecond(), 899_000_000);
+
+        assert_eq!(
+            ChallongeDateTime::decode(&serde_json::Value::Null).unwrap(),
+            None
+        );
+        assert!(ChallongeDateTime::decode_required(&serde_json::Value::Null).is_err());
+        assert!(ChallongeDateTime::parse("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let dt = ChallongeDateTime::parse("2015-01-19T16:57:17-05:00").unwrap();
+        let json = serde_json::to_string(&dt).unwrap();
+        assert_eq!(json, "\"2015-01-19T16:57:17-05:00\"");
+        let decoded: ChallongeDateTime = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, dt);
+        let missing: Option<ChallongeDateTime> = serde_json::from_str("null").unwrap();
+        assert_eq!(missing, None);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time_conversion() {
+        let dt = ChallongeDateTime::parse("2016-07-02T13:24:09.899-04:00").unwrap();
+        let converted = dt.to_offset_date_time().unwrap();
+        assert_eq!(converted.offset().whole_hours(), -4);
+        assert_eq!(converted.hour(), 13);
+        assert_eq!(converted.millisecond(), 899);
+        assert_eq!(
+            ChallongeDateTime::from_offset_date_time(converted).unwrap(),
+            dt
+        );
+
+        let far = converted.replace_offset(time::UtcOffset::from_hms(25, 0, 0).unwrap());
+        assert!(ChallongeDateTime::from_offset_date_time(far).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! Helpers for tournament descriptions.
+//!
+//! Challonge stores a description twice: `description_source` is the text as it was entered
+//! and `description` is the rendered HTML shown on the tournament page.
+//! `DescriptionTemplate` keeps descriptions of recurring events consistent.
+
+use pulldown_cmark::{html, Parser};
+use std::collections::BTreeMap;
+
+use datetime::ChallongeDateTime;
+use error::Error;
+
+/// Default maximum length of a description in characters, used by `validate`.
+pub const MAX_DESCRIPTION_LENGTH: usize = 10000;
+
+/// Renders Markdown into HTML suitable for the `description` attribute.
+pub fn markdown_to_html(markdown: &str) -> String {
+    let mut out = String::new();
+    html::push_html(&mut out, Parser::new(markdown));
+    out.trim_end().to_owned()
+}
+
+/// Converts a HTML description into plain text for contexts which can not render HTML.
+/// Tags are removed, block elements become line breaks and common entities are decoded.
+pub fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let end = match rest[start..].find('>') {
+            Some(end) => start + end,
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        };
+        let tag = rest[start + 1..end]
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+        if is_block_tag(&tag) {
+            text.push('\n');
+        }
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+
+    let text = decode_entities(&text);
+    text.lines()
+        .map(|l| l.split_whitespace().collect::<Vec<&str>>().join(" "))
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn is_block_tag(tag: &str) -> bool {
+    match tag {
+        "p" | "br" | "div" | "li" | "ul" | "ol" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+        | "tr" | "blockquote" | "pre" | "hr" => true,
+        _ => false,
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// Escapes text for use inside HTML content and attribute values.
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Values of template variables.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateVars {
+    values: BTreeMap<String, String>,
+}
+impl TemplateVars {
+    /// Creates an empty set of variables.
+    pub fn new() -> TemplateVars {
+        TemplateVars::default()
+    }
+
+    /// Sets a variable.
+    pub fn var<'a, K: Into<String>, V: Into<String>>(
+        &'a mut self,
+        name: K,
+        value: V,
+    ) -> &'a mut Self {
+        self.values.insert(name.into(), value.into());
+        self
+    }
+
+    /// Sets `date` (e.g. "2016-04-01") and `time` (e.g. "18:00") of the event.
+    pub fn date<'a>(&'a mut self, date: &ChallongeDateTime) -> &'a mut Self {
+        self.var("date", date.format("%Y-%m-%d").to_string())
+            .var("time", date.format("%H:%M").to_string())
+    }
+
+    /// Sets `game`.
+    pub fn game<'a, S: Into<String>>(&'a mut self, game: S) -> &'a mut Self {
+        self.var("game", game)
+    }
+
+    /// Sets `prize_pool`.
+    pub fn prize_pool<'a, S: Into<String>>(&'a mut self, prize_pool: S) -> &'a mut Self {
+        self.var("prize_pool", prize_pool)
+    }
+
+    /// Sets `ruleset_link`.
+    pub fn ruleset_link<'a, S: Into<String>>(&'a mut self, url: S) -> &'a mut Self {
+        self.var("ruleset_link", url)
+    }
+}
+
+/// A HTML description with `{{name}}` placeholders.
+///
+/// Values are HTML-escaped when rendered, so they can not break the markup of the template.
+/// # Example
+/// ```ignore
+/// extern crate challonge;
+///
+/// use self::challonge::description::{DescriptionTemplate, TemplateVars};
+///
+/// let template = DescriptionTemplate::new(
+///     "<p>{{game}} weekly, prize pool {{prize_pool}}.</p><p><a href=\"{{ruleset_link}}\">Rules</a></p>",
+/// );
+/// let mut vars = TemplateVars::new();
+/// vars.game("Tekken 7").prize_pool("$100").ruleset_link("https://example.com/rules");
+/// let html = template.render(&vars).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct DescriptionTemplate {
+    source: String,
+}
+impl DescriptionTemplate {
+    /// Creates a template.
+    pub fn new<S: Into<String>>(source: S) -> DescriptionTemplate {
+        DescriptionTemplate {
+            source: source.into(),
+        }
+    }
+
+    /// Renders the template. Fails on unclosed placeholders and variables which are not set.
+    pub fn render(&self, vars: &TemplateVars) -> Result<String, Error> {
+        let mut out = String::with_capacity(self.source.len());
+        let mut rest = &self.source[..];
+        while let Some(start) = rest.find("{{") {
+            out.push_str(&rest[..start]);
+            let end = match rest[start..].find("}}") {
+                Some(end) => start + end,
+                None => {
+                    return Err(Error::Api(
+                        "Unclosed placeholder in the description template",
+                    ))
+                }
+            };
+            match vars.values.get(rest[start + 2..end].trim()) {
+                Some(value) => out.push_str(&escape_html(value)),
+                None => return Err(Error::Api("Unknown variable in the description template")),
+            }
+            rest = &rest[end + 2..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+}
+
+/// Checks that a description is not longer than `max` characters.
+pub fn validate(description: &str, max: usize) -> Result<(), Error> {
+    if description.chars().count() > max {
+        return Err(Error::Api("Description is too long"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use description::{markdown_to_html, strip_html, validate, DescriptionTemplate, TemplateVars};
+
+    #[test]
+    fn test_description_helpers() {
+        let html = markdown_to_html("# Weekly\n\nBring **your own** controller & cable.");
+        assert_eq!(
+            html,
+            "<h1>Weekly</h1>\n<p>Bring <strong>your own</strong> controller &amp; cable.</p>"
+        );
+        assert_eq!(
+            strip_html(&html),
+            "Weekly\nBring your own controller & cable."
+        );
+        assert_eq!(strip_html("a<br/>b &lt;3"), "a\nb <3");
+
+        assert!(validate("short", 10).is_ok());
+        assert!(validate("\u{1F3AE}\u{1F3AE}", 2).is_ok());
+        assert!(validate("too long", 3).is_err());
+    }
+
+    #[test]
+    fn test_description_template() {
+        let template = DescriptionTemplate::new(
+            "<p>{{ game }} weekly</p><a href=\"{{ruleset_link}}\">Rules</a>",
+        );
+        let mut vars = TemplateVars::new();
+        vars.game("Rock & <Roll>")
+            .ruleset_link("https://example.com/\"rules\"");
+        assert_eq!(
+            template.render(&vars).unwrap(),
+            "<p>Rock &amp; &lt;Roll&gt; weekly</p><a href=\"https://example.com/&quot;rules&quot;\">Rules</a>"
+        );
+
+        assert!(DescriptionTemplate::new("{{prize_pool}}")
+            .render(&vars)
+            .is_err());
+        assert!(DescriptionTemplate::new("{{game").render(&vars).is_err());
+    }
+}
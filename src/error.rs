@@ -1,18 +1,46 @@
 //! Challonge REST API error type.
 
-extern crate hyper;
 extern crate serde_json;
 
+use chrono::{DateTime, FixedOffset};
+
+use datetime::ChallongeDateTime;
+use participants::DuplicateField;
 use serde_json::Error as JsonError;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io;
+use std::time::Duration;
+use tournament::TournamentId;
+use transaction::RollbackReport;
+use transport::Response;
+
+/// Default delay before retrying a request which failed during a maintenance window.
+pub const MAINTENANCE_BACKOFF_SECS: u64 = 300;
+
+/// Default delay before retrying other retryable failures.
+pub const RETRY_BACKOFF_SECS: u64 = 5;
 
 /// Challonge REST API error type.
 #[derive(Debug)]
 pub enum Error {
     /// A `hyper` crate error
-    Hyper(hyper::Error),
+    #[cfg(feature = "hyper")]
+    Hyper(::hyper::Error),
+
+    /// An OS credential store error
+    #[cfg(feature = "keyring")]
+    Keyring(::keyring::Error),
 
-    /// A generic non-success response from the REST API
-    Status(hyper::status::StatusCode, Option<serde_json::Value>),
+    /// An I/O error, e.g. a refused or reset connection or a failed read of a response
+    Io(io::Error),
+
+    /// A transport error which is not an I/O error, with a description
+    Transport(String),
+
+    /// A generic non-success response from the REST API, with the status code, the json body if any
+    /// and the codes of the messages in the body. Build it with `Error::status`.
+    Status(u16, Option<serde_json::Value>, Vec<ApiErrorCode>),
 
     /// A `serde_json` crate error
     Json(JsonError),
@@ -22,22 +50,680 @@ pub enum Error {
 
     /// Challonge-rs error.
     Api(&'static str),
+
+    /// The request url is too long to be sent and the request can not carry its parameters in a body.
+    /// Contains the length of the url.
+    UrlTooLong(usize),
+
+    /// Challonge is down for maintenance (a 503 response without a json body).
+    /// `retry_after` is taken from the `Retry-After` header when present.
+    Maintenance {
+        /// Time to wait before retrying
+        retry_after: Option<Duration>,
+    },
+
+    /// The API key has no write access to a subdomain or a collaborator-managed tournament.
+    /// The subdomain and the tournament are filled in when they are known from the request.
+    InsufficientPermissions {
+        /// Subdomain the request was scoped to
+        subdomain: Option<String>,
+
+        /// Tournament the request was made for
+        tournament: Option<TournamentId>,
+    },
+
+    /// The sandbox guard of the client refused to modify a tournament whose url lacks the sandbox prefix.
+    SandboxViolation(TournamentId),
+
+    /// The tournament does not accept match attachments (its `accept_attachments` attribute is `false`).
+    AttachmentsNotAccepted(TournamentId),
+
+    /// An update with a precondition was not sent because the resource was modified since it was seen.
+    Conflict {
+        /// `updated_at` of the resource as it was seen by the caller
+        seen: ChallongeDateTime,
+
+        /// `updated_at` of the resource as it is now
+        current: ChallongeDateTime,
+    },
+
+    /// A participant with the same name, email or username is already registered in the tournament.
+    DuplicateParticipant {
+        /// The field which is already taken
+        field: DuplicateField,
+    },
+
+    /// The response body is larger than the limit of the client, in bytes.
+    ResponseTooLarge(u64),
+
+    /// The response json nests deeper than the limit of the client.
+    JsonTooDeep(usize),
+
+    /// A multi-step operation failed and its performed steps were rolled back.
+    RolledBack {
+        /// The error which made the operation fail
+        cause: Box<Error>,
+
+        /// What was rolled back
+        report: RollbackReport,
+    },
+
+    /// The confirmation hook of the client declined the described destructive action.
+    Cancelled(String),
+
+    /// The request did not complete within the timeout of the transport or the deadline of the call.
+    Timeout,
+
+    /// Challonge throttled the client (a 429 response).
+    /// `retry_after` is taken from the `Retry-After` or `RateLimit-Reset` header when present.
+    RateLimited {
+        /// Time to wait before retrying
+        retry_after: Option<Duration>,
+    },
+
+    /// The circuit breaker of the client is open after repeated server failures, the request was not sent.
+    CircuitOpen {
+        /// Time until the breaker lets a trial request through
+        retry_after: Duration,
+    },
+}
+impl Error {
+    /// Creates a `Error` from a non-success transport response.
+    pub fn error_from_response(response: Response) -> Error {
+        let value = response.json().ok();
+        if response.status == 503 && value.is_none() {
+            return Error::Maintenance {
+                retry_after: retry_after(&response),
+            };
+        }
+        if response.status == 429 {
+            return Error::RateLimited {
+                retry_after: retry_after(&response).or_else(|| {
+                    response
+                        .header("RateLimit-Reset")
+                        .and_then(|v| v.trim().parse::<u64>().ok())
+                        .map(Duration::from_secs)
+                }),
+            };
+        }
+        let text = match value {
+            Some(ref v) => api_messages(v).unwrap_or_default(),
+            None => String::from_utf8_lossy(&response.body).into_owned(),
+        };
+        if is_permission_denied(response.status, &text) {
+            return Error::InsufficientPermissions {
+                subdomain: None,
+                tournament: None,
+            };
+        }
+        Error::status(response.status, value)
+    }
+
+    /// Creates an `Error::Status`, parsing the codes of the messages in `body`.
+    pub fn status(status: u16, body: Option<Value>) -> Error {
+        let codes = match body {
+            Some(ref body) => api_message_list(body)
+                .into_iter()
+                .map(ApiErrorCode::from_message)
+                .collect(),
+            None => Vec::new(),
+        };
+        Error::Status(status, body, codes)
+    }
+
+    /// Maps a validation error of a participant request which reports an already taken field
+    /// to `Error::DuplicateParticipant`, other errors are returned as is.
+    pub fn duplicate_participant(self) -> Error {
+        let field = match self {
+            Error::Status(422, _, ref codes) => codes
+                .iter()
+                .filter_map(|code| match *code {
+                    ApiErrorCode::Duplicate(field) => Some(field),
+                    _ => None,
+                })
+                .next(),
+            _ => None,
+        };
+        match field {
+            Some(field) => Error::DuplicateParticipant { field: field },
+            None => self,
+        }
+    }
+
+    /// Returns `true` if the failed request may succeed when retried later.
+    pub fn is_retryable(&self) -> bool {
+        match *self {
+            Error::Maintenance { .. }
+            | Error::RateLimited { .. }
+            | Error::Io(_)
+            | Error::Timeout => true,
+            Error::Status(status, _, _) => status >= 500,
+            _ => false,
+        }
+    }
+
+    /// Returns the codes of the messages in the body of a rejected request, so callers can match
+    /// on them instead of the English text. Empty for errors without a Challonge error body.
+    /// The codes are parsed once, when the error is created.
+    pub fn api_codes(&self) -> Vec<ApiErrorCode> {
+        match *self {
+            Error::Status(_, _, ref codes) => codes.clone(),
+            Error::DuplicateParticipant { field } => vec![ApiErrorCode::Duplicate(field)],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns how long to wait before retrying the failed request, `None` if it is not retryable.
+    /// Maintenance windows get a longer backoff than other failures.
+    pub fn retry_delay(&self) -> Option<Duration> {
+        match *self {
+            Error::Maintenance { retry_after } => {
+                Some(retry_after.unwrap_or(Duration::from_secs(MAINTENANCE_BACKOFF_SECS)))
+            }
+            Error::RateLimited {
+                retry_after: Some(after),
+            } => Some(after),
+            _ if self.is_retryable() => Some(Duration::from_secs(RETRY_BACKOFF_SECS)),
+            _ => None,
+        }
+    }
+}
+/// HTTP-style problem details of an error as described by [RFC 7807](https://tools.ietf.org/html/rfc7807).
+///
+/// Services which proxy Challonge operations can return it to their own clients.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    /// URI reference identifying the problem type
+    pub problem_type: String,
+
+    /// Short summary of the problem type
+    pub title: String,
+
+    /// HTTP status code suggested for the proxied response
+    pub status: u16,
+
+    /// Explanation specific to this occurrence of the problem
+    pub detail: String,
+}
+impl Problem {
+    fn new(kind: &str, title: &str, status: u16, detail: String) -> Problem {
+        Problem {
+            problem_type: format!("urn:challonge:error:{}", kind),
+            title: title.to_owned(),
+            status: status,
+            detail: detail,
+        }
+    }
+
+    /// Returns the problem as an `application/problem+json` object.
+    pub fn to_json(&self) -> Value {
+        let mut map = BTreeMap::new();
+        map.insert("type".to_owned(), Value::String(self.problem_type.clone()));
+        map.insert("title".to_owned(), Value::String(self.title.clone()));
+        map.insert("status".to_owned(), Value::U64(self.status as u64));
+        map.insert("detail".to_owned(), Value::String(self.detail.clone()));
+        Value::Object(map)
+    }
+}
+
+/// Reads the `Retry-After` header, given either in seconds or as an HTTP date.
+/// A date is taken relative to the `Date` header of the response, so the local clock does not matter.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = match response.header("Retry-After") {
+        Some(v) => v.trim(),
+        None => return None,
+    };
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = DateTime::<FixedOffset>::parse_from_rfc2822(value).ok();
+    let now = response
+        .header("Date")
+        .and_then(|d| DateTime::<FixedOffset>::parse_from_rfc2822(d.trim()).ok());
+    match (at, now) {
+        (Some(at), Some(now)) => Some(Duration::from_secs((at - now).num_seconds().max(0) as u64)),
+        _ => None,
+    }
+}
+
+/// A known error of the API, recognized from the English message Challonge returns for it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiErrorCode {
+    /// Another tournament already uses the url
+    UrlTaken,
+
+    /// The url contains characters other than letters, numbers and underscores
+    UrlInvalid,
+
+    /// The name is missing
+    NameBlank,
+
+    /// The name is longer than Challonge allows
+    NameTooLong,
+
+    /// A participant with the same name, email or username is already registered
+    Duplicate(DuplicateField),
+
+    /// The tournament has already been started, so the request is not allowed anymore
+    TournamentAlreadyStarted,
+
+    /// The tournament can not be started with fewer than 2 participants
+    NotEnoughParticipants,
+
+    /// The seed of a participant is out of range
+    InvalidSeed,
+
+    /// A message which is not in the table, as returned by Challonge
+    Other(String),
+}
+impl ApiErrorCode {
+    /// Maps a message of a Challonge error body to its code, unknown messages to `ApiErrorCode::Other`.
+    pub fn from_message(message: &str) -> ApiErrorCode {
+        if let Some(field) = DuplicateField::from_message(message) {
+            return ApiErrorCode::Duplicate(field);
+        }
+        let lower = message.to_lowercase();
+        API_ERROR_CODES
+            .iter()
+            .find(|&&(phrase, _)| lower.contains(phrase))
+            .map_or(ApiErrorCode::Other(message.to_owned()), |&(_, ref code)| {
+                code.clone()
+            })
+    }
 }
+
+/// Phrases of the messages Challonge returns for the known errors, in lowercase.
+const API_ERROR_CODES: &'static [(&'static str, ApiErrorCode)] = &[
+    ("url has already been taken", ApiErrorCode::UrlTaken),
+    ("url is taken", ApiErrorCode::UrlTaken),
+    ("url is invalid", ApiErrorCode::UrlInvalid),
+    ("url can only contain", ApiErrorCode::UrlInvalid),
+    ("name can't be blank", ApiErrorCode::NameBlank),
+    ("name is too long", ApiErrorCode::NameTooLong),
+    (
+        "already been started",
+        ApiErrorCode::TournamentAlreadyStarted,
+    ),
+    ("already started", ApiErrorCode::TournamentAlreadyStarted),
+    ("already underway", ApiErrorCode::TournamentAlreadyStarted),
+    (
+        "at least 2 participants",
+        ApiErrorCode::NotEnoughParticipants,
+    ),
+    (
+        "at least two participants",
+        ApiErrorCode::NotEnoughParticipants,
+    ),
+    ("seed is invalid", ApiErrorCode::InvalidSeed),
+    ("seed must be", ApiErrorCode::InvalidSeed),
+];
+
+/// Phrases Challonge uses when an API key may not modify a tournament.
+const PERMISSION_PHRASES: &'static [&'static str] = &[
+    "permission",
+    "not authorized",
+    "unauthorized",
+    "access denied",
+    "have access",
+];
+
+fn is_permission_denied(status: u16, text: &str) -> bool {
+    let text = text.to_lowercase();
+    match status {
+        403 => true,
+        401 | 422 => PERMISSION_PHRASES.iter().any(|p| text.contains(p)),
+        _ => false,
+    }
+}
+
+/// Returns the messages of a Challonge error body (`{"errors": ["..."]}`).
+fn api_message_list(value: &Value) -> Vec<&str> {
+    match value.as_object().and_then(|m| m.get("errors")) {
+        Some(&Value::Array(ref errors)) => errors.iter().filter_map(|e| e.as_string()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Joins the messages of a Challonge error body (`{"errors": ["..."]}`).
+fn api_messages(value: &Value) -> Option<String> {
+    let messages = api_message_list(value);
+    if messages.is_empty() {
+        None
+    } else {
+        Some(messages.join("; "))
+    }
+}
+
 impl Error {
-    /// Creates a `Error` from `hyper`'s client response.
-    pub fn error_from_response(response: hyper::client::Response) -> Error {
-        let status = response.status;
-        let value = ::serde_json::from_reader(response).ok();
-        Error::Status(status, value)
+    /// Converts the error into RFC 7807-style problem details.
+    /// Failures of Challonge itself are reported as `502 Bad Gateway`, client errors keep their status.
+    pub fn to_problem(&self) -> Problem {
+        match *self {
+            #[cfg(feature = "hyper")]
+            Error::Hyper(ref e) => Problem::new("transport", "Transport error", 502, e.to_string()),
+            #[cfg(feature = "keyring")]
+            Error::Keyring(ref e) => {
+                Problem::new("keyring", "Credential store error", 500, e.to_string())
+            }
+            Error::Io(ref e) => Problem::new("transport", "Transport error", 502, e.to_string()),
+            Error::Transport(ref e) => Problem::new("transport", "Transport error", 502, e.clone()),
+            Error::Status(status, ref body, _) => {
+                let detail = body
+                    .as_ref()
+                    .and_then(api_messages)
+                    .unwrap_or_else(|| format!("Challonge responded with status {}", status));
+                let status = if status >= 400 && status < 500 {
+                    status
+                } else {
+                    502
+                };
+                Problem::new("status", "Request rejected by Challonge", status, detail)
+            }
+            Error::Json(ref e) => Problem::new("json", "Invalid response", 502, e.to_string()),
+            Error::Decode(description, _) => {
+                Problem::new("decode", "Invalid response", 502, description.to_owned())
+            }
+            Error::Api(description) => {
+                Problem::new("api", "Invalid request", 400, description.to_owned())
+            }
+            Error::UrlTooLong(len) => Problem::new(
+                "url-too-long",
+                "Request url is too long",
+                414,
+                format!("The request url is {} characters long", len),
+            ),
+            Error::Maintenance { retry_after } => Problem::new(
+                "maintenance",
+                "Challonge is under maintenance",
+                503,
+                match retry_after {
+                    Some(d) => format!("Retry after {} seconds", d.as_secs()),
+                    None => "Retry later".to_owned(),
+                },
+            ),
+            Error::InsufficientPermissions {
+                ref subdomain,
+                ref tournament,
+            } => Problem::new(
+                "insufficient-permissions",
+                "Insufficient permissions",
+                403,
+                match (subdomain.as_ref(), tournament.as_ref()) {
+                    (_, Some(t)) => format!("The API key may not modify tournament {}", t),
+                    (Some(s), None) => format!("The API key may not modify subdomain {}", s),
+                    (None, None) => "The API key may not perform this operation".to_owned(),
+                },
+            ),
+            Error::SandboxViolation(ref id) => Problem::new(
+                "sandbox-violation",
+                "Tournament is outside of the sandbox",
+                403,
+                format!("Tournament {} does not carry the sandbox prefix", id),
+            ),
+            Error::AttachmentsNotAccepted(ref id) => Problem::new(
+                "attachments-not-accepted",
+                "Attachments are not accepted",
+                409,
+                format!("Tournament {} does not accept match attachments", id),
+            ),
+            Error::Conflict { seen, current } => Problem::new(
+                "conflict",
+                "Resource was modified",
+                409,
+                format!(
+                    "The resource was modified at {}, after it was seen at {}",
+                    current, seen
+                ),
+            ),
+            Error::DuplicateParticipant { field } => Problem::new(
+                "duplicate-participant",
+                "Participant is already registered",
+                409,
+                format!(
+                    "A participant with the same {} is already registered",
+                    match field {
+                        DuplicateField::Name => "name",
+                        DuplicateField::Email => "email",
+                        DuplicateField::Username => "username",
+                    }
+                ),
+            ),
+            Error::ResponseTooLarge(limit) => Problem::new(
+                "response-too-large",
+                "Invalid response",
+                502,
+                format!("The response is larger than {} bytes", limit),
+            ),
+            Error::JsonTooDeep(limit) => Problem::new(
+                "json-too-deep",
+                "Invalid response",
+                502,
+                format!("The response json nests deeper than {} levels", limit),
+            ),
+            Error::RolledBack {
+                ref cause,
+                ref report,
+            } => {
+                let mut problem = cause.to_problem();
+                problem.detail = format!(
+                    "{} ({} steps rolled back, {} could not be rolled back)",
+                    problem.detail,
+                    report.compensated.len(),
+                    report.failed.len()
+                );
+                problem
+            }
+            Error::Cancelled(ref action) => Problem::new(
+                "cancelled",
+                "Operation was cancelled",
+                409,
+                format!("{} was declined by the operator", action),
+            ),
+            Error::Timeout => Problem::new(
+                "timeout",
+                "Challonge did not respond in time",
+                504,
+                "The request did not complete before its deadline".to_owned(),
+            ),
+            Error::CircuitOpen { retry_after } => Problem::new(
+                "circuit-open",
+                "Challonge is unavailable",
+                503,
+                format!(
+                    "Requests are suspended after repeated failures, retry after {} seconds",
+                    retry_after.as_secs()
+                ),
+            ),
+            Error::RateLimited { retry_after } => Problem::new(
+                "rate-limited",
+                "Rate limit of Challonge exceeded",
+                429,
+                match retry_after {
+                    Some(d) => format!("Retry after {} seconds", d.as_secs()),
+                    None => "Retry later".to_owned(),
+                },
+            ),
+        }
     }
 }
-impl From<hyper::Error> for Error {
-    fn from(err: hyper::Error) -> Error {
+#[cfg(feature = "hyper")]
+impl From<::hyper::Error> for Error {
+    fn from(err: ::hyper::Error) -> Error {
         Error::Hyper(err)
     }
 }
+#[cfg(feature = "keyring")]
+impl From<::keyring::Error> for Error {
+    fn from(err: ::keyring::Error) -> Error {
+        Error::Keyring(err)
+    }
+}
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
 impl From<JsonError> for Error {
     fn from(err: JsonError) -> Error {
         Error::Json(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use error::{ApiErrorCode, Error};
+    use participants::DuplicateField;
+    use std::time::Duration;
+    use transport::Response;
+
+    fn response(status: u16, headers: Vec<(&str, &str)>, body: &str) -> Response {
+        Response {
+            status: status,
+            headers: headers
+                .into_iter()
+                .map(|(n, v)| (n.to_owned(), v.to_owned()))
+                .collect(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_maintenance_detection() {
+        let html = "<html><body>Challonge is down for maintenance</body></html>";
+        let e = Error::error_from_response(response(503, vec![("Retry-After", "120")], html));
+        match e {
+            Error::Maintenance { retry_after } => {
+                assert_eq!(retry_after, Some(Duration::from_secs(120)))
+            }
+            _ => assert!(false),
+        }
+        assert!(e.is_retryable());
+        assert_eq!(e.retry_delay(), Some(Duration::from_secs(120)));
+
+        let e = Error::error_from_response(response(503, vec![], html));
+        assert_eq!(e.retry_delay(), Some(Duration::from_secs(300)));
+
+        let e = Error::error_from_response(response(503, vec![], r#"{"errors": ["x"]}"#));
+        match e {
+            Error::Status(503, Some(_), _) => {}
+            _ => assert!(false),
+        }
+
+        let e = Error::error_from_response(response(422, vec![], r#"{"errors": ["x"]}"#));
+        assert!(!e.is_retryable());
+        assert_eq!(e.retry_delay(), None);
+    }
+
+    #[test]
+    fn test_permission_detection() {
+        let body = r#"{"errors": ["You don't have permission to modify this tournament"]}"#;
+        match Error::error_from_response(response(401, vec![], body)) {
+            Error::InsufficientPermissions { .. } => {}
+            _ => assert!(false),
+        }
+        match Error::error_from_response(response(403, vec![], "")) {
+            Error::InsufficientPermissions { .. } => {}
+            _ => assert!(false),
+        }
+        let body = r#"{"errors": ["Invalid API key"]}"#;
+        match Error::error_from_response(response(401, vec![], body)) {
+            Error::Status(401, _, _) => {}
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_detection() {
+        let e = Error::error_from_response(response(429, vec![("Retry-After", "30")], ""));
+        match e {
+            Error::RateLimited { retry_after } => {
+                assert_eq!(retry_after, Some(Duration::from_secs(30)))
+            }
+            _ => assert!(false),
+        }
+        assert!(e.is_retryable());
+        assert_eq!(e.to_problem().status, 429);
+
+        let headers = vec![
+            ("Date", "Wed, 21 Oct 2015 07:28:00 GMT"),
+            ("Retry-After", "Wed, 21 Oct 2015 07:30:00 GMT"),
+        ];
+        let e = Error::error_from_response(response(429, headers, r#"{"errors": ["x"]}"#));
+        assert_eq!(e.retry_delay(), Some(Duration::from_secs(120)));
+
+        let e = Error::error_from_response(response(429, vec![("RateLimit-Reset", "7")], ""));
+        assert_eq!(e.retry_delay(), Some(Duration::from_secs(7)));
+
+        let e = Error::error_from_response(response(429, vec![], ""));
+        assert_eq!(e.retry_delay(), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_api_codes() {
+        let body = r#"{"errors": ["URL has already been taken", "Name is too long (maximum is 60 characters)",
+            "Email has already been taken", "Something new"]}"#;
+        let e = Error::error_from_response(response(422, vec![], body));
+        assert_eq!(
+            e.api_codes(),
+            vec![
+                ApiErrorCode::UrlTaken,
+                ApiErrorCode::NameTooLong,
+                ApiErrorCode::Duplicate(DuplicateField::Email),
+                ApiErrorCode::Other("Something new".to_owned()),
+            ]
+        );
+        assert_eq!(
+            e.duplicate_participant().api_codes(),
+            vec![ApiErrorCode::Duplicate(DuplicateField::Email)]
+        );
+        assert!(Error::Timeout.api_codes().is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_participant() {
+        let body = r#"{"errors": ["Seed is invalid", "Email has already been taken"]}"#;
+        match Error::error_from_response(response(422, vec![], body)).duplicate_participant() {
+            Error::DuplicateParticipant { field } => assert_eq!(field, DuplicateField::Email),
+            _ => assert!(false),
+        }
+        let body = r#"{"errors": ["Name has already been taken"]}"#;
+        match Error::error_from_response(response(422, vec![], body)).duplicate_participant() {
+            Error::DuplicateParticipant { field } => assert_eq!(field, DuplicateField::Name),
+            _ => assert!(false),
+        }
+        let body = r#"{"errors": ["Name can't be blank"]}"#;
+        match Error::error_from_response(response(422, vec![], body)).duplicate_participant() {
+            Error::Status(422, _, _) => {}
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_problem_details() {
+        let body = r#"{"errors": ["Name can't be blank", "URL is taken"]}"#;
+        let p = Error::error_from_response(response(422, vec![], body)).to_problem();
+        assert_eq!(p.problem_type, "urn:challonge:error:status");
+        assert_eq!(p.status, 422);
+        assert_eq!(p.detail, "Name can't be blank; URL is taken");
+
+        let p = Error::error_from_response(response(500, vec![], "")).to_problem();
+        assert_eq!(p.status, 502);
+        assert_eq!(p.detail, "Challonge responded with status 500");
+
+        let p = Error::Maintenance {
+            retry_after: Some(Duration::from_secs(60)),
+        }
+        .to_problem();
+        assert_eq!(p.status, 503);
+        let json = p.to_json();
+        let map = json.as_object().unwrap();
+        assert_eq!(
+            map.get("type").unwrap().as_string(),
+            Some("urn:challonge:error:maintenance")
+        );
+        assert_eq!(
+            map.get("detail").unwrap().as_string(),
+            Some("Retry after 60 seconds")
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! Conditional GET requests with ETags.
+//!
+//! With `Challonge::with_etag_cache` the client remembers the `ETag` of GET responses by url and
+//! sends it back in `If-None-Match`. When Challonge answers `304 Not Modified`, the remembered
+//! response is decoded again instead, so pollers fetching the same match list every few seconds
+//! only download the bodies which changed. The cache is shared by the clones of the client.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use transport::{Method, Request, Response};
+
+#[derive(Debug, Default)]
+struct Entries {
+    responses: HashMap<String, (String, Response)>,
+    order: VecDeque<String>,
+}
+
+/// Responses of GET requests with their ETags, keyed by url.
+/// The oldest response is dropped once `capacity` urls are cached.
+#[derive(Debug)]
+pub struct EtagCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+impl EtagCache {
+    /// Creates an empty cache of up to `capacity` responses.
+    pub fn new(capacity: usize) -> EtagCache {
+        EtagCache {
+            capacity: capacity.max(1),
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Returns the largest number of cached responses.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of cached responses.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().responses.len()
+    }
+
+    /// Returns `true` if no response is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds `If-None-Match` to a GET request whose url has a cached response.
+    pub fn prepare(&self, request: &mut Request) {
+        if request.method != Method::Get {
+            return;
+        }
+        let entries = self.entries.lock().unwrap();
+        if let Some(&(ref etag, _)) = entries.responses.get(&request.url) {
+            request
+                .headers
+                .push(("If-None-Match".to_owned(), etag.clone()));
+        }
+    }
+
+    /// Returns the response to a request: the cached one if the server answered `304 Not Modified`,
+    /// otherwise the received one, which is cached if it carries an ETag.
+    pub fn revalidate(&self, request: &Request, response: Response) -> Response {
+        if request.method != Method::Get {
+            return response;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if response.status == 304 {
+            return match entries.responses.get(&request.url) {
+                Some(&(_, ref cached)) => cached.clone(),
+                None => response,
+            };
+        }
+        let etag = match response.header("ETag") {
+            Some(etag) if response.is_success() => etag.to_owned(),
+            _ => return response,
+        };
+        if entries
+            .responses
+            .insert(request.url.clone(), (etag, response.clone()))
+            .is_none()
+        {
+            entries.order.push_back(request.url.clone());
+        }
+        while entries.order.len() > self.capacity {
+            if let Some(url) = entries.order.pop_front() {
+                entries.responses.remove(&url);
+            }
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use etag::EtagCache;
+    use transport::{Method, Request, Response};
+
+    fn response(status: u16, etag: &str, body: &str) -> Response {
+        Response {
+            status: status,
+            headers: vec![("ETag".to_owned(), etag.to_owned())],
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_etag_cache() {
+        let cache = EtagCache::new(1);
+        let matches = Request::new(
+            Method::Get,
+            "https://api.challonge.com/v1/tournaments/1/matches.json",
+        );
+
+        let mut request = matches.clone();
+        cache.prepare(&mut request);
+        assert_eq!(request.header("If-None-Match"), None);
+        cache.revalidate(&request, response(200, "\"v1\"", "[]"));
+
+        let mut request = matches.clone();
+        cache.prepare(&mut request);
+        assert_eq!(request.header("If-None-Match"), Some("\"v1\""));
+        let r = cache.revalidate(&request, response(304, "\"v1\"", ""));
+        assert_eq!((r.status, &r.body[..]), (200, &b"[]"[..]));
+
+        // the oldest url is dropped over capacity
+        let other = Request::new(
+            Method::Get,
+            "https://api.challonge.com/v1/tournaments/2/matches.json",
+        );
+        cache.revalidate(&other, response(200, "\"v7\"", "[]"));
+        assert_eq!(cache.len(), 1);
+        let mut request = matches.clone();
+        cache.prepare(&mut request);
+        assert_eq!(request.header("If-None-Match"), None);
+    }
+}
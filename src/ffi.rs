@@ -0,0 +1,172 @@
+//! Minimal C ABI (`ffi` feature) for scripting languages.
+//!
+//! Build a shared library with `cargo rustc --release --features ffi -- --crate-type cdylib`
+//! and declare the functions from `include/challonge.h` (e.g. with Python `ctypes` or `cffi`).
+//!
+//! Strings are UTF-8 and NUL-terminated. Strings returned by the library must be released with
+//! `challonge_string_free`. Failed calls return a null pointer or a non-zero status and store a
+//! description of the error, which `challonge_last_error` returns until the next failed call of the thread.
+//! Panics do not cross the ABI, they fail the call like errors do.
+
+extern crate serde_json;
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use error::Error;
+use matches::{MatchId, MatchScores, MatchUpdate};
+use participants::ParticipantId;
+use tournament::TournamentIncludes;
+use {parse_tournament_path, Challonge};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+fn set_last_error(error: &Error) {
+    let message = format!("{:?}", error).replace('\0', " ");
+    LAST_ERROR.with(|e| *e.borrow_mut() = CString::new(message).ok());
+}
+
+/// Runs the body of an exported function, catching a panic since unwinding into C is undefined behavior.
+fn guard<T, F: FnOnce() -> Result<T, Error>>(f: F) -> Result<T, Error> {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(Err(Error::Api(
+        "The library panicked, the call was aborted",
+    )))
+}
+
+unsafe fn to_str<'a>(s: *const c_char) -> Result<&'a str, Error> {
+    if s.is_null() {
+        return Err(Error::Api("Null string passed"));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| Error::Api("String is not valid UTF-8"))
+}
+
+/// Creates a client. Returns null if an argument is not a valid string.
+/// The client must be released with `challonge_client_free`.
+#[no_mangle]
+pub unsafe extern "C" fn challonge_client_new(
+    user_name: *const c_char,
+    api_key: *const c_char,
+) -> *mut Challonge {
+    let client = guard(|| {
+        Challonge::builder()
+            .credentials(try!(to_str(user_name)), try!(to_str(api_key)))
+            .build()
+    });
+    match client {
+        Ok(client) => Box::into_raw(Box::new(client)),
+        Err(e) => {
+            set_last_error(&e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Releases a client created with `challonge_client_new`.
+#[no_mangle]
+pub unsafe extern "C" fn challonge_client_free(client: *mut Challonge) {
+    if !client.is_null() {
+        drop(Box::from_raw(client));
+    }
+}
+
+/// Fetches a tournament with its participants and matches as the JSON returned by the API.
+/// `tournament` is an id, url or `subdomain-url`. Returns null on failure.
+#[no_mangle]
+pub unsafe extern "C" fn challonge_tournament_json(
+    client: *const Challonge,
+    tournament: *const c_char,
+) -> *mut c_char {
+    let result = guard(|| {
+        let client = try!(client.as_ref().ok_or(Error::Api("Null client passed")));
+        let id = parse_tournament_path(try!(to_str(tournament)));
+        let json = try!(client.get_tournament_json(&id, &TournamentIncludes::All));
+        Ok(try!(serde_json::to_string(&json)))
+    });
+    match result {
+        Ok(json) => CString::new(json)
+            .map(CString::into_raw)
+            .unwrap_or(ptr::null_mut()),
+        Err(e) => {
+            set_last_error(&e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Reports the scores of a match, e.g. `"3-1,2-3,3-0"`. `winner_id` of 0 only updates the scores.
+/// Returns 0 on success and -1 on failure.
+#[no_mangle]
+pub unsafe extern "C" fn challonge_report_score(
+    client: *const Challonge,
+    tournament: *const c_char,
+    match_id: u64,
+    scores_csv: *const c_char,
+    winner_id: u64,
+) -> c_int {
+    let result = guard(|| {
+        let client = try!(client.as_ref().ok_or(Error::Api("Null client passed")));
+        let id = parse_tournament_path(try!(to_str(tournament)));
+        let mut update = MatchUpdate::new();
+        update.scores_csv(MatchScores::decode(try!(to_str(scores_csv)).to_owned()));
+        if winner_id != 0 {
+            update.winner_id(ParticipantId(winner_id));
+        }
+        client.update_match(&id, &MatchId(match_id), &update)
+    });
+    match result {
+        Ok(_) => 0,
+        Err(e) => {
+            set_last_error(&e);
+            -1
+        }
+    }
+}
+
+/// Returns the description of the last error of the calling thread, null if there was none.
+/// The string is owned by the library and stays valid until the next failed call of the thread.
+#[no_mangle]
+pub extern "C" fn challonge_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// Releases a string returned by the library.
+#[no_mangle]
+pub unsafe extern "C" fn challonge_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use error::Error;
+    use ffi::{challonge_client_free, challonge_client_new, challonge_last_error, guard};
+    use std::ffi::{CStr, CString};
+    use std::ptr;
+
+    #[test]
+    fn test_client_lifecycle() {
+        let user = CString::new("user").unwrap();
+        let key = CString::new("key").unwrap();
+        unsafe {
+            let client = challonge_client_new(user.as_ptr(), key.as_ptr());
+            assert!(!client.is_null());
+            challonge_client_free(client);
+
+            assert!(challonge_client_new(user.as_ptr(), ptr::null()).is_null());
+            let error = CStr::from_ptr(challonge_last_error()).to_str().unwrap();
+            assert!(error.contains("Null string passed"));
+        }
+        match guard(|| -> Result<(), Error> { panic!("bug") }) {
+            Err(Error::Api(_)) => {}
+            _ => assert!(false),
+        }
+    }
+}
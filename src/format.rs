@@ -0,0 +1,107 @@
+//! Formatting of match scores for display.
+
+use matches::{Match, MatchScore, MatchScores};
+
+/// How the scores of a match are presented.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScoreStyle {
+    /// Every set is shown separately (e.g. "3-1, 2-3, 3-0").
+    PerSet,
+
+    /// Only the number of won sets is shown (e.g. "2-1").
+    Aggregate,
+}
+
+/// Score formatting options.
+#[derive(Debug, Clone)]
+pub struct ScoreFormat {
+    /// Separator between the scores of two players (e.g. "-" or "–")
+    pub separator: String,
+
+    /// Separator between sets
+    pub set_separator: String,
+
+    /// Presentation of the scores
+    pub style: ScoreStyle,
+
+    /// Put the score of the winner first
+    pub winner_first: bool,
+}
+impl ScoreFormat {
+    /// Creates new `ScoreFormat` with default values: "3-1, 2-3" with player 1 first.
+    pub fn new() -> ScoreFormat {
+        ScoreFormat {
+            separator: "-".to_owned(),
+            set_separator: ", ".to_owned(),
+            style: ScoreStyle::PerSet,
+            winner_first: false,
+        }
+    }
+
+    builder_s!(separator);
+    builder_s!(set_separator);
+    builder!(style, ScoreStyle);
+    builder!(winner_first, bool);
+
+    /// Formats scores. `player2_won` tells whether the second player won the match,
+    /// it is used to put the winner's score first if requested.
+    pub fn format(&self, scores: &MatchScores, player2_won: bool) -> String {
+        let swap = self.winner_first && player2_won;
+        let pairs: Vec<MatchScore> = match self.style {
+            ScoreStyle::PerSet => scores.0.clone(),
+            ScoreStyle::Aggregate => {
+                let mut sets = MatchScore(0, 0);
+                for s in &scores.0 {
+                    if s.0 > s.1 {
+                        sets.0 += 1;
+                    } else if s.1 > s.0 {
+                        sets.1 += 1;
+                    }
+                }
+                vec![sets]
+            }
+        };
+        pairs
+            .iter()
+            .map(|s| {
+                let (first, second) = if swap { (s.1, s.0) } else { (s.0, s.1) };
+                format!("{}{}{}", first, self.separator, second)
+            })
+            .collect::<Vec<String>>()
+            .join(&self.set_separator)
+    }
+
+    /// Formats scores of a match.
+    pub fn format_match(&self, m: &Match) -> String {
+        let player2_won = m.winner_id.is_some() && m.winner_id == m.player2.id;
+        self.format(&m.scores_csv, player2_won)
+    }
+}
+impl Default for ScoreFormat {
+    fn default() -> ScoreFormat {
+        ScoreFormat::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use format::{ScoreFormat, ScoreStyle};
+    use matches::{MatchScore, MatchScores};
+
+    #[test]
+    fn test_score_format() {
+        let scores = MatchScores(vec![MatchScore(3, 1), MatchScore(2, 3), MatchScore(1, 3)]);
+
+        let f = ScoreFormat::new();
+        assert_eq!(f.format(&scores, true), "3-1, 2-3, 1-3");
+
+        let mut f = ScoreFormat::new();
+        f.separator("–").set_separator(" / ").winner_first(true);
+        assert_eq!(f.format(&scores, true), "1–3 / 3–2 / 3–1");
+        assert_eq!(f.format(&scores, false), "3–1 / 2–3 / 1–3");
+
+        f.style(ScoreStyle::Aggregate);
+        assert_eq!(f.format(&scores, true), "2–1");
+        assert_eq!(f.format(&MatchScores(Vec::new()), false), "0–0");
+    }
+}
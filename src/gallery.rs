@@ -0,0 +1,126 @@
+//! Attachments of a whole tournament grouped by round and match.
+//!
+//! Useful for post-event media galleries. `Challonge::attachment_gallery` fetches the attachments
+//! of every match which has any, a few matches at a time and paced by `GALLERY_INTERVAL_MILLIS`.
+
+use std::collections::BTreeMap;
+
+use attachments::Attachment;
+use error::Error;
+use matches::{Match, MatchId};
+
+/// Pause between two batches of attachment requests, in milliseconds.
+pub const GALLERY_INTERVAL_MILLIS: u64 = 250;
+
+/// Attachments of a single match.
+#[derive(Debug, Clone)]
+pub struct GalleryMatch {
+    /// Id of the match
+    pub match_id: MatchId,
+
+    /// Human readable match identifier (e.g. "A")
+    pub identifier: String,
+
+    /// Attachments of the match in the order of creation
+    pub attachments: Vec<Attachment>,
+}
+
+/// Attachments of a tournament grouped by round and match.
+#[derive(Debug, Default)]
+pub struct Gallery {
+    /// Matches with attachments keyed by round, ordered by identifier within a round
+    pub rounds: BTreeMap<u64, Vec<GalleryMatch>>,
+
+    /// Matches whose attachments could not be fetched along with the errors
+    pub failed: Vec<(MatchId, Error)>,
+}
+impl Gallery {
+    /// Groups attachments of matches. Matches without attachments are left out.
+    pub fn new(matches: Vec<(Match, Vec<Attachment>)>) -> Gallery {
+        let mut gallery = Gallery::default();
+        for (m, mut attachments) in matches {
+            if attachments.is_empty() {
+                continue;
+            }
+            attachments.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+            gallery
+                .rounds
+                .entry(m.round)
+                .or_insert_with(Vec::new)
+                .push(GalleryMatch {
+                    match_id: m.id,
+                    identifier: m.identifier,
+                    attachments: attachments,
+                });
+        }
+        for matches in gallery.rounds.values_mut() {
+            matches.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+        }
+        gallery
+    }
+
+    /// Returns the number of attachments in the gallery.
+    pub fn len(&self) -> usize {
+        self.rounds
+            .values()
+            .flat_map(|matches| matches.iter())
+            .map(|m| m.attachments.len())
+            .sum()
+    }
+
+    /// Returns `true` if the attachments of every match were fetched.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Returns `true` if the gallery has no attachments.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use attachments::Attachment;
+    use gallery::Gallery;
+    use matches::{Match, MatchId};
+
+    const MATCH: &'static str = r#"{"match": {"attachment_count": 1, "created_at": "2015-01-19T16:57:17-05:00",
+        "has_attachment": true, "id": 1, "identifier": "A", "loser_id": null,
+        "player1_id": 16543993, "player1_is_prereq_match_loser": false, "player1_prereq_match_id": null,
+        "player1_votes": null, "player2_id": 16543997, "player2_is_prereq_match_loser": false,
+        "player2_prereq_match_id": null, "player2_votes": null, "round": 1, "started_at": null,
+        "state": "complete", "tournament_id": 1086875, "updated_at": "2015-01-19T16:57:17-05:00",
+        "winner_id": 16543993, "prerequisite_match_ids_csv": "", "scores_csv": "3-1"}}"#;
+
+    const ATTACHMENT: &'static str = r#"{"match_attachment": {"id": 7, "match_id": 1, "user_id": 3,
+        "description": "Final screenshot", "url": null, "original_file_name": "final.png",
+        "created_at": "2015-01-19T17:00:00-05:00", "updated_at": "2015-01-19T17:00:00-05:00",
+        "asset_file_name": "final.png", "asset_content_type": "image/png", "asset_file_size": 1024,
+        "asset_url": "//s3.amazonaws.com/final.png"}}"#;
+
+    #[test]
+    fn test_gallery_grouping() {
+        let first = Match::decode(&serde_json::from_str(MATCH).unwrap()).unwrap();
+        let mut second = first.clone();
+        second.id = MatchId(2);
+        second.identifier = "B".to_owned();
+        let mut final_match = first.clone();
+        final_match.id = MatchId(3);
+        final_match.round = 2;
+        let attachment = Attachment::decode(&serde_json::from_str(ATTACHMENT).unwrap()).unwrap();
+
+        let gallery = Gallery::new(vec![
+            (final_match, vec![attachment.clone()]),
+            (second, vec![attachment.clone(), attachment.clone()]),
+            (first, Vec::new()),
+        ]);
+        assert_eq!(gallery.len(), 3);
+        assert_eq!(gallery.rounds.len(), 2);
+        assert_eq!(gallery.rounds[&1].len(), 1);
+        assert_eq!(gallery.rounds[&1][0].identifier, "B");
+        assert_eq!(gallery.rounds[&2][0].match_id, MatchId(3));
+    }
+}
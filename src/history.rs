@@ -0,0 +1,146 @@
+//! Enumeration of old tournaments by creation date windows.
+//!
+//! Deep pages of a large index are unreliable, so the history is requested
+//! window by window (e.g. month by month) with `created_after`/`created_before` filters.
+//! Tournaments are decoded with `Tournament::decode_legacy`, so very old ones do not stop the walk.
+
+use chrono::*;
+use std::collections::{HashSet, VecDeque};
+
+use error::Error;
+use tournament::{Tournament, TournamentState};
+use Challonge;
+
+/// Splits the date range `[from, to]` into consecutive windows of `step` length.
+/// Neighbouring windows share their boundary date, as the API filters are inclusive on some accounts
+/// and exclusive on others; tournaments seen twice are deduplicated by `TournamentHistory`.
+pub fn windows(
+    from: Date<Local>,
+    to: Date<Local>,
+    step: Duration,
+) -> Vec<(Date<Local>, Date<Local>)> {
+    let step = if step < Duration::days(1) {
+        Duration::days(1)
+    } else {
+        step
+    };
+    let mut windows = Vec::new();
+    let mut start = from;
+    while start <= to {
+        let end = start + step;
+        if end >= to {
+            windows.push((start, to));
+            break;
+        }
+        windows.push((start, end));
+        start = end;
+    }
+    windows
+}
+
+/// Iterator over tournaments created within a date range, oldest window first.
+///
+/// Every tournament is yielded once even if it is returned for several windows.
+/// A failed window yields the error and the iteration continues with the next window.
+pub struct TournamentHistory<'a> {
+    client: &'a Challonge,
+    windows: VecDeque<(Date<Local>, Date<Local>)>,
+    pending: VecDeque<Tournament>,
+    seen: HashSet<String>,
+}
+impl<'a> TournamentHistory<'a> {
+    /// Creates an iterator over tournaments created between `from` and `to`, requested `step` at a time.
+    pub fn new(
+        client: &'a Challonge,
+        from: Date<Local>,
+        to: Date<Local>,
+        step: Duration,
+    ) -> TournamentHistory<'a> {
+        TournamentHistory {
+            client: client,
+            windows: windows(from, to, step).into_iter().collect(),
+            pending: VecDeque::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    fn enqueue(&mut self, tournaments: Vec<Tournament>) {
+        for t in tournaments {
+            if self.seen.insert(t.id.to_string()) {
+                self.pending.push_back(t);
+            }
+        }
+    }
+}
+impl<'a> Iterator for TournamentHistory<'a> {
+    type Item = Result<Tournament, Error>;
+
+    fn next(&mut self) -> Option<Result<Tournament, Error>> {
+        loop {
+            if let Some(t) = self.pending.pop_front() {
+                return Some(Ok(t));
+            }
+            let (start, end) = match self.windows.pop_front() {
+                Some(w) => w,
+                None => return None,
+            };
+            let index = self.client.fetch_legacy_tournament_index(&[
+                ("state", TournamentState::All.to_string()),
+                ("created_after", format_date!(start)),
+                ("created_before", format_date!(end)),
+            ]);
+            match index {
+                Ok(index) => self.enqueue(index.0),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use chrono::*;
+    use history::{windows, TournamentHistory};
+    use tournament::{Tournament, TournamentId};
+    use Challonge;
+
+    #[test]
+    fn test_history_windows_and_dedup() {
+        let from = Local.ymd(2016, 1, 1);
+        let to = Local.ymd(2016, 3, 15);
+        let w = windows(from, to, Duration::days(30));
+        assert_eq!(w.len(), 3);
+        assert_eq!(w[0], (from, Local.ymd(2016, 1, 31)));
+        assert_eq!(w[1], (Local.ymd(2016, 1, 31), Local.ymd(2016, 3, 1)));
+        assert_eq!(w[2], (Local.ymd(2016, 3, 1), to));
+        assert!(windows(to, from, Duration::days(30)).is_empty());
+
+        let t = Tournament::decode(
+            &serde_json::from_str(include_str!("../benches/fixtures/tournament.json")).unwrap(),
+        )
+        .unwrap();
+        let with_id = |id| {
+            let mut t = t.clone();
+            t.id = TournamentId::Id(id);
+            t
+        };
+        let c = Challonge::builder()
+            .credentials("user", "key")
+            .build()
+            .unwrap();
+        let mut h = TournamentHistory::new(&c, from, from, Duration::days(1));
+        h.enqueue(vec![with_id(1), with_id(2)]);
+        h.enqueue(vec![with_id(2), with_id(3)]);
+        let ids: Vec<TournamentId> = h.pending.iter().map(|t| t.id.clone()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                TournamentId::Id(1),
+                TournamentId::Id(2),
+                TournamentId::Id(3)
+            ]
+        );
+    }
+}
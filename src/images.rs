@@ -0,0 +1,162 @@
+//! Variants of the live bracket image of a tournament.
+//!
+//! `Tournament::live_image_url` points to a full-size PNG. Other formats and sizes are derived
+//! from it; not every tournament has every variant, `Challonge::available_live_images`
+//! checks which of them exist.
+
+use std::fmt;
+
+/// File format of a bracket image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageFormat {
+    /// Raster image
+    Png,
+
+    /// Vector image, scales to any resolution
+    Svg,
+}
+impl ImageFormat {
+    /// Returns the file extension of the format.
+    pub fn extension(&self) -> &'static str {
+        match *self {
+            ImageFormat::Png => "png",
+            ImageFormat::Svg => "svg",
+        }
+    }
+}
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(self.extension())
+    }
+}
+
+/// Size preset of a bracket image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageSize {
+    /// 400 pixels wide, for thumbnails and chat embeds
+    Small,
+
+    /// 800 pixels wide, for web pages
+    Medium,
+
+    /// 1600 pixels wide, for streams and print
+    Large,
+
+    /// The size Challonge renders by default
+    Original,
+}
+impl ImageSize {
+    /// Returns the width of the preset in pixels, `None` for the original size.
+    pub fn width(&self) -> Option<u32> {
+        match *self {
+            ImageSize::Small => Some(400),
+            ImageSize::Medium => Some(800),
+            ImageSize::Large => Some(1600),
+            ImageSize::Original => None,
+        }
+    }
+}
+
+/// Every known combination of format and size, smallest first.
+pub const IMAGE_VARIANTS: &'static [(ImageFormat, ImageSize)] = &[
+    (ImageFormat::Png, ImageSize::Small),
+    (ImageFormat::Png, ImageSize::Medium),
+    (ImageFormat::Png, ImageSize::Large),
+    (ImageFormat::Png, ImageSize::Original),
+    (ImageFormat::Svg, ImageSize::Original),
+];
+
+/// A variant of the live bracket image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveImage {
+    /// Format of the image
+    pub format: ImageFormat,
+
+    /// Size of the image
+    pub size: ImageSize,
+
+    /// Url of the image
+    pub url: String,
+}
+impl LiveImage {
+    /// Derives a variant from the live image url of a tournament, `None` if the url is empty.
+    pub fn from_url(
+        live_image_url: &str,
+        format: ImageFormat,
+        size: ImageSize,
+    ) -> Option<LiveImage> {
+        let url = live_image_url.trim();
+        if url.is_empty() {
+            return None;
+        }
+        let (path, query) = match url.find('?') {
+            Some(i) => (&url[..i], Some(&url[i + 1..])),
+            None => (url, None),
+        };
+        let name_start = path.rfind('/').map_or(0, |i| i + 1);
+        let base = match path[name_start..].rfind('.') {
+            Some(i) => &path[..name_start + i],
+            None => path,
+        };
+        let mut params: Vec<String> = query
+            .into_iter()
+            .flat_map(|q| q.split('&'))
+            .filter(|p| !p.is_empty() && !p.starts_with("width="))
+            .map(|p| p.to_owned())
+            .collect();
+        if let Some(width) = size.width() {
+            params.push(format!("width={}", width));
+        }
+        let mut url = format!("{}.{}", base, format.extension());
+        if !params.is_empty() {
+            url.push('?');
+            url.push_str(&params.join("&"));
+        }
+        Some(LiveImage {
+            format: format,
+            size: size,
+            url: url,
+        })
+    }
+
+    /// Derives every known variant from the live image url of a tournament.
+    pub fn variants(live_image_url: &str) -> Vec<LiveImage> {
+        IMAGE_VARIANTS
+            .iter()
+            .filter_map(|&(format, size)| LiveImage::from_url(live_image_url, format, size))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use images::{ImageFormat, ImageSize, LiveImage};
+
+    #[test]
+    fn test_live_image_variants() {
+        let url = "http://images.challonge.com/sample_tournament_1.png";
+        let variants = LiveImage::variants(url);
+        assert_eq!(variants.len(), 5);
+        assert_eq!(
+            variants[0].url,
+            "http://images.challonge.com/sample_tournament_1.png?width=400"
+        );
+        assert_eq!(variants[3].url, url);
+        assert_eq!(
+            variants[4].url,
+            "http://images.challonge.com/sample_tournament_1.svg"
+        );
+
+        let themed = LiveImage::from_url(
+            "http://images.challonge.com/a.b/cup.png?theme=2&width=10",
+            ImageFormat::Png,
+            ImageSize::Medium,
+        )
+        .unwrap();
+        assert_eq!(
+            themed.url,
+            "http://images.challonge.com/a.b/cup.png?theme=2&width=800"
+        );
+        assert!(LiveImage::variants("").is_empty());
+    }
+}
@@ -0,0 +1,263 @@
+//! Journal of watcher events.
+//!
+//! A journal is a JSONL file: every line is an event emitted by the `Watcher` with the time it was seen
+//! and the entity it carries in the API format. `Timeline::build` reconstructs how long each round
+//! of a tournament took from a journal, e.g. for post-event analysis.
+//!
+//! The journal keeps participant data unredacted, treat it like the tournament itself.
+
+extern crate serde_json;
+
+use chrono::Duration;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+
+use datetime::ChallongeDateTime;
+use error::Error;
+use matches::{Match, MatchState};
+use participants::{Participant, ParticipantId};
+use redaction::RedactionPolicy;
+use tournament::{Tournament, TournamentId};
+use util::{as_map, field};
+use watcher::Event;
+
+/// A recorded watcher event.
+#[derive(Debug, Clone)]
+pub struct JournalEntry {
+    /// Time when the event was seen
+    pub at: ChallongeDateTime,
+
+    /// Tournament the event belongs to
+    pub tournament: TournamentId,
+
+    /// The event
+    pub event: Event,
+}
+impl JournalEntry {
+    /// Encodes the entry into a single journal line.
+    pub fn encode(&self) -> Value {
+        let (kind, data) = match self.event {
+            Event::TournamentUpdated(ref t) => ("tournament_updated", t.encode()),
+            Event::SignupReceived(ref p) => ("signup_received", p.encode(&RedactionPolicy::none())),
+            Event::ParticipantRemoved(ref id) => ("participant_removed", Value::U64(id.0)),
+            Event::SignupCapReached => ("signup_cap_reached", Value::Null),
+            Event::MatchUpdated(ref m) => ("match_updated", m.encode()),
+        };
+        let mut map = BTreeMap::new();
+        map.insert("at".to_owned(), Value::String(self.at.to_string()));
+        map.insert(
+            "tournament".to_owned(),
+            Value::String(self.tournament.to_string()),
+        );
+        map.insert("event".to_owned(), Value::String(kind.to_owned()));
+        map.insert("data".to_owned(), data);
+        Value::Object(map)
+    }
+
+    /// Decodes an entry from a journal line.
+    pub fn decode(value: &Value) -> Result<JournalEntry, Error> {
+        let map = try!(as_map(value));
+        let data = try!(field(map, "data"));
+        let event = match try!(field(map, "event")).as_string() {
+            Some("tournament_updated") => Event::TournamentUpdated(try!(Tournament::decode(data))),
+            Some("signup_received") => Event::SignupReceived(try!(Participant::decode(data))),
+            Some("participant_removed") => match data.as_u64() {
+                Some(id) => Event::ParticipantRemoved(ParticipantId(id)),
+                None => return Err(Error::Decode("Expected participant id", data.clone())),
+            },
+            Some("signup_cap_reached") => Event::SignupCapReached,
+            Some("match_updated") => Event::MatchUpdated(try!(Match::decode(data))),
+            _ => return Err(Error::Decode("Unknown journal event", value.clone())),
+        };
+        let tournament = try!(field(map, "tournament")).as_string().unwrap_or("");
+        Ok(JournalEntry {
+            at: try!(ChallongeDateTime::decode_required(try!(field(map, "at")))),
+            tournament: match tournament.parse::<u64>() {
+                Ok(id) => TournamentId::Id(id),
+                Err(_) => TournamentId::Url(String::new(), tournament.to_owned()),
+            },
+            event: event,
+        })
+    }
+}
+
+/// Appends journal entries to a writer, one line per entry.
+pub struct JournalWriter<W: Write> {
+    writer: W,
+}
+impl<W: Write> JournalWriter<W> {
+    /// Creates a journal writer, usually over a file opened for appending.
+    pub fn new(writer: W) -> JournalWriter<W> {
+        JournalWriter { writer: writer }
+    }
+
+    /// Writes an entry and flushes it, so the journal survives a crash of the watcher.
+    pub fn write(&mut self, entry: &JournalEntry) -> Result<(), Error> {
+        let line = try!(serde_json::to_string(&entry.encode()));
+        try!(self.writer.write_all(line.as_bytes()));
+        try!(self.writer.write_all(b"\n"));
+        try!(self.writer.flush());
+        Ok(())
+    }
+}
+
+/// Reads every entry of a journal. Empty lines are skipped.
+pub fn read_journal<R: BufRead>(reader: R) -> Result<Vec<JournalEntry>, Error> {
+    let mut entries = Vec::new();
+    for line in reader.lines() {
+        let line = try!(line);
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value = try!(serde_json::from_str(&line));
+        entries.push(try!(JournalEntry::decode(&value)));
+    }
+    Ok(entries)
+}
+
+/// Time span of a round, as seen by the watcher.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundSpan {
+    /// Round number
+    pub round: u64,
+
+    /// Time when the first match of the round was seen open
+    pub started: ChallongeDateTime,
+
+    /// Time when the last match of the round was seen complete, `None` while the round is running
+    pub finished: Option<ChallongeDateTime>,
+}
+impl RoundSpan {
+    /// Returns how long the round took, `None` while it is running.
+    pub fn duration(&self) -> Option<Duration> {
+        self.finished.map(|f| f.0 - self.started.0)
+    }
+}
+
+/// Rounds of a tournament reconstructed from a journal.
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    /// The tournament
+    pub tournament: TournamentId,
+
+    /// Rounds ordered by number
+    pub rounds: Vec<RoundSpan>,
+}
+impl Timeline {
+    /// Builds timelines of every tournament in the journal from its match updates.
+    pub fn build(entries: &[JournalEntry]) -> Vec<Timeline> {
+        let mut tournaments: Vec<(TournamentId, BTreeMap<u64, RoundState>)> = Vec::new();
+        for entry in entries {
+            let m = match entry.event {
+                Event::MatchUpdated(ref m) => m,
+                _ => continue,
+            };
+            let pos = match tournaments.iter().position(|t| t.0 == entry.tournament) {
+                Some(pos) => pos,
+                None => {
+                    tournaments.push((entry.tournament.clone(), BTreeMap::new()));
+                    tournaments.len() - 1
+                }
+            };
+            let round = tournaments[pos]
+                .1
+                .entry(m.round)
+                .or_insert_with(RoundState::default);
+            round
+                .complete
+                .insert(m.id.0, m.state == MatchState::Complete);
+            if m.state != MatchState::Pending && round.started.is_none() {
+                round.started = Some(entry.at);
+            }
+            round.finished = if round.complete.values().all(|c| *c) {
+                Some(entry.at)
+            } else {
+                None
+            };
+        }
+
+        tournaments
+            .into_iter()
+            .map(|(id, rounds)| Timeline {
+                tournament: id,
+                rounds: rounds
+                    .into_iter()
+                    .filter_map(|(number, r)| {
+                        r.started.map(|started| RoundSpan {
+                            round: number,
+                            started: started,
+                            finished: r.finished,
+                        })
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct RoundState {
+    started: Option<ChallongeDateTime>,
+    finished: Option<ChallongeDateTime>,
+    complete: BTreeMap<u64, bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use chrono::Duration;
+    use datetime::ChallongeDateTime;
+    use journal::{read_journal, JournalEntry, JournalWriter, Timeline};
+    use matches::{Match, MatchState};
+    use tournament::TournamentId;
+    use watcher::Event;
+
+    const MATCH: &'static str = r#"{"match": {"attachment_count": null, "created_at": "2015-01-19T16:57:17-05:00",
+        "has_attachment": false, "id": 23575258, "identifier": "A", "loser_id": null,
+        "player1_id": 16543993, "player1_is_prereq_match_loser": false, "player1_prereq_match_id": null,
+        "player1_votes": null, "player2_id": 16543997, "player2_is_prereq_match_loser": false,
+        "player2_prereq_match_id": null, "player2_votes": null, "round": 1, "started_at": null,
+        "state": "open", "tournament_id": 1086875, "updated_at": "2015-01-19T16:57:17-05:00",
+        "winner_id": null, "prerequisite_match_ids_csv": "", "scores_csv": ""}}"#;
+
+    fn entry(at: &str, id: u64, state: MatchState) -> JournalEntry {
+        let mut m = Match::decode(&serde_json::from_str(MATCH).unwrap()).unwrap();
+        m.id.0 = id;
+        m.state = state;
+        JournalEntry {
+            at: ChallongeDateTime::parse(at).unwrap(),
+            tournament: TournamentId::Id(1086875),
+            event: Event::MatchUpdated(m),
+        }
+    }
+
+    #[test]
+    fn test_journal_timeline() {
+        let entries = vec![
+            entry("2015-01-19T17:00:00-05:00", 1, MatchState::Open),
+            entry("2015-01-19T17:00:00-05:00", 2, MatchState::Open),
+            entry("2015-01-19T17:40:00-05:00", 1, MatchState::Complete),
+            entry("2015-01-19T18:30:00-05:00", 2, MatchState::Complete),
+        ];
+        let mut buffer = Vec::new();
+        {
+            let mut writer = JournalWriter::new(&mut buffer);
+            for e in &entries {
+                writer.write(e).unwrap();
+            }
+        }
+        let read = read_journal(&buffer[..]).unwrap();
+        assert_eq!(read.len(), 4);
+        assert_eq!(read[2].at, entries[2].at);
+
+        let timelines = Timeline::build(&read);
+        assert_eq!(timelines.len(), 1);
+        assert_eq!(timelines[0].rounds.len(), 1);
+        assert_eq!(
+            timelines[0].rounds[0].duration(),
+            Some(Duration::minutes(90))
+        );
+    }
+}
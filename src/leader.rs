@@ -0,0 +1,233 @@
+//! Leader election between several instances watching the same tournament.
+//!
+//! When a bot runs in more than one instance, attach the same `LeaderLock` to the watcher of
+//! every instance with `Watcher::with_leader_lock`. Only the instance holding the lock polls and
+//! emits events, the others retry after a jittered interval and take over when the lock expires.
+//!
+//! `FileLock` works for instances sharing a file system. Other stores implement `LeaderLock`,
+//! e.g. with Redis:
+//!
+//! ```ignore
+//! struct RedisLock(redis::Client);
+//!
+//! impl LeaderLock for RedisLock {
+//!     fn try_acquire(&self, key: &str, holder: &str, ttl: Duration) -> Result<bool, Error> {
+//!         let mut con = self.0.get_connection().map_err(|e| Error::Transport(e.to_string()))?;
+//!         // take the free lock, or extend it when we already hold it
+//!         let script = redis::Script::new(
+//!             "if redis.call('SET', KEYS[1], ARGV[1], 'NX', 'PX', ARGV[2]) then return 1 end
+//!              if redis.call('GET', KEYS[1]) == ARGV[1] then
+//!                  return redis.call('PEXPIRE', KEYS[1], ARGV[2]) end
+//!              return 0",
+//!         );
+//!         let held: i32 = script.key(key).arg(holder).arg(ttl.as_millis() as u64)
+//!             .invoke(&mut con).map_err(|e| Error::Transport(e.to_string()))?;
+//!         Ok(held == 1)
+//!     }
+//!
+//!     fn release(&self, key: &str, holder: &str) -> Result<(), Error> {
+//!         let mut con = self.0.get_connection().map_err(|e| Error::Transport(e.to_string()))?;
+//!         redis::Script::new(
+//!             "if redis.call('GET', KEYS[1]) == ARGV[1] then redis.call('DEL', KEYS[1]) end",
+//!         )
+//!         .key(key).arg(holder).invoke::<()>(&mut con)
+//!         .map_err(|e| Error::Transport(e.to_string()))
+//!     }
+//! }
+//! ```
+
+use rand;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use clock::{Clock, SystemClock};
+use error::Error;
+
+/// A lock held by a single instance at a time, which expires unless it is renewed.
+pub trait LeaderLock: Send + Sync {
+    /// Takes the lock `key` for `holder` or renews it if `holder` already has it.
+    /// Returns `false` if another holder has an unexpired lock.
+    fn try_acquire(&self, key: &str, holder: &str, ttl: Duration) -> Result<bool, Error>;
+
+    /// Releases the lock `key` if it is held by `holder`.
+    fn release(&self, key: &str, holder: &str) -> Result<(), Error>;
+}
+
+/// Lock stored as files in a directory shared by the instances.
+///
+/// Each lock is a file named after the hex encoded key, with the holder and the expiry time.
+/// Lock files are written to a temporary file first and moved into place, so they are never seen
+/// partially written.
+/// Taking over an expired lock is not atomic, so the ttl should be well above the time between
+/// two attempts.
+#[derive(Clone)]
+pub struct FileLock {
+    dir: PathBuf,
+    clock: Arc<dyn Clock>,
+}
+impl FileLock {
+    /// Creates a lock keeping its files in `dir`, which is created if missing.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<FileLock, Error> {
+        FileLock::with_clock(dir, SystemClock)
+    }
+
+    /// Creates a lock which checks expiry times against `clock`.
+    pub fn with_clock<P: AsRef<Path>, C: Clock + 'static>(
+        dir: P,
+        clock: C,
+    ) -> Result<FileLock, Error> {
+        try!(fs::create_dir_all(dir.as_ref()));
+        Ok(FileLock {
+            dir: dir.as_ref().to_path_buf(),
+            clock: Arc::new(clock),
+        })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        let name: String = key.bytes().map(|b| format!("{:02x}", b)).collect();
+        self.dir.join(format!("{}.lock", name))
+    }
+
+    fn now_millis(&self) -> u64 {
+        let now = self.clock.now();
+        if now.timestamp() < 0 {
+            return 0;
+        }
+        now.timestamp() as u64 * 1000 + u64::from(now.timestamp_subsec_millis())
+    }
+
+    /// Writes `contents` to a new temporary file next to `path`.
+    fn write_temp(&self, path: &Path, contents: &str) -> Result<PathBuf, Error> {
+        let temp = path.with_extension(format!("{:016x}.tmp", rand::random::<u64>()));
+        let written = File::create(&temp).and_then(|mut file| {
+            try!(file.write_all(contents.as_bytes()));
+            file.sync_all()
+        });
+        match written {
+            Ok(()) => Ok(temp),
+            Err(e) => {
+                let _ = fs::remove_file(&temp);
+                Err(Error::Io(e))
+            }
+        }
+    }
+
+    fn read(&self, path: &Path) -> Result<Option<(String, u64)>, Error> {
+        let mut contents = String::new();
+        match File::open(path) {
+            Ok(mut file) => try!(file.read_to_string(&mut contents)),
+            Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(Error::Io(e)),
+        };
+        let mut lines = contents.lines();
+        let holder = lines.next().unwrap_or("").to_owned();
+        let expires = lines.next().and_then(|l| l.parse().ok()).unwrap_or(0);
+        Ok(Some((holder, expires)))
+    }
+}
+impl LeaderLock for FileLock {
+    fn try_acquire(&self, key: &str, holder: &str, ttl: Duration) -> Result<bool, Error> {
+        let path = self.path(key);
+        let now = self.now_millis();
+        let existing = match try!(self.read(&path)) {
+            Some((ref current, expires)) if current != holder && expires > now => return Ok(false),
+            Some(_) => true,
+            None => false,
+        };
+        let expires = now + ttl.as_secs() * 1000 + u64::from(ttl.subsec_nanos() / 1_000_000);
+        let temp = try!(self.write_temp(&path, &format!("{}\n{}\n", holder, expires)));
+        let placed = if existing {
+            fs::rename(&temp, &path)
+        } else {
+            // linking fails if another instance created the lock first
+            fs::hard_link(&temp, &path)
+        };
+        let _ = fs::remove_file(&temp);
+        match placed {
+            Ok(()) => Ok(true),
+            Err(ref e) if e.kind() == ErrorKind::AlreadyExists => Ok(false),
+            Err(e) => Err(Error::Io(e)),
+        }
+    }
+
+    fn release(&self, key: &str, holder: &str) -> Result<(), Error> {
+        let path = self.path(key);
+        match try!(self.read(&path)) {
+            Some((ref current, _)) if current == holder => Ok(try!(fs::remove_file(&path))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Debug for FileLock {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("FileLock")
+            .field("dir", &self.dir)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration as ChronoDuration, TimeZone, UTC};
+    use clock::ManualClock;
+    use leader::{FileLock, LeaderLock};
+    use std::env;
+    use std::fs;
+    use std::time::Duration;
+
+    #[test]
+    fn test_file_lock() {
+        let dir = env::temp_dir().join("challonge-rs-test-file-lock");
+        let _ = fs::remove_dir_all(&dir);
+        let lock = FileLock::new(&dir).unwrap();
+        let ttl = Duration::from_secs(60);
+
+        assert!(lock.try_acquire("watcher-1086875", "a", ttl).unwrap());
+        assert!(!lock.try_acquire("watcher-1086875", "b", ttl).unwrap());
+        assert!(lock.try_acquire("watcher-1086875", "a", ttl).unwrap());
+        assert!(lock.try_acquire("watcher-other", "b", ttl).unwrap());
+
+        lock.release("watcher-1086875", "b").unwrap();
+        assert!(!lock.try_acquire("watcher-1086875", "b", ttl).unwrap());
+        lock.release("watcher-1086875", "a").unwrap();
+        assert!(lock.try_acquire("watcher-1086875", "b", ttl).unwrap());
+
+        // an expired lock is taken over
+        assert!(lock
+            .try_acquire("watcher-other", "b", Duration::from_millis(0))
+            .unwrap());
+        assert!(lock.try_acquire("watcher-other", "a", ttl).unwrap());
+
+        // no temporary files are left behind
+        for entry in fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            assert_eq!(path.extension().unwrap(), "lock");
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_file_lock_clock_and_keys() {
+        let dir = env::temp_dir().join("challonge-rs-test-file-lock-clock");
+        let _ = fs::remove_dir_all(&dir);
+        let start = UTC.ymd(2016, 4, 1).and_hms(12, 0, 0);
+        let ttl = Duration::from_secs(60);
+        let first = FileLock::with_clock(&dir, ManualClock::new(start)).unwrap();
+        assert!(first.try_acquire("watcher/1", "a", ttl).unwrap());
+        // keys which would map to the same sanitized name are separate locks
+        assert!(first.try_acquire("watcher_1", "b", ttl).unwrap());
+        assert!(first.try_acquire("../watcher", "b", ttl).unwrap());
+        assert!(!first.try_acquire("watcher/1", "b", ttl).unwrap());
+
+        let later = ManualClock::new(start + ChronoDuration::minutes(2));
+        let second = FileLock::with_clock(&dir, later).unwrap();
+        assert!(second.try_acquire("watcher/1", "b", ttl).unwrap());
+        assert!(!first.try_acquire("watcher/1", "a", ttl).unwrap());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
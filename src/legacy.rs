@@ -0,0 +1,188 @@
+//! Decoding of tournaments created before the API returned its current fields.
+//!
+//! Tournaments from the first years of Challonge lack fields like `group_stages_enabled` or
+//! `review_before_finalizing`, return some counters and flags as strings and points as numbers.
+//! `Tournament::decode_legacy` upgrades such records to the current format before decoding them,
+//! so archival tooling reading historical data does not fail on them.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use error::Error;
+
+/// Flags added over time or returned as strings or numbers by old records.
+const FLAGS: &'static [&'static str] = &[
+    "accept_attachments",
+    "accepting_predictions",
+    "allow_participant_match_reporting",
+    "anonymous_voting",
+    "created_by_api",
+    "credit_capped",
+    "group_stages_enabled",
+    "group_stages_were_started",
+    "hide_forum",
+    "hide_seeds",
+    "hold_third_place_match",
+    "notify_users_when_matches_open",
+    "notify_users_when_the_tournament_ends",
+    "open_signup",
+    "participants_locked",
+    "participants_swappable",
+    "private",
+    "quick_advance",
+    "require_score_agreement",
+    "review_before_finalizing",
+    "sequential_pairings",
+    "show_rounds",
+    "team_convertable",
+    "teams",
+];
+
+/// Counters, `null` if missing or not a number.
+const COUNTERS: &'static [&'static str] = &[
+    "check_in_duration",
+    "game_id",
+    "id",
+    "max_predictions_per_user",
+    "participants_count",
+    "prediction_method",
+    "progress_meter",
+    "signup_cap",
+    "swiss_rounds",
+];
+
+/// Other fields the decoder expects to be present, `null` if missing.
+const FIELDS: &'static [&'static str] = &[
+    "category",
+    "full_challonge_url",
+    "game_name",
+    "live_image_url",
+    "name",
+    "predictions_opened_at",
+    "start_at",
+    "started_at",
+    "tournament_type",
+    "url",
+];
+
+/// Points of the swiss and round robin systems with the defaults of Challonge.
+const POINTS: &'static [(&'static str, &'static str)] = &[
+    ("pts_for_match_win", "1.0"),
+    ("pts_for_match_tie", "0.5"),
+    ("pts_for_game_win", "0.0"),
+    ("pts_for_game_tie", "0.0"),
+];
+
+/// Upgrades a legacy tournament response (`{"tournament": {...}}`) to the current format.
+pub fn upgrade_tournament(value: &Value) -> Result<Value, Error> {
+    let mut upgraded = value.clone();
+    match upgraded {
+        Value::Object(ref mut map) => match map.get_mut("tournament") {
+            Some(&mut Value::Object(ref mut t)) => upgrade(t),
+            _ => return Err(Error::Decode("Expected tournament object", value.clone())),
+        },
+        _ => return Err(Error::Decode("Expected object", value.clone())),
+    }
+    Ok(upgraded)
+}
+
+fn upgrade(t: &mut BTreeMap<String, Value>) {
+    for key in FLAGS {
+        let v = flag(t.get(*key));
+        t.insert((*key).to_owned(), v);
+    }
+    for key in COUNTERS {
+        let v = counter(t.get(*key));
+        t.insert((*key).to_owned(), v);
+    }
+    for key in FIELDS {
+        t.entry((*key).to_owned()).or_insert(Value::Null);
+    }
+    for prefix in &["", "rr_"] {
+        for &(key, default) in POINTS {
+            let key = format!("{}{}", prefix, key);
+            let v = match t.get(&key) {
+                Some(&Value::String(ref s)) => s.clone(),
+                Some(v) => number(v).unwrap_or_else(|| default.to_owned()),
+                None => default.to_owned(),
+            };
+            t.insert(key, Value::String(v));
+        }
+        let bye = format!("{}pts_for_bye", prefix);
+        if let Some(v) = t.get(&bye).and_then(number) {
+            t.insert(bye, Value::String(v));
+        }
+    }
+    let updated_at = match t.get("updated_at") {
+        Some(&Value::String(ref s)) if !s.trim().is_empty() => None,
+        _ => t.get("created_at").cloned(),
+    };
+    if let Some(created_at) = updated_at {
+        t.insert("updated_at".to_owned(), created_at);
+    }
+}
+
+fn flag(value: Option<&Value>) -> Value {
+    Value::Bool(match value {
+        Some(&Value::Bool(b)) => b,
+        Some(&Value::U64(n)) => n != 0,
+        Some(&Value::I64(n)) => n != 0,
+        Some(&Value::String(ref s)) => match s.trim() {
+            "true" | "t" | "1" => true,
+            _ => false,
+        },
+        _ => false,
+    })
+}
+
+fn counter(value: Option<&Value>) -> Value {
+    match value {
+        Some(&Value::U64(n)) => Value::U64(n),
+        Some(&Value::I64(n)) if n >= 0 => Value::U64(n as u64),
+        Some(&Value::F64(n)) if n >= 0.0 && n.fract() == 0.0 => Value::U64(n as u64),
+        Some(&Value::String(ref s)) => s.trim().parse().map(Value::U64).unwrap_or(Value::Null),
+        _ => Value::Null,
+    }
+}
+
+/// Formats points returned as a number the way the API returns them now, e.g. `"1.0"`.
+fn number(value: &Value) -> Option<String> {
+    match *value {
+        Value::U64(n) => Some(format!("{:.1}", n as f64)),
+        Value::I64(n) => Some(format!("{:.1}", n as f64)),
+        Value::F64(n) => Some(format!("{:.1}", n)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use tournament::{Tournament, TournamentId, TournamentStatus, TournamentType};
+
+    #[test]
+    fn test_decode_legacy() {
+        let legacy: serde_json::Value =
+            serde_json::from_str(include_str!("../benches/fixtures/legacy_tournament.json"))
+                .unwrap();
+        assert!(Tournament::decode(&legacy).is_err());
+
+        let t = Tournament::decode_legacy(&legacy).unwrap();
+        assert_eq!(t.id, TournamentId::Id(148263));
+        assert_eq!(t.participants_count, 8);
+        assert_eq!(t.state, TournamentStatus::Complete);
+        assert_eq!(t.tournament_type, TournamentType::DoubleElimination);
+        assert!(!t.group_stages_enabled && !t.hold_third_place_match);
+        assert_eq!(t.swiss_points.match_win, 1.0);
+        assert_eq!(t.round_robin_points.match_tie, 0.5);
+        assert_eq!(t.swiss_points.bye, Some(1.0));
+        assert!(t.started_at.is_some());
+
+        // current records decode the same way
+        let current: serde_json::Value =
+            serde_json::from_str(include_str!("../benches/fixtures/tournament.json")).unwrap();
+        let t = Tournament::decode_legacy(&current).unwrap();
+        assert_eq!(t.encode(), Tournament::decode(&current).unwrap().encode());
+    }
+}
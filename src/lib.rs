@@ -1,8 +1,10 @@
 //! Client library for the [Challonge](https://challonge.com) REST API.
 //!
-//! Log in to Challonge with `Challonge::new`.
+//! Log in to Challonge with `Challonge::builder()`.
 //! Call API methods to interact with the service.
 //!
+//! The API of challonge 0.4 stays available in the `v1` module while code migrates to the current one.
+//!
 //! For Challonge API documentation [look here](http://api.challonge.com/ru/v1/documents).
 //!
 //! For examples, see the `examples` directory in the source tree.
@@ -11,76 +13,168 @@
 #[macro_use]
 extern crate log;
 extern crate chrono;
+#[cfg(feature = "hyper")]
 extern crate hyper;
+#[cfg(feature = "wasm")]
+extern crate js_sys;
+#[cfg(feature = "keyring")]
+extern crate keyring;
+#[cfg(all(feature = "hyper", feature = "native-tls"))]
+extern crate native_tls;
+#[cfg(feature = "protobuf")]
+extern crate prost;
+extern crate pulldown_cmark;
+extern crate rand;
+#[cfg(feature = "reqwest")]
+extern crate reqwest;
+extern crate serde;
 extern crate serde_json;
+extern crate sha2;
+#[cfg(feature = "time")]
+extern crate time;
+#[cfg(feature = "async")]
+extern crate tokio;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+extern crate unicode_normalization;
+#[cfg(feature = "ureq")]
+extern crate ureq;
+extern crate url;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen_futures;
+#[cfg(feature = "wasm")]
+extern crate web_sys;
 
 use chrono::date::Date;
 use chrono::offset::local::Local;
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration as StdDuration, Instant};
 #[macro_use]
 mod macroses;
+pub mod anonymize;
+pub mod api_v2;
+pub mod archive;
+#[cfg(feature = "async")]
+pub mod async_client;
 pub mod attachments;
+pub mod backend;
+pub mod bracket;
+pub mod builder;
+pub mod circuit;
+pub mod cleanup;
+pub mod clock;
+pub mod consistency;
+pub mod datetime;
+pub mod description;
 pub mod error;
+pub mod etag;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod format;
+pub mod gallery;
+pub mod history;
+pub mod images;
+pub mod journal;
+pub mod leader;
+pub mod legacy;
+pub mod limits;
+pub mod match_sync;
 pub mod matches;
+pub mod metadata;
+pub mod names;
 pub mod participants;
+pub mod plan;
+pub mod presets;
+#[cfg(feature = "protobuf")]
+pub mod proto;
+pub mod ratelimit;
+pub mod redaction;
+pub mod replay;
+pub mod retry;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod season;
+pub mod seeding;
+pub mod settings;
+pub mod stats;
+#[cfg(feature = "svg")]
+pub mod svg;
 pub mod tournament;
+#[cfg(feature = "tracing")]
+mod trace;
+pub mod transaction;
+pub mod transport;
 mod util;
+pub mod v1;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod watcher;
+pub use archive::{archive_account, ArchiveReport};
+#[cfg(feature = "async")]
+pub use async_client::AsyncChallonge;
+use attachments::UploadCache;
 pub use attachments::{Attachment, AttachmentCreate, AttachmentId, Index as AttachmentIndex};
+pub use backend::Backend;
+pub use builder::ClientBuilder;
+pub use circuit::{CircuitBreaker, CircuitState};
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use consistency::ReadYourWrites;
+pub use datetime::ChallongeDateTime;
 use error::Error;
+use etag::EtagCache;
+pub use gallery::Gallery;
+pub use history::TournamentHistory;
+pub use images::{ImageFormat, ImageSize, LiveImage};
+pub use leader::{FileLock, LeaderLock};
+pub use limits::ResponseLimits;
+pub use match_sync::{MatchSync, SyncCursor};
+use matches::SubmissionCache;
 pub use matches::{
-    Index as MatchIndex, Match, MatchId, MatchScore, MatchScores, MatchState, MatchUpdate,
+    Index as MatchIndex, Match, MatchId, MatchIndexOptions, MatchScore, MatchScores, MatchState,
+    MatchUpdate,
+};
+pub use metadata::MatchMetadata;
+pub use participants::{
+    BatchReport, DuplicateField, DuplicateGroup, Index as ParticipantIndex, InvitationStatus,
+    MergeReport, Participant, ParticipantCreate, ParticipantId, ParticipantIndexOptions,
+    ParticipantUpdate, SignupCapReport, UsernameStatus,
 };
-pub use participants::{Index as ParticipantIndex, Participant, ParticipantCreate, ParticipantId};
+pub use ratelimit::{Priority, RateLimiter};
+pub use retry::RetryPolicy;
+use settings::SettingsCache;
+use stats::StatsRecorder;
+pub use stats::{EndpointStats, Metrics};
 pub use tournament::{
-    Index as TournamentIndex, Tournament, TournamentCreate, TournamentId, TournamentIncludes,
-    TournamentState, TournamentType,
+    Category, Game, Index as TournamentIndex, IndexSummary as TournamentIndexSummary,
+    PredictionMethod, Tournament, TournamentCreate, TournamentId, TournamentIncludes,
+    TournamentIndexOptions, TournamentState, TournamentStatus, TournamentType,
+};
+pub use transport::{
+    redact_url, BodyEncoding, Method, Normalization, Request, Response, Transport,
+};
+#[cfg(feature = "wasm")]
+pub use wasm::WasmChallonge;
+pub use watcher::{
+    AdaptiveIntervals, Event as WatcherEvent, IdleBackoff, WatchMode, Watcher, WatcherControl,
 };
 
-const API_BASE: &'static str = "https://api.challonge.com/v1";
-
-fn check_status(
-    response: hyper::Result<hyper::client::Response>,
-) -> Result<hyper::client::Response, Error> {
-    let response = try!(response);
-    if !response.status.is_success() {
-        return Err(Error::error_from_response(response));
-    }
-    Ok(response)
-}
-
-fn retry<'a, F: Fn() -> hyper::client::RequestBuilder<'a>>(
-    f: F,
-) -> Result<hyper::client::Response, Error> {
-    let f2 = || check_status(f().send());
-    // retry on a ConnectionAborted, which occurs if it's been a while since the last request
-    match f2() {
-        // Err(hyper::error::Error::Io(ref io))
-        //     if io.kind() == std::io::ErrorKind::ConnectionAborted => f2(),
-        other => other,
-    }
-}
+/// Hook confirming destructive calls, see `Challonge::with_confirmation`.
+type Confirmation = dyn Fn(&str) -> bool + Send + Sync;
 
-fn make_headers(user_name: String, api_key: String) -> hyper::header::Headers {
-    let mut headers = hyper::header::Headers::new();
-    headers.set(hyper::header::Authorization(hyper::header::Basic {
-        username: user_name,
-        password: Some(api_key),
-    }));
-    headers
-}
+const API_BASE: &'static str = "https://api.challonge.com/v1";
+const WEB_BASE: &'static str = "https://challonge.com";
+const BATCH_CONCURRENCY: usize = 4;
+/// Longest url which is sent as is. Longer urls of requests with a body have their query moved into the body.
+const MAX_URL_LENGTH: usize = 2000;
 
 type FieldPairs = Vec<(&'static str, String)>;
 
-fn pairs_to_string(params: FieldPairs) -> String {
-    let mut body = String::new();
-    let mut sep = "";
-    for p in params {
-        body.push_str(sep);
-        body.push_str(&format!("{}={}", p.0, p.1));
-        sep = "&";
-    }
-    body
-}
-
 fn pcs_to_pairs(participants: Vec<ParticipantCreate>) -> FieldPairs {
     let mut params = Vec::new();
     for p in participants {
@@ -114,6 +208,27 @@ fn pc_to_pairs(participant: &ParticipantCreate) -> FieldPairs {
     params
 }
 
+fn pu_to_pairs(update: &ParticipantUpdate) -> FieldPairs {
+    let mut params = FieldPairs::new();
+
+    if let Some(n) = update.name.as_ref() {
+        params.push((p!("name"), n.clone()));
+    }
+    if let Some(un) = update.challonge_username.as_ref() {
+        params.push((p!("challonge_username"), un.clone()));
+    }
+    if let Some(e) = update.email.as_ref() {
+        params.push((p!("email"), e.clone()));
+    }
+    if let Some(s) = update.seed {
+        params.push((p!("seed"), s.to_string()));
+    }
+    if let Some(m) = update.misc.as_ref() {
+        params.push((p!("misc"), m.clone()));
+    }
+    params
+}
+
 fn at_to_pairs(attachment: &AttachmentCreate) -> FieldPairs {
     let mut params = FieldPairs::new();
 
@@ -210,9 +325,85 @@ fn tc_to_pairs(tournament: &TournamentCreate) -> FieldPairs {
     if let Some(game) = tournament.game_name.as_ref() {
         params.push((t!("game_name"), game.clone()));
     }
+    if let Some(category) = tournament.category.as_ref() {
+        params.push((t!("category"), category.to_string()));
+    }
+    if let Some(method) = tournament.prediction_method.as_ref() {
+        params.push((t!("prediction_method"), method.code().to_string()));
+    }
     params
 }
 
+/// Query parameters of the options of an index call, so every call serializes its filters the same way.
+trait IndexQuery {
+    fn query(&self) -> FieldPairs;
+}
+impl IndexQuery for TournamentIndexOptions {
+    fn query(&self) -> FieldPairs {
+        let mut params = vec![("state", self.state.to_string())];
+        if let Some(t) = self.tournament_type.as_ref() {
+            params.push(("type", t.to_get_param().to_owned()));
+        }
+        if let Some(d) = self.created_after.as_ref() {
+            params.push(("created_after", format_date!(d)));
+        }
+        if let Some(d) = self.created_before.as_ref() {
+            params.push(("created_before", format_date!(d)));
+        }
+        if let Some(s) = self.subdomain.as_ref() {
+            params.push(("subdomain", s.clone()));
+        }
+        params
+    }
+}
+impl IndexQuery for MatchIndexOptions {
+    fn query(&self) -> FieldPairs {
+        let mut params = FieldPairs::new();
+        if let Some(s) = self.state.as_ref() {
+            params.push(("state", s.to_string()));
+        }
+        if let Some(pid) = self.participant_id.as_ref() {
+            params.push(("participant_id", pid.0.to_string()));
+        }
+        params
+    }
+}
+impl IndexQuery for ParticipantIndexOptions {
+    fn query(&self) -> FieldPairs {
+        FieldPairs::new()
+    }
+}
+
+/// Log target of the requests of the client and of decode failures.
+const LOG_TARGET: &'static str = "challonge";
+
+/// Decodes the body of a response, failures are logged before they are returned.
+fn decode_response<T, F>(response: &Response, decode: F) -> Result<T, Error>
+where
+    F: Fn(&serde_json::Value) -> Result<T, Error>,
+{
+    let result = response.json().and_then(|json| decode(&json));
+    if let Err(ref e) = result {
+        warn!(
+            target: LOG_TARGET,
+            "Failed to decode a response of {} bytes: {:?}",
+            response.body.len(),
+            e
+        );
+    }
+    result
+}
+
+/// Parses the url of an index call and appends the query of its options.
+fn index_url<O: IndexQuery>(url: &str, options: &O) -> url::Url {
+    let mut url = url::Url::parse(url).unwrap();
+    let query = options.query();
+    if !query.is_empty() {
+        url.query_pairs_mut().extend_pairs(query);
+    }
+    url
+}
+
 fn mu_to_pairs(mu: &MatchUpdate) -> FieldPairs {
     let mut params = Vec::new();
 
@@ -230,311 +421,1035 @@ fn mu_to_pairs(mu: &MatchUpdate) -> FieldPairs {
 }
 
 /// Client for the Challonge REST API.
+/// Its `Debug` and `Display` output never contains the API key.
+///
+/// The client is created with `Challonge::builder()` only, its fields are private:
+/// ```compile_fail
+/// extern crate challonge;
+///
+/// let c = challonge::Challonge {
+///     user_name: "myusername".to_owned(),
+/// };
+/// ```
+#[derive(Clone)]
 pub struct Challonge {
-    headers: hyper::header::Headers,
-    client: hyper::client::Client,
+    user_name: String,
+    authorization: String,
+    base_url: String,
+    user_agent: Option<String>,
+    transport: Arc<dyn Transport>,
+    clock: Arc<dyn Clock>,
+    encoding: BodyEncoding,
+    normalization: Normalization,
+    subdomain: Option<String>,
+    sandbox_prefix: Option<String>,
+    read_your_writes: Option<ReadYourWrites>,
+    stats: Arc<StatsRecorder>,
+    slow_call_threshold: Option<StdDuration>,
+    limits: ResponseLimits,
+    uploads: Arc<UploadCache>,
+    submissions: Arc<SubmissionCache>,
+    idempotency_window: StdDuration,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    priority: Priority,
+    confirmation: Option<Arc<Confirmation>>,
+    deadline: Option<chrono::DateTime<chrono::UTC>>,
+    retry: Option<RetryPolicy>,
+    circuit: Option<Arc<CircuitBreaker>>,
+    settings: Option<Arc<SettingsCache>>,
+    etags: Option<Arc<EtagCache>>,
 }
 impl Challonge {
-    /// Create new connection to Challonge.
+    /// Returns a builder of a client.
     /// # Example
     /// ```ignore
     /// extern crate challonge;
     ///
     /// use self::challonge::Challonge;
     ///
-    /// let c = Challonge::new("myusername", "myapikey");
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap();
     /// ```
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// Create new connection to Challonge.
+    #[deprecated(since = "0.5.0", note = "use `Challonge::builder()` instead")]
+    #[allow(deprecated)]
     pub fn new<S: Into<String>>(user_name: S, api_key: S) -> Challonge {
-        Challonge {
-            client: hyper::Client::new(),
-            headers: make_headers(user_name.into(), api_key.into()),
-        }
+        v1::new(user_name, api_key)
     }
 
-    /// Retrieve a set of tournaments created with your account.
+    /// Create new connection to Challonge with the API key loaded from the OS credential store.
     /// # Example
     /// ```ignore
     /// extern crate challonge;
-    /// extern crate chrono;
     ///
     /// use self::challonge::Challonge;
-    /// use self::challonge::tournament::{ TournamentState, TournamentType };
-    /// use self::chrono::*;
     ///
-    /// let c = Challonge::new("myusername", "myapikey");
-    /// let index = c.tournament_index (
-    ///        &TournamentState::All,
-    ///        &TournamentType::DoubleElimination,
-    ///        &Local::today(),
-    ///        &Local::today(),
-    ///        "subdomain"
-    /// );
+    /// Challonge::store_in_keyring("challonge", "myusername", "myapikey").unwrap();
+    /// let c = Challonge::from_keyring("challonge", "myusername").unwrap();
     /// ```
-    pub fn tournament_index(
-        &self,
-        state: &TournamentState,
-        tournament_type: &TournamentType,
-        created_after: &Date<Local>,
-        created_before: &Date<Local>,
-        subdomain: &str,
-    ) -> Result<TournamentIndex, Error> {
-        let mut url = hyper::Url::parse(&format!("{}/tournaments.json", API_BASE)).unwrap();
-        url.query_pairs_mut()
-            .append_pair("state", &state.to_string())
-            .append_pair("type", &tournament_type.to_get_param())
-            .append_pair("created_after", &format_date!(created_after))
-            .append_pair("created_before", &format_date!(created_before))
-            .append_pair("subdomain", subdomain);
+    #[cfg(feature = "keyring")]
+    pub fn from_keyring(service: &str, user_name: &str) -> Result<Challonge, Error> {
+        let api_key = try!(keyring::Entry::new(service, user_name).get_password());
+        Challonge::builder()
+            .credentials(user_name.to_owned(), api_key)
+            .build()
+    }
 
-        let response = try!(retry(|| self
-            .client
-            .get(url.as_str())
-            .headers(self.headers.clone())));
-        TournamentIndex::decode(try!(serde_json::from_reader(response)))
+    /// Stores the API key in the OS credential store to be loaded with `from_keyring`.
+    #[cfg(feature = "keyring")]
+    pub fn store_in_keyring(service: &str, user_name: &str, api_key: &str) -> Result<(), Error> {
+        try!(keyring::Entry::new(service, user_name).set_password(api_key));
+        Ok(())
     }
 
-    /// Retrieve a single tournament record created with your account.
+    /// Replaces the HTTP transport of the client.
+    /// The transport is shared between clones of the client.
     /// # Example
     /// ```ignore
     /// extern crate challonge;
     ///
-    /// use challonge::Challonge;
+    /// use self::challonge::Challonge;
+    /// use self::challonge::transport::UreqTransport;
     ///
-    /// let c = Challonge::new("myusername", "myapikey");
-    /// let i = TournamentIncludes::Matches;
-    /// let t = c.get_tournament(&TournamentId::Id(2669881), &i);
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap()
+    ///     .with_transport(UreqTransport::new());
     /// ```
-    pub fn get_tournament(
-        &self,
-        id: &TournamentId,
-        includes: &TournamentIncludes,
-    ) -> Result<Tournament, Error> {
-        let mut url =
-            hyper::Url::parse(&format!("{}/tournaments/{}.json", API_BASE, id.to_string()))
-                .unwrap();
-
-        Challonge::add_tournament_includes(&mut url, includes);
-        let response = try!(retry(|| self
-            .client
-            .get(url.as_str())
-            .headers(self.headers.clone())));
-        Tournament::decode(try!(serde_json::from_reader(response)))
+    pub fn with_transport<T: Transport + 'static>(mut self, transport: T) -> Challonge {
+        self.transport = Arc::new(transport);
+        self
     }
 
-    /// Create a new tournament.
+    /// Sets how create/update payloads are encoded into request bodies.
+    /// Form encoding is used by default.
     /// # Example
     /// ```ignore
     /// extern crate challonge;
     ///
-    /// use challonge::Challonge;
-    /// use challonge::tournament::TournamentCreate;
+    /// use self::challonge::{BodyEncoding, Challonge};
     ///
-    /// let c = Challonge::new("myusername", "myapikey");
-    /// let tc = TournamentCreate { // explicitly define the whole structure
-    ///            name: "Tester".to_owned(),
-    ///            tournament_type: TournamentType::SingleElimination,
-    ///            url: "testerurl".to_owned(),
-    ///            subdomain: "subdomain".to_owned(),
-    ///            description: "Test tournament created from challonge-rs".to_owned(),
-    ///            open_signup: false,
-    ///            hold_third_place_match: false,
-    ///            pts_for_match_win: 0.0f64,
-    ///            pts_for_match_tie: 0.0f64,
-    ///            pts_for_game_win: 0.0f64,
-    ///            pts_for_game_tie: 0.0f64,
-    ///            pts_for_bye: 0.0f64,
-    ///            swiss_rounds: 0,
-    ///            ranked_by: RankedBy::PointsScored,
-    ///            rr_pts_for_match_win: 0.0f64,
-    ///            rr_pts_for_match_tie: 0.0f64,
-    ///            rr_pts_for_game_win: 0.0f64,
-    ///            rr_pts_for_game_tie: 0.0f64,
-    ///            show_rounds: false,
-    ///            private: false,
-    ///            notify_users_when_matches_open: true,
-    ///            notify_users_when_the_tournament_ends: true,
-    ///            sequential_pairings: false,
-    ///            signup_cap: 4,
-    ///            start_at: UTC::now().add(Duration::weeks(2)),
-    ///            check_in_duration: 60,
-    ///            grand_finals_modifier: None,
-    /// };
-    /// let t = c.create_tournament(&tc);
-    /// // or you may create `TournamentCreate` by using a builder:
-    /// let mut tcb = TournamentCreate::new();
-    /// tcb.name("Test tournament")
-    ///   .tournament_type(TournamentType::SingleElimination)
-    ///   .url("TestUrl")
-    ///   .subdomain("subdomain")
-    ///   .description("TEST TOURNAMENT created by challonge-rs");
-    /// let tb = c.create_tournament(&tcb);
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap()
+    ///     .with_body_encoding(BodyEncoding::Json);
     /// ```
-    pub fn create_tournament(&self, tournament: &TournamentCreate) -> Result<Tournament, Error> {
-        let url = &format!("{}/tournaments.json", API_BASE);
-        let body = pairs_to_string(tc_to_pairs(tournament));
-        let response = try!(retry(|| self
-            .client
-            .post(url)
-            .headers(self.headers.clone())
-            .body(&body)));
-        Tournament::decode(try!(serde_json::from_reader(response)))
+    pub fn with_body_encoding(mut self, encoding: BodyEncoding) -> Challonge {
+        self.encoding = encoding;
+        self
     }
 
-    /// Update a tournament's attributes.
-    pub fn update_tournament(
-        &self,
-        id: &TournamentId,
-        tournament: &TournamentCreate,
-    ) -> Result<Tournament, Error> {
-        let url = &format!("{}/tournaments/{}.json", API_BASE, id.to_string());
-        let body = pairs_to_string(tc_to_pairs(tournament));
-        let response = try!(retry(|| self
-            .client
-            .put(url)
-            .headers(self.headers.clone())
-            .body(&body)));
-        Tournament::decode(try!(serde_json::from_reader(response)))
+    /// Sets the unicode normalization applied to create/update parameter values.
+    /// Values are normalized to NFC by default, so names typed on different systems compare equal.
+    pub fn with_normalization(mut self, normalization: Normalization) -> Challonge {
+        self.normalization = normalization;
+        self
     }
 
-    /// Deletes a tournament along with all its associated records. There is no undo, so use with care!
-    pub fn delete_tournament(&self, id: &TournamentId) -> Result<(), Error> {
-        let url = &format!("{}/tournaments/{}.json", API_BASE, id.to_string());
-        let _ = try!(retry(|| self
-            .client
-            .delete(url)
-            .headers(self.headers.clone())));
-        Ok(())
+    /// Replaces the clock used by time-dependent logic of the client and its watchers.
+    /// The clock is shared between clones of the client.
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Challonge {
+        self.clock = Arc::new(clock);
+        self
     }
 
-    /// This should be invoked after a tournament's check-in window closes before the tournament is started.
+    /// Returns the clock of the client.
+    pub fn clock(&self) -> &dyn Clock {
+        &*self.clock
+    }
+
+    /// Sets a default subdomain for the client.
+    /// Tournament ids given as `TournamentId::Url` with an empty subdomain, index calls without a subdomain
+    /// and created tournaments without a subdomain are scoped to it.
+    /// A subdomain passed explicitly to a call still takes precedence.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
     ///
-    /// 1. Marks participants who have not checked in as inactive.
-    /// 2. Moves inactive participants to bottom seeds (ordered by original seed).
-    /// 3. Transitions the tournament state from 'checking_in' to 'checked_in'
+    /// use self::challonge::Challonge;
     ///
-    /// NOTE: Checked in participants on the waiting list will be promoted if slots become available.
-    pub fn tournament_process_checkins(
-        &self,
-        id: &TournamentId,
-        includes: &TournamentIncludes,
-    ) -> Result<(), Error> {
-        self.tournament_action("process_check_ins", id, includes)
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap()
+    ///     .with_subdomain("subdomain");
+    /// let t = c.get_tournament(&TournamentId::Url(String::new(), "weekly1".to_owned()),
+    ///                          &TournamentIncludes::All);
+    /// ```
+    pub fn with_subdomain<S: Into<String>>(mut self, subdomain: S) -> Challonge {
+        self.subdomain = Some(subdomain.into());
+        self
     }
 
-    /// When your tournament is in a 'checking_in' or 'checked_in' state, there's no way to edit the tournament's start time (start_at) or check-in duration (check_in_duration). You must first abort check-in, then you may edit those attributes.
+    /// Enables the sandbox guard: the client refuses to create or modify any tournament
+    /// whose url does not start with `prefix`, failing with `Error::SandboxViolation` before a request is sent.
+    /// Use it to run integration tests against a real account safely.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
     ///
-    /// 1. Makes all participants active and clears their checked_in_at times.
-    /// 2. Transitions the tournament state from 'checking_in' or 'checked_in' to 'pending'
-    pub fn tournament_abort_checkins(
-        &self,
-        id: &TournamentId,
-        includes: &TournamentIncludes,
-    ) -> Result<(), Error> {
-        self.tournament_action("abort_check_in", id, includes)
+    /// use self::challonge::Challonge;
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap()
+    ///     .with_sandbox_prefix("test_");
+    /// // fails without touching the tournament
+    /// c.delete_tournament(&TournamentId::Url(String::new(), "weekly1".to_owned()));
+    /// ```
+    pub fn with_sandbox_prefix<S: Into<String>>(mut self, prefix: S) -> Challonge {
+        self.sandbox_prefix = Some(prefix.into());
+        self
     }
 
-    /// Start a tournament, opening up first round matches for score reporting. The tournament must have at least 2 participants.
-    pub fn tournament_start(
-        &self,
-        id: &TournamentId,
-        includes: &TournamentIncludes,
-    ) -> Result<(), Error> {
-        self.tournament_action("start", id, includes)
+    /// Makes mutation methods which return the changed resource (`update_tournament`, `create_participant`,
+    /// `update_match`) re-fetch it until the change is visible and return the confirmed state.
+    /// If the change does not become visible within the policy, the mutation response is returned.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    ///
+    /// use self::challonge::{Challonge, ReadYourWrites};
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap()
+    ///     .with_read_your_writes(ReadYourWrites::new());
+    /// ```
+    pub fn with_read_your_writes(mut self, policy: ReadYourWrites) -> Challonge {
+        self.read_your_writes = Some(policy);
+        self
     }
 
-    /// Finalize a tournament that has had all match scores submitted, rendering its results permanent.
-    pub fn tournament_finalize(
-        &self,
-        id: &TournamentId,
-        includes: &TournamentIncludes,
-    ) -> Result<(), Error> {
-        self.tournament_action("finalize", id, includes)
+    /// Sets the limits on response size and json depth checked before decoding.
+    /// Responses over a limit fail with `Error::ResponseTooLarge` or `Error::JsonTooDeep`.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    ///
+    /// use self::challonge::{Challonge, ResponseLimits};
+    ///
+    /// let mut limits = ResponseLimits::new();
+    /// limits.max_body_bytes(4 * 1024 * 1024).max_depth(16);
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap()
+    ///     .with_response_limits(limits);
+    /// ```
+    pub fn with_response_limits(mut self, limits: ResponseLimits) -> Challonge {
+        self.limits = limits;
+        self
     }
 
-    /// Reset a tournament, clearing all of its scores and attachments. You can then add/remove/edit participants before starting the tournament again.
-    pub fn tournament_reset(
-        &self,
-        id: &TournamentId,
-        includes: &TournamentIncludes,
-    ) -> Result<(), Error> {
-        self.tournament_action("reset", id, includes)
+    /// Limits the requests of the client and its clones with a rate limiter.
+    /// The same limiter can be attached to several clients to share the limit between them.
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Challonge {
+        self.rate_limiter = Some(limiter);
+        self
     }
 
-    /// Retrieve a tournament's participant list.
-    pub fn participant_index(&self, id: &TournamentId) -> Result<ParticipantIndex, Error> {
-        let url = &format!(
-            "{}/tournaments/{}/participants.json",
-            API_BASE,
-            id.to_string()
-        );
-        let response = try!(retry(|| self.client.get(url).headers(self.headers.clone())));
-        ParticipantIndex::decode(try!(serde_json::from_reader(response)))
+    /// Limits the requests of the client and its clones to `per_minute` requests per minute,
+    /// allowing bursts of `ratelimit::BURST_SECS` seconds worth of requests.
+    /// Clones made afterwards share the limit, so a tool polling many tournaments stays under it.
+    pub fn with_rate_limit(self, per_minute: u32) -> Challonge {
+        self.with_rate_limiter(Arc::new(RateLimiter::from_rate(per_minute)))
     }
 
-    /// Add a participant to a tournament (up until it is started).
-    pub fn create_participant(
-        &self,
-        id: &TournamentId,
-        participant: &ParticipantCreate,
-    ) -> Result<Participant, Error> {
-        let url = &format!(
-            "{}/tournaments/{}/participants.json",
-            API_BASE,
-            id.to_string()
-        );
-        let body = pairs_to_string(pc_to_pairs(participant));
-        let response = try!(retry(|| self
-            .client
-            .post(url)
-            .headers(self.headers.clone())
-            .body(&body)));
-        Participant::decode(try!(serde_json::from_reader(response)))
+    /// Sets the priority of the requests of the client when its rate limiter is saturated.
+    /// Give background work a low-priority clone so interactive calls are not starved behind it.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    ///
+    /// use std::sync::Arc;
+    /// use self::challonge::{Challonge, Priority, RateLimiter, Watcher};
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap()
+    ///     .with_rate_limiter(Arc::new(RateLimiter::new(60, 10)))
+    ///     .with_priority(Priority::Interactive);
+    /// let polling = c.clone().with_priority(Priority::Background);
+    /// let watcher = Watcher::new(&polling, TournamentId::Id(2669881));
+    /// // score reports made with `c` go before the polls of the watcher
+    /// ```
+    pub fn with_priority(mut self, priority: Priority) -> Challonge {
+        self.priority = priority;
+        self
     }
 
-    /// Bulk add participants to a tournament (up until it is started).
-    /// If an invalid participant is detected, bulk participant creation will halt and any previously added participants (from this API request) will be rolled back.
-    pub fn create_participant_bulk(
-        &self,
-        id: &TournamentId,
-        participants: Vec<ParticipantCreate>,
-    ) -> Result<(), Error> {
-        let url = &format!(
-            "{}/tournaments/{}/participants/bulk_add.json",
-            API_BASE,
-            id.to_string()
-        );
-        let body = pairs_to_string(pcs_to_pairs(participants));
-        let response = try!(retry(|| self
-            .client
-            .post(url)
-            .headers(self.headers.clone())
-            .body(&body)));
-        let _: () = try!(serde_json::from_reader(response));
-        Ok(())
+    /// Retries requests which failed with a transient error (e.g. 502 or 503 responses)
+    /// with an exponential backoff. Mutations are retried only if the policy allows it.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    ///
+    /// use self::challonge::{Challonge, RetryPolicy};
+    ///
+    /// let mut policy = RetryPolicy::new();
+    /// policy.max_attempts(5).retry_mutations(true);
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap()
+    ///     .with_retry_policy(policy);
+    /// ```
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Challonge {
+        self.retry = Some(policy);
+        self
     }
 
-    /// Retrieve a single participant record for a tournament.
-    pub fn get_participant(
-        &self,
-        id: &TournamentId,
-        participant_id: &ParticipantId,
-        include_matches: bool,
-    ) -> Result<Participant, Error> {
-        let mut url = hyper::Url::parse(&format!(
-            "{}/tournaments/{}/participants/{}.json",
-            API_BASE,
-            id.to_string(),
+    /// Stops sending requests for `cooldown` after `threshold` consecutive server failures,
+    /// failing fast with `Error::CircuitOpen` instead, see the `circuit` module.
+    /// Clones made afterwards share the breaker.
+    pub fn with_circuit_breaker(mut self, threshold: u32, cooldown: StdDuration) -> Challonge {
+        self.circuit = Some(Arc::new(CircuitBreaker::new(threshold, cooldown)));
+        self
+    }
+
+    /// Keeps fetched tournament settings for `ttl`, so helpers needing them do not fetch
+    /// the tournament on every call, see the `settings` module. Clones made afterwards share the cache.
+    pub fn with_settings_cache(mut self, ttl: StdDuration) -> Challonge {
+        self.settings = Some(Arc::new(SettingsCache::new(ttl)));
+        self
+    }
+
+    /// Sends GET requests with the ETag of the previous response to the same url and reuses that
+    /// response when nothing changed, keeping up to `capacity` responses, see the `etag` module.
+    /// Clones made afterwards share the cache.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    ///
+    /// use self::challonge::Challonge;
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap()
+    ///     .with_etag_cache(256);
+    /// ```
+    pub fn with_etag_cache(mut self, capacity: usize) -> Challonge {
+        self.etags = Some(Arc::new(EtagCache::new(capacity)));
+        self
+    }
+
+    /// Sets how long `update_match_once` suppresses a resubmission of an acknowledged update, 10 minutes by default.
+    pub fn with_idempotency_window(mut self, window: StdDuration) -> Challonge {
+        self.idempotency_window = window;
+        self
+    }
+
+    /// Sets a hook asking an operator to confirm destructive calls (`tournament_reset`,
+    /// `delete_tournament`, `clear_participants`) before they are sent.
+    /// The hook receives a description of the action; if it returns `false`,
+    /// the call fails with `Error::Cancelled` without touching the tournament.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    ///
+    /// use std::io::{self, BufRead, Write};
+    /// use self::challonge::Challonge;
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap()
+    ///     .with_confirmation(|action| {
+    ///         print!("{}? [y/N] ", action);
+    ///         io::stdout().flush().unwrap();
+    ///         let mut answer = String::new();
+    ///         io::stdin().lock().read_line(&mut answer).unwrap();
+    ///         answer.trim() == "y"
+    ///     });
+    /// ```
+    pub fn with_confirmation<F>(mut self, confirmation: F) -> Challonge
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.confirmation = Some(Arc::new(confirmation));
+        self
+    }
+
+    /// Returns a clone of the client whose calls fail with `Error::Timeout` once `within` has elapsed.
+    /// Use it to bound a single call, including the requests of multi-step operations.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    ///
+    /// use std::time::Duration;
+    /// use self::challonge::Challonge;
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .read_timeout(Duration::from_secs(30))
+    ///     .build()
+    ///     .unwrap();
+    /// let t = c
+    ///     .deadline(Duration::from_secs(5))
+    ///     .get_tournament(&TournamentId::Id(2669881), &TournamentIncludes::All);
+    /// ```
+    pub fn deadline(&self, within: StdDuration) -> Challonge {
+        let mut c = self.clone();
+        let within = chrono::Duration::from_std(within).unwrap_or(chrono::Duration::max_value());
+        c.deadline = Some(self.clock.now() + within);
+        c
+    }
+
+    /// Logs a warning for every request which takes longer than `threshold`.
+    pub fn with_slow_call_threshold(mut self, threshold: StdDuration) -> Challonge {
+        self.slow_call_threshold = Some(threshold);
+        self
+    }
+
+    /// Returns per-endpoint request statistics (calls, errors, p50/p95 latency) keyed by endpoint,
+    /// e.g. `GET /tournaments/:id.json`. Statistics are shared between clones of the client.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    ///
+    /// use self::challonge::Challonge;
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap();
+    /// for (endpoint, s) in c.stats() {
+    ///     println!("{}: {} calls, {} errors, p95 {:?}", endpoint, s.calls, s.errors, s.p95);
+    /// }
+    /// ```
+    pub fn stats(&self) -> BTreeMap<String, EndpointStats> {
+        self.stats.snapshot()
+    }
+
+    /// Returns the counters of the client and its clones since it was built: requests, errors and
+    /// cumulative latency by endpoint. Unlike `stats` they are not reset by `reset_stats`.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    ///
+    /// use self::challonge::Challonge;
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap();
+    /// // e.g. the body of a `/metrics` endpoint
+    /// let body = c.metrics().to_prometheus();
+    /// ```
+    pub fn metrics(&self) -> Metrics {
+        self.stats.metrics()
+    }
+
+    /// Forgets the request statistics collected so far.
+    pub fn reset_stats(&self) {
+        self.stats.reset()
+    }
+
+    /// Retrieve a set of tournaments created with your account.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    /// extern crate chrono;
+    ///
+    /// use self::challonge::Challonge;
+    /// use self::challonge::tournament::{ TournamentState, TournamentType };
+    /// use self::chrono::*;
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap();
+    /// let index = c.tournament_index (
+    ///        &TournamentState::All,
+    ///        &TournamentType::DoubleElimination,
+    ///        &Local::today(),
+    ///        &Local::today(),
+    ///        "subdomain"
+    /// );
+    /// ```
+    pub fn tournament_index(
+        &self,
+        state: &TournamentState,
+        tournament_type: &TournamentType,
+        created_after: &Date<Local>,
+        created_before: &Date<Local>,
+        subdomain: &str,
+    ) -> Result<TournamentIndex, Error> {
+        let mut options = TournamentIndexOptions::new();
+        options
+            .state(state.clone())
+            .tournament_type(tournament_type.clone())
+            .created_after(created_after.clone())
+            .created_before(created_before.clone())
+            .subdomain(subdomain);
+        self.tournament_index_with(&options)
+    }
+
+    /// Retrieve tournaments created with your account matching the options.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    ///
+    /// use self::challonge::{Challonge, TournamentIndexOptions, TournamentState};
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap();
+    /// let index = c.tournament_index_with(TournamentIndexOptions::new().state(TournamentState::InProgress));
+    /// ```
+    pub fn tournament_index_with(
+        &self,
+        options: &TournamentIndexOptions,
+    ) -> Result<TournamentIndex, Error> {
+        self.fetch_tournament_index(&options.query())
+    }
+
+    /// Retrieve tournaments of your account whose name or url contains `query` (case-insensitive).
+    /// The API has no server-side search, so the whole index is fetched and filtered locally.
+    pub fn find_tournaments(&self, query: &str) -> Result<Vec<Tournament>, Error> {
+        let index =
+            try!(self.fetch_tournament_index(&[("state", TournamentState::All.to_string())]));
+        Ok(index.search(query).into_iter().cloned().collect())
+    }
+
+    /// Retrieve tournaments which were updated within the given `window` (e.g. the last 24 hours),
+    /// most recently updated first.
+    /// If `lookback` is given, only tournaments created within it are requested from the server,
+    /// which keeps the index small for accounts with a long history.
+    pub fn recently_active_tournaments(
+        &self,
+        window: chrono::Duration,
+        lookback: Option<chrono::Duration>,
+    ) -> Result<Vec<Tournament>, Error> {
+        let mut params = vec![("state", TournamentState::All.to_string())];
+        let now = self.clock.now();
+        if let Some(l) = lookback {
+            let today = now.with_timezone(&Local).date();
+            params.push(("created_after", format_date!(today - l)));
+        }
+        let index = try!(self.fetch_tournament_index(&params));
+        let since = now - window;
+        Ok(index.updated_since(&since).into_iter().cloned().collect())
+    }
+
+    /// Iterate over tournaments created between `from` and `to`, requesting the index `step` at a time
+    /// (e.g. month by month). Use it to enumerate very old tournaments, deep pages of the index are unreliable.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    /// extern crate chrono;
+    ///
+    /// use self::challonge::Challonge;
+    /// use self::chrono::*;
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap();
+    /// for t in c.tournament_history(Local.ymd(2012, 1, 1), Local::today(), Duration::weeks(4)) {
+    ///     println!("{}", t.unwrap().name);
+    /// }
+    /// ```
+    pub fn tournament_history(
+        &self,
+        from: Date<Local>,
+        to: Date<Local>,
+        step: chrono::Duration,
+    ) -> TournamentHistory<'_> {
+        TournamentHistory::new(self, from, to, step)
+    }
+
+    fn fetch_tournament_index(&self, params: &[(&str, String)]) -> Result<TournamentIndex, Error> {
+        decode_response(
+            &try!(self.fetch_tournament_index_response(params)),
+            TournamentIndex::decode,
+        )
+    }
+
+    /// Retrieves the tournament index like `fetch_tournament_index`, tolerating legacy tournaments.
+    /// Used by the archival tooling which reads tournaments of all ages.
+    fn fetch_legacy_tournament_index(
+        &self,
+        params: &[(&str, String)],
+    ) -> Result<TournamentIndex, Error> {
+        decode_response(
+            &try!(self.fetch_tournament_index_response(params)),
+            TournamentIndex::decode_legacy,
+        )
+    }
+
+    fn fetch_tournament_index_response(
+        &self,
+        params: &[(&str, String)],
+    ) -> Result<Response, Error> {
+        let mut url = url::Url::parse(&format!("{}/tournaments.json", self.base_url)).unwrap();
+        {
+            let mut pairs = url.query_pairs_mut();
+            let mut scoped = false;
+            for &(key, ref value) in params {
+                if key == "subdomain" {
+                    if value.is_empty() {
+                        continue;
+                    }
+                    scoped = true;
+                }
+                pairs.append_pair(key, value);
+            }
+            if let (false, Some(subdomain)) = (scoped, self.subdomain.as_ref()) {
+                pairs.append_pair("subdomain", subdomain);
+            }
+        }
+
+        self.call(Method::Get, url.as_str(), None)
+    }
+
+    /// Retrieve a single tournament record created with your account.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    ///
+    /// use challonge::Challonge;
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap();
+    /// let i = TournamentIncludes::Matches;
+    /// let t = c.get_tournament(&TournamentId::Id(2669881), &i);
+    /// ```
+    pub fn get_tournament(
+        &self,
+        id: &TournamentId,
+        includes: &TournamentIncludes,
+    ) -> Result<Tournament, Error> {
+        decode_response(
+            &try!(self.get_tournament_response(id, includes)),
+            Tournament::decode,
+        )
+    }
+
+    /// Retrieves a tournament along with its participants in a single call.
+    pub fn get_tournament_with_participants(
+        &self,
+        id: &TournamentId,
+    ) -> Result<(Tournament, ParticipantIndex), Error> {
+        let response = try!(self.get_tournament_response(id, &TournamentIncludes::Participants));
+        decode_response(&response, |json| {
+            let participants = try!(json
+                .find_path(&["tournament", "participants"])
+                .ok_or(Error::Decode("Missing included participants", json.clone())));
+            Ok((
+                try!(Tournament::decode(json)),
+                try!(ParticipantIndex::decode(participants)),
+            ))
+        })
+    }
+
+    /// Retrieves the settings of a tournament, from the settings cache of the client if it has one.
+    /// The participants and matches of the returned tournament are not included.
+    pub fn tournament_settings(&self, id: &TournamentId) -> Result<Tournament, Error> {
+        let cache = match self.settings {
+            Some(ref cache) => cache,
+            None => return self.get_tournament(id, &TournamentIncludes::Nothing),
+        };
+        let id = self.resolve_id(id);
+        if let Some(t) = cache.get(&id, self.clock.now()) {
+            return Ok(t);
+        }
+        let t = try!(self.get_tournament(&id, &TournamentIncludes::Nothing));
+        cache.insert(&id, &t, self.clock.now());
+        Ok(t)
+    }
+
+    /// Drops the cached settings of a tournament, e.g. after it was changed on the website.
+    pub fn invalidate_tournament_settings(&self, id: &TournamentId) {
+        if let Some(ref cache) = self.settings {
+            cache.invalidate(&self.resolve_id(id));
+        }
+    }
+
+    /// Retrieves a tournament as it was returned by the API, without decoding it.
+    fn get_tournament_json(
+        &self,
+        id: &TournamentId,
+        includes: &TournamentIncludes,
+    ) -> Result<serde_json::Value, Error> {
+        try!(self.get_tournament_response(id, includes)).json()
+    }
+
+    fn get_tournament_response(
+        &self,
+        id: &TournamentId,
+        includes: &TournamentIncludes,
+    ) -> Result<Response, Error> {
+        let mut url = url::Url::parse(&format!(
+            "{}/tournaments/{}.json",
+            self.base_url,
+            self.tournament_path(id)
+        ))
+        .unwrap();
+
+        Challonge::add_tournament_includes(&mut url, includes);
+        self.call(Method::Get, url.as_str(), None)
+    }
+
+    /// Create a new tournament.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    ///
+    /// use challonge::Challonge;
+    /// use challonge::tournament::TournamentCreate;
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap();
+    /// let tc = TournamentCreate { // explicitly define the whole structure
+    ///            name: "Tester".to_owned(),
+    ///            tournament_type: TournamentType::SingleElimination,
+    ///            url: "testerurl".to_owned(),
+    ///            subdomain: "subdomain".to_owned(),
+    ///            description: "Test tournament created from challonge-rs".to_owned(),
+    ///            open_signup: false,
+    ///            hold_third_place_match: false,
+    ///            pts_for_match_win: 0.0f64,
+    ///            pts_for_match_tie: 0.0f64,
+    ///            pts_for_game_win: 0.0f64,
+    ///            pts_for_game_tie: 0.0f64,
+    ///            pts_for_bye: 0.0f64,
+    ///            swiss_rounds: 0,
+    ///            ranked_by: RankedBy::PointsScored,
+    ///            rr_pts_for_match_win: 0.0f64,
+    ///            rr_pts_for_match_tie: 0.0f64,
+    ///            rr_pts_for_game_win: 0.0f64,
+    ///            rr_pts_for_game_tie: 0.0f64,
+    ///            show_rounds: false,
+    ///            private: false,
+    ///            notify_users_when_matches_open: true,
+    ///            notify_users_when_the_tournament_ends: true,
+    ///            sequential_pairings: false,
+    ///            signup_cap: 4,
+    ///            start_at: UTC::now().add(Duration::weeks(2)),
+    ///            check_in_duration: 60,
+    ///            grand_finals_modifier: None,
+    ///            prediction_method: None,
+    /// };
+    /// let t = c.create_tournament(&tc);
+    /// // or you may create `TournamentCreate` by using a builder:
+    /// let mut tcb = TournamentCreate::new();
+    /// tcb.name("Test tournament")
+    ///   .tournament_type(TournamentType::SingleElimination)
+    ///   .url("TestUrl")
+    ///   .subdomain("subdomain")
+    ///   .description("TEST TOURNAMENT created by challonge-rs");
+    /// let tb = c.create_tournament(&tcb);
+    /// ```
+    pub fn create_tournament(&self, tournament: &TournamentCreate) -> Result<Tournament, Error> {
+        try!(self.sandbox_guard_url(&tournament.subdomain, &tournament.url));
+        try!(description::validate(
+            &tournament.description,
+            description::MAX_DESCRIPTION_LENGTH
+        ));
+        let url = &format!("{}/tournaments.json", self.base_url);
+        let mut params = tc_to_pairs(tournament);
+        if tournament.subdomain.is_empty() {
+            if let Some(subdomain) = self.subdomain.as_ref() {
+                for param in params.iter_mut().filter(|p| p.0 == t!("subdomain")) {
+                    param.1 = subdomain.clone();
+                }
+            }
+        }
+        let response = try!(self.call(Method::Post, url, Some(params)));
+        decode_response(&response, Tournament::decode)
+    }
+
+    /// Update a tournament's attributes.
+    pub fn update_tournament(
+        &self,
+        id: &TournamentId,
+        tournament: &TournamentCreate,
+    ) -> Result<Tournament, Error> {
+        try!(self.sandbox_guard(id));
+        if !tournament.url.is_empty() {
+            try!(self.sandbox_guard_url(&tournament.subdomain, &tournament.url));
+        }
+        try!(description::validate(
+            &tournament.description,
+            description::MAX_DESCRIPTION_LENGTH
+        ));
+        let url = &format!(
+            "{}/tournaments/{}.json",
+            self.base_url,
+            self.tournament_path(id)
+        );
+        let response = try!(self.call(Method::Put, url, Some(tc_to_pairs(tournament))));
+        self.invalidate_tournament_settings(id);
+        let written = try!(decode_response(&response, Tournament::decode));
+        self.confirm_write(
+            written,
+            || self.get_tournament(id, &TournamentIncludes::All),
+            |t| t.updated_at,
+        )
+    }
+
+    /// Update a tournament's attributes only if it was not modified since `seen`, its previously seen `updated_at`.
+    /// The tournament is re-fetched before writing, `Error::Conflict` is returned if it changed.
+    pub fn update_tournament_if_unmodified(
+        &self,
+        id: &TournamentId,
+        tournament: &TournamentCreate,
+        seen: &ChallongeDateTime,
+    ) -> Result<Tournament, Error> {
+        let current = try!(self.get_tournament(id, &TournamentIncludes::Nothing));
+        try!(check_unmodified(seen, &current.updated_at));
+        self.update_tournament(id, tournament)
+    }
+
+    /// Deletes a tournament along with all its associated records. There is no undo, so use with care!
+    /// The call goes through the sandbox guard and the confirmation hook of the client.
+    /// Returns the final state of the deleted tournament.
+    pub fn delete_tournament(&self, id: &TournamentId) -> Result<Tournament, Error> {
+        try!(self.sandbox_guard(id));
+        try!(self.confirm(&format!("Delete tournament {}", self.tournament_path(id))));
+        let url = &format!(
+            "{}/tournaments/{}.json",
+            self.base_url,
+            self.tournament_path(id)
+        );
+        let response = try!(self.call(Method::Delete, url, None));
+        self.invalidate_tournament_settings(id);
+        decode_response(&response, Tournament::decode)
+    }
+
+    /// This should be invoked after a tournament's check-in window closes before the tournament is started.
+    ///
+    /// 1. Marks participants who have not checked in as inactive.
+    /// 2. Moves inactive participants to bottom seeds (ordered by original seed).
+    /// 3. Transitions the tournament state from 'checking_in' to 'checked_in'
+    ///
+    /// NOTE: Checked in participants on the waiting list will be promoted if slots become available.
+    pub fn tournament_process_checkins(
+        &self,
+        id: &TournamentId,
+        includes: &TournamentIncludes,
+    ) -> Result<(), Error> {
+        self.tournament_action("process_check_ins", id, includes)
+    }
+
+    /// When your tournament is in a 'checking_in' or 'checked_in' state, there's no way to edit the tournament's start time (start_at) or check-in duration (check_in_duration). You must first abort check-in, then you may edit those attributes.
+    ///
+    /// 1. Makes all participants active and clears their checked_in_at times.
+    /// 2. Transitions the tournament state from 'checking_in' or 'checked_in' to 'pending'
+    pub fn tournament_abort_checkins(
+        &self,
+        id: &TournamentId,
+        includes: &TournamentIncludes,
+    ) -> Result<(), Error> {
+        self.tournament_action("abort_check_in", id, includes)
+    }
+
+    /// Start a tournament, opening up first round matches for score reporting. The tournament must have at least 2 participants.
+    pub fn tournament_start(
+        &self,
+        id: &TournamentId,
+        includes: &TournamentIncludes,
+    ) -> Result<(), Error> {
+        self.tournament_action("start", id, includes)
+    }
+
+    /// Open a tournament for predictions, so a prediction contest can run before it is started.
+    /// The tournament must have a `prediction_method` other than `PredictionMethod::Disabled`.
+    ///
+    /// NOTE: Once open for predictions, match records are persisted and participants can no longer be added or removed.
+    pub fn tournament_open_for_predictions(
+        &self,
+        id: &TournamentId,
+        includes: &TournamentIncludes,
+    ) -> Result<(), Error> {
+        self.tournament_action("open_for_predictions", id, includes)
+    }
+
+    /// Finalize a tournament that has had all match scores submitted, rendering its results permanent.
+    pub fn tournament_finalize(
+        &self,
+        id: &TournamentId,
+        includes: &TournamentIncludes,
+    ) -> Result<(), Error> {
+        self.tournament_action("finalize", id, includes)
+    }
+
+    /// Reset a tournament, clearing all of its scores and attachments. You can then add/remove/edit participants before starting the tournament again.
+    pub fn tournament_reset(
+        &self,
+        id: &TournamentId,
+        includes: &TournamentIncludes,
+    ) -> Result<(), Error> {
+        try!(self.confirm(&format!("Reset tournament {}", self.tournament_path(id))));
+        self.tournament_action("reset", id, includes)
+    }
+
+    /// Retrieve a tournament's participant list.
+    pub fn participant_index(&self, id: &TournamentId) -> Result<ParticipantIndex, Error> {
+        self.participant_index_with(id, &ParticipantIndexOptions::new())
+    }
+
+    /// Retrieve a tournament's participant list matching the options.
+    pub fn participant_index_with(
+        &self,
+        id: &TournamentId,
+        options: &ParticipantIndexOptions,
+    ) -> Result<ParticipantIndex, Error> {
+        let url = index_url(
+            &format!(
+                "{}/tournaments/{}/participants.json",
+                self.base_url,
+                self.tournament_path(id)
+            ),
+            options,
+        );
+        let response = try!(self.call(Method::Get, url.as_str(), None));
+        decode_response(&response, ParticipantIndex::decode)
+    }
+
+    /// Add a participant to a tournament (up until it is started).
+    pub fn create_participant(
+        &self,
+        id: &TournamentId,
+        participant: &ParticipantCreate,
+    ) -> Result<Participant, Error> {
+        try!(self.sandbox_guard(id));
+        let url = &format!(
+            "{}/tournaments/{}/participants.json",
+            self.base_url,
+            self.tournament_path(id)
+        );
+        let response = try!(self.call(Method::Post, url, Some(pc_to_pairs(participant))));
+        self.invalidate_tournament_settings(id);
+        let written = try!(decode_response(&response, Participant::decode));
+        let participant_id = written.id.clone();
+        self.confirm_write(
+            written,
+            || self.get_participant(id, &participant_id, false),
+            |p| p.updated_at,
+        )
+    }
+
+    /// Bulk add participants to a tournament (up until it is started).
+    /// If an invalid participant is detected, bulk participant creation will halt and any previously added participants (from this API request) will be rolled back.
+    pub fn create_participant_bulk(
+        &self,
+        id: &TournamentId,
+        participants: Vec<ParticipantCreate>,
+    ) -> Result<(), Error> {
+        try!(self.sandbox_guard(id));
+        let url = &format!(
+            "{}/tournaments/{}/participants/bulk_add.json",
+            self.base_url,
+            self.tournament_path(id)
+        );
+        try!(self.call(Method::Post, url, Some(pcs_to_pairs(participants))));
+        self.invalidate_tournament_settings(id);
+        Ok(())
+    }
+
+    /// Bulk add participants to a tournament taking its signup cap into account.
+    /// The participant count is fetched fresh rather than from the settings cache.
+    /// A warning is logged if the participants do not fit into the remaining slots.
+    /// If `trim` is `true`, only participants which fit are submitted, the rest are reported as deferred.
+    /// Otherwise all participants are submitted and the deferred ones are placed on the waiting list by Challonge.
+    pub fn create_participant_bulk_capped(
+        &self,
+        id: &TournamentId,
+        participants: Vec<ParticipantCreate>,
+        trim: bool,
+    ) -> Result<SignupCapReport, Error> {
+        let tournament = try!(self.get_tournament(id, &TournamentIncludes::Nothing));
+        let report = tournament.check_signup_cap(participants);
+        if report.exceeds_cap() {
+            warn!(
+                target: LOG_TARGET,
+                "Bulk add to tournament {} exceeds signup cap: {} participant(s) deferred to the waiting list",
+                id,
+                report.deferred.len()
+            );
+        }
+
+        let mut submit = report.accepted.clone();
+        if !trim {
+            submit.extend(report.deferred.iter().cloned());
+        }
+        if !submit.is_empty() {
+            try!(self.create_participant_bulk(id, submit));
+        }
+        Ok(report)
+    }
+
+    /// Checks whether a Challonge username exists and can be invited to a tournament.
+    /// The API has no endpoint for this, so it is a best-effort probe of the user's public profile page.
+    pub fn check_username(&self, username: &str) -> Result<UsernameStatus, Error> {
+        if !participants::is_valid_username(username) {
+            return Ok(UsernameStatus::Invalid);
+        }
+        let mut url = url::Url::parse(WEB_BASE).unwrap();
+        url.path_segments_mut()
+            .unwrap()
+            .push("users")
+            .push(username);
+        match self.head(url.as_str()) {
+            Ok(_) => Ok(UsernameStatus::Exists),
+            Err(Error::Status(404, _, _)) => Ok(UsernameStatus::NotFound),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the variants of the live bracket image of the tournament which exist,
+    /// checking each of them with a HEAD request.
+    pub fn available_live_images(&self, tournament: &Tournament) -> Result<Vec<LiveImage>, Error> {
+        let mut available = Vec::new();
+        for image in LiveImage::variants(&tournament.live_image_url) {
+            match self.head(&image.url) {
+                Ok(_) => available.push(image),
+                Err(Error::Status(404, _, _)) | Err(Error::InsufficientPermissions { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(available)
+    }
+
+    /// Retrieve a single participant record for a tournament.
+    pub fn get_participant(
+        &self,
+        id: &TournamentId,
+        participant_id: &ParticipantId,
+        include_matches: bool,
+    ) -> Result<Participant, Error> {
+        let mut url = url::Url::parse(&format!(
+            "{}/tournaments/{}/participants/{}.json",
+            self.base_url,
+            self.tournament_path(id),
             participant_id.0
         ))
         .unwrap();
         url.query_pairs_mut()
             .append_pair("include_matches", &(include_matches as i64).to_string());
 
-        let response = try!(retry(|| self
-            .client
-            .get(url.as_str())
-            .headers(self.headers.clone())));
-        Participant::decode(try!(serde_json::from_reader(response)))
+        let response = try!(self.call(Method::Get, url.as_str(), None));
+        decode_response(&response, Participant::decode)
     }
 
     /// Update the attributes of a tournament participant.
@@ -544,37 +1459,102 @@ impl Challonge {
         participant_id: &ParticipantId,
         participant: &ParticipantCreate,
     ) -> Result<(), Error> {
+        try!(self.sandbox_guard(id));
         let url = &format!(
             "{}/tournaments/{}/participants/{}.json",
-            API_BASE,
-            id.to_string(),
+            self.base_url,
+            self.tournament_path(id),
+            participant_id.0
+        );
+        let _ = try!(self.call(Method::Put, url, Some(pc_to_pairs(participant))));
+        self.invalidate_tournament_settings(id);
+        Ok(())
+    }
+
+    /// Update the attributes of a tournament participant only if it was not modified since `seen`,
+    /// its previously seen `updated_at`. Fails with `Error::Conflict` if it changed.
+    pub fn update_participant_if_unmodified(
+        &self,
+        id: &TournamentId,
+        participant_id: &ParticipantId,
+        participant: &ParticipantCreate,
+        seen: &ChallongeDateTime,
+    ) -> Result<(), Error> {
+        let current = try!(self.get_participant(id, participant_id, false));
+        try!(check_unmodified(seen, &current.updated_at));
+        self.update_participant(id, participant_id, participant)
+    }
+
+    /// Update only the provided attributes of a tournament participant.
+    pub fn update_participant_with(
+        &self,
+        id: &TournamentId,
+        participant_id: &ParticipantId,
+        update: &ParticipantUpdate,
+    ) -> Result<(), Error> {
+        try!(self.sandbox_guard(id));
+        let url = &format!(
+            "{}/tournaments/{}/participants/{}.json",
+            self.base_url,
+            self.tournament_path(id),
             participant_id.0
         );
-        let body = pairs_to_string(pc_to_pairs(participant));
-        let _ = try!(retry(|| self
-            .client
-            .put(url)
-            .headers(self.headers.clone())
-            .body(&body)));
+        let _ = try!(self.call(Method::Put, url, Some(pu_to_pairs(update))));
+        self.invalidate_tournament_settings(id);
         Ok(())
     }
 
+    /// Update many participants of a tournament.
+    /// Updates are executed concurrently, no more than a few at a time, and all failures are collected into the report.
+    /// The updates share the rate limiter of the client, without one the batches are paced by
+    /// `participants::BATCH_INTERVAL_MILLIS`.
+    pub fn update_participants(
+        &self,
+        id: &TournamentId,
+        updates: &[(ParticipantId, ParticipantUpdate)],
+    ) -> BatchReport {
+        let pause = match self.rate_limiter {
+            Some(_) => None,
+            None => Some(StdDuration::from_millis(
+                participants::BATCH_INTERVAL_MILLIS,
+            )),
+        };
+        let id = id.clone();
+        let results = self.fan_out(
+            updates,
+            pause,
+            move |client: &Challonge, u: &(ParticipantId, ParticipantUpdate)| {
+                client.update_participant_with(&id, &u.0, &u.1)
+            },
+        );
+        let mut report = BatchReport {
+            succeeded: Vec::new(),
+            failed: Vec::new(),
+        };
+        for ((participant_id, _), result) in results {
+            match result {
+                Ok(()) => report.succeeded.push(participant_id),
+                Err(e) => report.failed.push((participant_id, e)),
+            }
+        }
+        report
+    }
+
     /// Checks a participant in, setting checked_in_at to the current time.
     pub fn check_in_participant(
         &self,
         id: &TournamentId,
         participant_id: &ParticipantId,
     ) -> Result<(), Error> {
+        try!(self.sandbox_guard(id));
         let url = &format!(
             "{}/tournaments/{}/participants/{}/check_in.json",
-            API_BASE,
-            id.to_string(),
+            self.base_url,
+            self.tournament_path(id),
             participant_id.0
         );
-        let _ = try!(retry(|| self
-            .client
-            .post(url)
-            .headers(self.headers.clone())));
+        let _ = try!(self.call(Method::Post, url, None));
+        self.invalidate_tournament_settings(id);
         Ok(())
     }
 
@@ -584,19 +1564,36 @@ impl Challonge {
         id: &TournamentId,
         participant_id: &ParticipantId,
     ) -> Result<(), Error> {
+        try!(self.sandbox_guard(id));
         let url = &format!(
             "{}/tournaments/{}/participants/{}/undo_check_in.json",
-            API_BASE,
-            id.to_string(),
+            self.base_url,
+            self.tournament_path(id),
             participant_id.0
         );
-        let _ = try!(retry(|| self
-            .client
-            .post(url)
-            .headers(self.headers.clone())));
+        let _ = try!(self.call(Method::Post, url, None));
+        self.invalidate_tournament_settings(id);
         Ok(())
     }
 
+    /// Marks a participant of a started tournament inactive, automatically forfeiting their remaining matches.
+    /// The participant stays in the participant list with `active` set to `false`.
+    /// Fails without changes if the tournament has not started, since the API would delete the participant.
+    pub fn deactivate_participant(
+        &self,
+        id: &TournamentId,
+        participant_id: &ParticipantId,
+    ) -> Result<(), Error> {
+        // not from the settings cache, a tournament reset since would make this delete the participant
+        let t = try!(self.get_tournament(id, &TournamentIncludes::Nothing));
+        if t.started_at.is_none() {
+            return Err(Error::Api(
+                "Participants of a tournament which has not started can not be deactivated",
+            ));
+        }
+        self.delete_participant(id, participant_id)
+    }
+
     /// If the tournament has not started, delete a participant, automatically filling in the abandoned seed number.
     /// If tournament is underway, mark a participant inactive, automatically forfeiting his/her remaining matches.
     pub fn delete_participant(
@@ -604,33 +1601,167 @@ impl Challonge {
         id: &TournamentId,
         participant_id: &ParticipantId,
     ) -> Result<(), Error> {
+        try!(self.sandbox_guard(id));
         let url = &format!(
             "{}/tournaments/{}/participants/{}.json",
-            API_BASE,
-            id.to_string(),
+            self.base_url,
+            self.tournament_path(id),
             participant_id.0
         );
-        let _ = try!(retry(|| self
-            .client
-            .delete(url)
-            .headers(self.headers.clone())));
+        let _ = try!(self.call(Method::Delete, url, None));
+        self.invalidate_tournament_settings(id);
         Ok(())
     }
 
+    /// Deletes all participants of a tournament. Only allowed if the tournament has not started yet.
+    pub fn clear_participants(&self, id: &TournamentId) -> Result<(), Error> {
+        try!(self.sandbox_guard(id));
+        try!(self.confirm(&format!(
+            "Delete all participants of tournament {}",
+            self.tournament_path(id)
+        )));
+        let url = &format!(
+            "{}/tournaments/{}/participants/clear.json",
+            self.base_url,
+            self.tournament_path(id)
+        );
+        let _ = try!(self.call(Method::Delete, url, None));
+        self.invalidate_tournament_settings(id);
+        Ok(())
+    }
+
+    /// Detects duplicate registrations in a tournament and removes them,
+    /// keeping the participant with the earliest seed in every group.
+    /// If `dry_run` is `true`, nothing is removed and the report describes what would be done.
+    pub fn merge_duplicate_participants(
+        &self,
+        id: &TournamentId,
+        dry_run: bool,
+    ) -> Result<MergeReport, Error> {
+        let index = try!(self.participant_index(id));
+        let groups = index.find_duplicates();
+        let mut removed = Vec::new();
+        for group in &groups {
+            for p in &group.duplicates {
+                if !dry_run {
+                    try!(self.delete_participant(id, &p.id));
+                }
+                removed.push(p.id.clone());
+            }
+        }
+        Ok(MergeReport {
+            groups: groups,
+            removed: removed,
+            dry_run: dry_run,
+        })
+    }
+
+    /// Retrieve participants of a tournament whose invitations have not been accepted yet.
+    pub fn pending_invitations(&self, id: &TournamentId) -> Result<Vec<Participant>, Error> {
+        let index = try!(self.participant_index(id));
+        Ok(index.pending_invitations().into_iter().cloned().collect())
+    }
+
+    /// Finds the participant of a tournament invited with the email address, `None` if there is no such participant.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    ///
+    /// use self::challonge::Challonge;
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap();
+    /// let id = TournamentId::Url(String::new(), "weekly1".to_owned());
+    /// if let Some(p) = c.find_participant_by_email(&id, "alice@example.com").unwrap() {
+    ///     c.resend_invitation(&id, &p).unwrap();
+    /// }
+    /// ```
+    pub fn find_participant_by_email(
+        &self,
+        id: &TournamentId,
+        email: &str,
+    ) -> Result<Option<Participant>, Error> {
+        let index = try!(self.participant_index(id));
+        Ok(index.find_by_email(email).cloned())
+    }
+
+    /// Re-sends an invitation to a participant of a pending tournament.
+    /// The API has no dedicated endpoint for this, so the participant is removed and added again
+    /// with the same name, email, username, seed and misc fields which triggers a new invitation.
+    /// The participant gets a new id, references to the old one have to be updated by the caller.
+    /// Fails without changes if the tournament is not pending or already has matches,
+    /// since removing the participant would then forfeit their matches.
+    pub fn resend_invitation(
+        &self,
+        id: &TournamentId,
+        participant: &Participant,
+    ) -> Result<Participant, Error> {
+        let t = try!(self.get_tournament(id, &TournamentIncludes::Nothing));
+        if t.state != TournamentStatus::Pending || t.started_at.is_some() {
+            return Err(Error::Api(
+                "Invitations can only be re-sent while the tournament is pending",
+            ));
+        }
+        if !try!(self.match_index(id, None, None)).0.is_empty() {
+            return Err(Error::Api(
+                "Invitations can not be re-sent once the tournament has matches",
+            ));
+        }
+        try!(self.delete_participant(id, &participant.id));
+        self.create_participant(id, &participant.to_create())
+    }
+
+    /// Removes all participants of a tournament whose invitations have not been accepted yet.
+    /// Returns ids of the removed participants.
+    pub fn remove_pending_invitations(
+        &self,
+        id: &TournamentId,
+    ) -> Result<Vec<ParticipantId>, Error> {
+        let mut removed = Vec::new();
+        for p in try!(self.pending_invitations(id)) {
+            try!(self.delete_participant(id, &p.id));
+            removed.push(p.id);
+        }
+        Ok(removed)
+    }
+
     /// Randomize seeds among participants. Only applicable before a tournament has started.
     pub fn randomize_participants(&self, id: &TournamentId) -> Result<(), Error> {
+        try!(self.sandbox_guard(id));
         let url = &format!(
             "{}/tournaments/{}/participants/randomize.json",
-            API_BASE,
-            id.to_string()
+            self.base_url,
+            self.tournament_path(id)
         );
-        let _ = try!(retry(|| self
-            .client
-            .post(url)
-            .headers(self.headers.clone())));
+        let _ = try!(self.call(Method::Post, url, None));
+        self.invalidate_tournament_settings(id);
         Ok(())
     }
 
+    /// Randomize seeds among active participants locally using the given random number generator.
+    /// Unlike `randomize_participants` the result is reproducible with a seeded generator.
+    /// Seeds are updated one by one, failures are collected into the report.
+    pub fn randomize_participants_with<R: Rng>(
+        &self,
+        id: &TournamentId,
+        rng: &mut R,
+    ) -> Result<BatchReport, Error> {
+        let index = try!(self.participant_index(id));
+        let mut report = BatchReport {
+            succeeded: Vec::new(),
+            failed: Vec::new(),
+        };
+        for (participant_id, update) in seeding::shuffled_seeds(&index, rng) {
+            match self.update_participant_with(id, &participant_id, &update) {
+                Ok(()) => report.succeeded.push(participant_id),
+                Err(e) => report.failed.push((participant_id, e)),
+            }
+        }
+        Ok(report)
+    }
+
     /// Retrieve a tournament's match list.
     pub fn match_index(
         &self,
@@ -638,129 +1769,347 @@ impl Challonge {
         state: Option<MatchState>,
         participant_id: Option<ParticipantId>,
     ) -> Result<MatchIndex, Error> {
-        let mut url = hyper::Url::parse(&format!(
-            "{}/tournaments/{}/matches.json",
-            API_BASE,
-            id.to_string()
+        let options = MatchIndexOptions {
+            state: state,
+            participant_id: participant_id,
+        };
+        self.match_index_with(id, &options)
+    }
+
+    /// Retrieve a tournament's match list matching the options.
+    pub fn match_index_with(
+        &self,
+        id: &TournamentId,
+        options: &MatchIndexOptions,
+    ) -> Result<MatchIndex, Error> {
+        let url = index_url(
+            &format!(
+                "{}/tournaments/{}/matches.json",
+                self.base_url,
+                self.tournament_path(id)
+            ),
+            options,
+        );
+        let response = try!(self.call(Method::Get, url.as_str(), None));
+        decode_response(&response, MatchIndex::decode)
+    }
+
+    /// Retrieve a single match record for a tournament.
+    pub fn get_match(
+        &self,
+        id: &TournamentId,
+        match_id: &MatchId,
+        include_attachments: bool,
+    ) -> Result<Match, Error> {
+        let mut url = url::Url::parse(&format!(
+            "{}/tournaments/{}/matches/{}.json",
+            self.base_url,
+            self.tournament_path(id),
+            match_id.0
         ))
         .unwrap();
+        url.query_pairs_mut().append_pair(
+            "include_attachments",
+            &(include_attachments as i64).to_string(),
+        );
+        let response = try!(self.call(Method::Get, url.as_str(), None));
+        decode_response(&response, Match::decode)
+    }
+
+    /// Update/submit the score(s) for a match.
+    pub fn update_match(
+        &self,
+        id: &TournamentId,
+        match_id: &MatchId,
+        match_update: &MatchUpdate,
+    ) -> Result<Match, Error> {
+        try!(self.sandbox_guard(id));
+        let url = &format!(
+            "{}/tournaments/{}/matches/{}.json",
+            self.base_url,
+            self.tournament_path(id),
+            match_id.0
+        );
+        let response = try!(self.call(Method::Put, url, Some(mu_to_pairs(match_update))));
+        self.invalidate_tournament_settings(id);
+        let written = try!(decode_response(&response, Match::decode));
+        self.confirm_write(
+            written,
+            || self.get_match(id, match_id, false),
+            |m| m.updated_at,
+        )
+    }
+
+    /// Update/submit the score(s) for a match only if it was not modified since `seen`,
+    /// its previously seen `updated_at`. Fails with `Error::Conflict` if it changed.
+    pub fn update_match_if_unmodified(
+        &self,
+        id: &TournamentId,
+        match_id: &MatchId,
+        match_update: &MatchUpdate,
+        seen: &ChallongeDateTime,
+    ) -> Result<Match, Error> {
+        let current = try!(self.get_match(id, match_id, false));
+        try!(check_unmodified(seen, &current.updated_at));
+        self.update_match(id, match_id, match_update)
+    }
+
+    /// Update/submit the score(s) for a match unless the same update was acknowledged recently.
+    /// `key` identifies the update, e.g. `MatchUpdate::idempotency_key`. A resubmission with a key
+    /// acknowledged within the idempotency window returns the acknowledged match without a request,
+    /// so a retry on a flaky connection does not notify the players twice.
+    /// # Example
+    /// ```ignore
+    /// let key = update.idempotency_key(&match_id);
+    /// let m = c.update_match_once(&id, &match_id, &update, &key).unwrap();
+    /// ```
+    pub fn update_match_once(
+        &self,
+        id: &TournamentId,
+        match_id: &MatchId,
+        match_update: &MatchUpdate,
+        key: &str,
+    ) -> Result<Match, Error> {
+        let key = format!("{}/{}", self.tournament_path(id), key);
+        if let Some(acknowledged) =
+            self.submissions
+                .get(&key, &self.clock.now(), self.idempotency_window)
         {
-            let mut pairs = url.query_pairs_mut();
-            if let Some(s) = state {
-                pairs.append_pair("state", &s.to_string());
-            }
-            if let Some(pid) = participant_id {
-                pairs.append_pair("participant_id", &pid.0.to_string());
+            return Ok(acknowledged);
+        }
+        let updated = try!(self.update_match(id, match_id, match_update));
+        self.submissions
+            .insert(&key, self.clock.now(), updated.clone());
+        Ok(updated)
+    }
+
+    /// Retrieve a match's attachments.
+    pub fn attachments_index(
+        &self,
+        id: &TournamentId,
+        match_id: &MatchId,
+    ) -> Result<AttachmentIndex, Error> {
+        let url = &format!(
+            "{}/tournaments/{}/matches/{}/attachments.json",
+            self.base_url,
+            self.tournament_path(id),
+            match_id.0
+        );
+        let response = try!(self.call(Method::Get, url, None));
+        decode_response(&response, AttachmentIndex::decode)
+    }
+
+    /// Collects the attachments of every match of a tournament, grouped by round and match.
+    /// Matches are fetched a few at a time, batches are paced by `gallery::GALLERY_INTERVAL_MILLIS`.
+    /// Matches whose attachments could not be fetched are listed in `Gallery::failed`.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    ///
+    /// use self::challonge::Challonge;
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap();
+    /// let gallery = c.attachment_gallery(&TournamentId::Id(2669881)).unwrap();
+    /// for (round, matches) in &gallery.rounds {
+    ///     println!("Round {}: {} matches with media", round, matches.len());
+    /// }
+    /// ```
+    pub fn attachment_gallery(&self, id: &TournamentId) -> Result<Gallery, Error> {
+        let index = try!(self.match_index(id, None, None));
+        let with_attachments: Vec<Match> = index
+            .0
+            .into_iter()
+            .filter(|m| m.has_attachment || m.attachment_count.map_or(false, |c| c > 0))
+            .collect();
+        let id = id.clone();
+        let results = self.fan_out(
+            &with_attachments,
+            Some(StdDuration::from_millis(gallery::GALLERY_INTERVAL_MILLIS)),
+            move |client: &Challonge, m: &Match| client.attachments_index(&id, &m.id),
+        );
+        let mut collected = Vec::new();
+        let mut failed = Vec::new();
+        for (m, result) in results {
+            match result {
+                Ok(attachments) => collected.push((m, attachments.0)),
+                Err(e) => failed.push((m.id, e)),
             }
         }
-        let response = try!(retry(|| self
-            .client
-            .get(url.as_str())
-            .headers(self.headers.clone())));
-        MatchIndex::decode(try!(serde_json::from_reader(response)))
+        let mut gallery = Gallery::new(collected);
+        gallery.failed = failed;
+        Ok(gallery)
     }
 
-    /// Retrieve a single match record for a tournament.
-    pub fn get_match(
+    /// Retrieve a single match attachment record.
+    pub fn get_attachment(
+        &self,
+        id: &TournamentId,
+        match_id: &MatchId,
+        attachment_id: &AttachmentId,
+    ) -> Result<Attachment, Error> {
+        let url = &format!(
+            "{}/tournaments/{}/matches/{}/attachments/{}.json",
+            self.base_url,
+            self.tournament_path(id),
+            match_id.0,
+            attachment_id.0
+        );
+        let response = try!(self.call(Method::Get, url, None));
+        decode_response(&response, Attachment::decode)
+    }
+
+    /// Add a file, link, or text attachment to a match.
+    /// The associated tournament's "accept_attachments" attribute is checked beforehand:
+    /// if it is `false`, `Error::AttachmentsNotAccepted` is returned without submitting the attachment.
+    pub fn create_attachment(
+        &self,
+        id: &TournamentId,
+        match_id: &MatchId,
+        attachment: &AttachmentCreate,
+    ) -> Result<Attachment, Error> {
+        let tournament = try!(self.tournament_settings(id));
+        if !tournament.accept_attachments {
+            return Err(Error::AttachmentsNotAccepted(id.clone()));
+        }
+        self.post_attachment(id, match_id, attachment)
+    }
+
+    /// Add an attachment to a match, enabling attachments for the tournament first if they are not accepted.
+    pub fn create_attachment_enabling(
         &self,
         id: &TournamentId,
         match_id: &MatchId,
-        include_attachments: bool,
-    ) -> Result<Match, Error> {
-        let mut url = hyper::Url::parse(&format!(
-            "{}/tournaments/{}/matches/{}.json",
-            API_BASE,
-            id.to_string(),
-            match_id.0
-        ))
-        .unwrap();
-        url.query_pairs_mut().append_pair(
-            "include_attachments",
-            &(include_attachments as i64).to_string(),
-        );
-        let response = try!(retry(|| self
-            .client
-            .get(url.as_str())
-            .headers(self.headers.clone())));
-        Match::decode(try!(serde_json::from_reader(response)))
+        attachment: &AttachmentCreate,
+    ) -> Result<Attachment, Error> {
+        let tournament = try!(self.tournament_settings(id));
+        if !tournament.accept_attachments {
+            try!(self.set_accept_attachments(id, true));
+        }
+        self.post_attachment(id, match_id, attachment)
     }
 
-    /// Update/submit the score(s) for a match.
-    pub fn update_match(
+    /// Add an attachment to a match unless the same file with the same description and url was already
+    /// uploaded to it by this client (or one of its clones), in which case the existing attachment is returned.
+    /// Files are compared by `AttachmentCreate::content_hash`, attachments without a file are always created.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    ///
+    /// use self::challonge::{AttachmentCreate, Challonge, MatchId};
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap();
+    /// let mut ac = AttachmentCreate::new();
+    /// ac.asset(screenshot_bytes);
+    /// // a retry after a timeout does not upload the screenshot again
+    /// let a = c.create_attachment_once(&TournamentId::Id(2669881), &MatchId(1), &ac).unwrap();
+    /// ```
+    pub fn create_attachment_once(
         &self,
         id: &TournamentId,
         match_id: &MatchId,
-        match_update: &MatchUpdate,
-    ) -> Result<Match, Error> {
-        let url = &format!(
-            "{}/tournaments/{}/matches/{}.json",
-            API_BASE,
-            id.to_string(),
-            match_id.0
-        );
-        let body = pairs_to_string(mu_to_pairs(match_update));
-        let response = try!(retry(|| self
-            .client
-            .put(url)
-            .headers(self.headers.clone())
-            .body(&body)));
-        Match::decode(try!(serde_json::from_reader(response)))
+        attachment: &AttachmentCreate,
+    ) -> Result<Attachment, Error> {
+        let hash = match attachment.content_hash() {
+            Some(hash) => hash,
+            None => return self.create_attachment(id, match_id, attachment),
+        };
+        if let Some(existing) = self.uploads.get(match_id, hash) {
+            return Ok(existing);
+        }
+        let created = try!(self.create_attachment(id, match_id, attachment));
+        self.uploads.insert(hash, created.clone());
+        Ok(created)
     }
 
-    /// Retrieve a match's attachments.
-    pub fn attachments_index(
+    /// Reads the metadata of a match stored by `set_match_metadata`, `None` if it has none.
+    pub fn match_metadata<M: MatchMetadata>(
         &self,
         id: &TournamentId,
         match_id: &MatchId,
-    ) -> Result<AttachmentIndex, Error> {
-        let url = &format!(
-            "{}/tournaments/{}/matches/{}/attachments.json",
-            API_BASE,
-            id.to_string(),
-            match_id.0
-        );
-        let response = try!(retry(|| self.client.get(url).headers(self.headers.clone())));
-        AttachmentIndex::decode(try!(serde_json::from_reader(response)))
+    ) -> Result<Option<M>, Error> {
+        let index = try!(self.attachments_index(id, match_id));
+        let stored = metadata::find(&index.0)
+            .and_then(|a| a.description.as_ref())
+            .and_then(|d| metadata::decode(d));
+        match stored {
+            Some(meta) => meta.map(Some),
+            None => Ok(None),
+        }
     }
 
-    /// Retrieve a single match attachment record.
-    pub fn get_attachment(
+    /// Stores structured metadata of a match (e.g. map picks or a VOD link) in a text attachment,
+    /// replacing the previously stored metadata, see the `metadata` module.
+    /// Fails with `Error::AttachmentsNotAccepted` if the tournament does not accept attachments.
+    /// # Example
+    /// ```ignore
+    /// extern crate challonge;
+    ///
+    /// use std::collections::BTreeMap;
+    /// use self::challonge::{Challonge, MatchId, TournamentId};
+    ///
+    /// let c = Challonge::builder()
+    ///     .credentials("myusername", "myapikey")
+    ///     .build()
+    ///     .unwrap();
+    /// let mut meta = BTreeMap::new();
+    /// meta.insert("map".to_owned(), "Dust II".to_owned());
+    /// let id = TournamentId::Id(2669881);
+    /// c.set_match_metadata(&id, &MatchId(59549286), &meta).unwrap();
+    /// let read: Option<BTreeMap<String, String>> = c.match_metadata(&id, &MatchId(59549286)).unwrap();
+    /// ```
+    pub fn set_match_metadata<M: MatchMetadata>(
         &self,
         id: &TournamentId,
         match_id: &MatchId,
-        attachment_id: &AttachmentId,
+        meta: &M,
     ) -> Result<Attachment, Error> {
+        let mut attachment = AttachmentCreate::new();
+        attachment.description(try!(metadata::encode(meta)));
+        let index = try!(self.attachments_index(id, match_id));
+        match metadata::find(&index.0) {
+            Some(existing) => self.update_attachment(id, match_id, &existing.id, &attachment),
+            None => self.create_attachment(id, match_id, &attachment),
+        }
+    }
+
+    /// Allow or disallow match attachments for a tournament.
+    pub fn set_accept_attachments(&self, id: &TournamentId, accept: bool) -> Result<(), Error> {
+        try!(self.sandbox_guard(id));
         let url = &format!(
-            "{}/tournaments/{}/matches/{}/attachments/{}.json",
-            API_BASE,
-            id.to_string(),
-            match_id.0,
-            attachment_id.0
+            "{}/tournaments/{}.json",
+            self.base_url,
+            self.tournament_path(id)
         );
-        let response = try!(retry(|| self.client.get(url).headers(self.headers.clone())));
-        Attachment::decode(try!(serde_json::from_reader(response)))
+        let _ = try!(self.call(
+            Method::Put,
+            url,
+            Some(vec![(t!("accept_attachments"), accept.to_string())])
+        ));
+        self.invalidate_tournament_settings(id);
+        Ok(())
     }
 
-    /// Add a file, link, or text attachment to a match. NOTE: The associated tournament's "accept_attachments" attribute must be true for this action to succeed.
-    pub fn create_attachment(
+    fn post_attachment(
         &self,
         id: &TournamentId,
         match_id: &MatchId,
         attachment: &AttachmentCreate,
     ) -> Result<Attachment, Error> {
+        try!(self.sandbox_guard(id));
         let url = &format!(
             "{}/tournaments/{}/matches/{}/attachments.json",
-            API_BASE,
-            id.to_string(),
+            self.base_url,
+            self.tournament_path(id),
             match_id.0
         );
-        let body = pairs_to_string(at_to_pairs(attachment));
-        let response = try!(retry(|| self
-            .client
-            .post(url)
-            .headers(self.headers.clone())
-            .body(&body)));
-        Attachment::decode(try!(serde_json::from_reader(response)))
+        let response = try!(self.call(Method::Post, url, Some(at_to_pairs(attachment))));
+        decode_response(&response, Attachment::decode)
     }
 
     /// Update the attributes of a match attachment.
@@ -771,20 +2120,16 @@ impl Challonge {
         attachment_id: &AttachmentId,
         attachment: &AttachmentCreate,
     ) -> Result<Attachment, Error> {
+        try!(self.sandbox_guard(id));
         let url = &format!(
             "{}/tournaments/{}/matches/{}/attachments/{}.json",
-            API_BASE,
-            id.to_string(),
+            self.base_url,
+            self.tournament_path(id),
             match_id.0,
             attachment_id.0
         );
-        let body = pairs_to_string(at_to_pairs(attachment));
-        let response = try!(retry(|| self
-            .client
-            .put(url)
-            .headers(self.headers.clone())
-            .body(&body)));
-        Attachment::decode(try!(serde_json::from_reader(response)))
+        let response = try!(self.call(Method::Put, url, Some(at_to_pairs(attachment))));
+        decode_response(&response, Attachment::decode)
     }
 
     /// Delete a match attachment.
@@ -794,43 +2139,138 @@ impl Challonge {
         match_id: &MatchId,
         attachment_id: &AttachmentId,
     ) -> Result<(), Error> {
+        try!(self.sandbox_guard(id));
         let url = &format!(
             "{}/tournaments/{}/matches/{}/attachments/{}.json",
-            API_BASE,
-            id.to_string(),
+            self.base_url,
+            self.tournament_path(id),
             match_id.0,
             attachment_id.0
         );
-        let _ = try!(retry(|| self
-            .client
-            .delete(url)
-            .headers(self.headers.clone())));
+        let _ = try!(self.call(Method::Delete, url, None));
+        self.uploads.remove(match_id, attachment_id);
         Ok(())
     }
 
+    /// Calls `f` for every item with a clone of the client, a few items at a time on their own threads,
+    /// sleeping `pause` between the batches. Returns the items with their results in order.
+    fn fan_out<T, R, F>(
+        &self,
+        items: &[T],
+        pause: Option<StdDuration>,
+        f: F,
+    ) -> Vec<(T, Result<R, Error>)>
+    where
+        T: Clone + Send + 'static,
+        R: Send + 'static,
+        F: Fn(&Challonge, &T) -> Result<R, Error> + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        let mut results = Vec::with_capacity(items.len());
+        for (i, chunk) in items.chunks(BATCH_CONCURRENCY).enumerate() {
+            if let Some(pause) = pause.filter(|_| i > 0) {
+                self.clock.sleep(pause);
+            }
+            let handles: Vec<_> = chunk
+                .iter()
+                .map(|item| {
+                    let client = self.clone();
+                    let f = f.clone();
+                    let owned = item.clone();
+                    (item.clone(), thread::spawn(move || f(&client, &owned)))
+                })
+                .collect();
+            for (item, handle) in handles {
+                let result = handle
+                    .join()
+                    .unwrap_or_else(|_| Err(Error::Api("A batched request panicked")));
+                results.push((item, result));
+            }
+        }
+        results
+    }
+
     fn tournament_action(
         &self,
         endpoint: &str,
         id: &TournamentId,
         includes: &TournamentIncludes,
     ) -> Result<(), Error> {
-        let mut url = hyper::Url::parse(&format!(
+        try!(self.sandbox_guard(id));
+        let mut url = url::Url::parse(&format!(
             "{}/tournaments/{}/{}.json",
-            API_BASE,
-            id.to_string(),
+            self.base_url,
+            self.tournament_path(id),
             endpoint
         ))
         .unwrap();
         Challonge::add_tournament_includes(&mut url, includes);
-        let _ = try!(retry(|| self
-            .client
-            .post(url.as_str())
-            .headers(self.headers.clone())));
+        let _ = try!(self.call(Method::Post, url.as_str(), None));
+        self.invalidate_tournament_settings(id);
         Ok(())
     }
 
+    /// Fails if the sandbox guard is enabled and the tournament url does not carry the sandbox prefix.
+    /// Tournaments given by id are fetched to learn their url.
+    fn sandbox_guard(&self, id: &TournamentId) -> Result<(), Error> {
+        if self.sandbox_prefix.is_none() {
+            return Ok(());
+        }
+        match *id {
+            TournamentId::Url(ref subdomain, ref url) => self.sandbox_guard_url(subdomain, url),
+            TournamentId::Id(_) => {
+                let t = try!(self.tournament_settings(id));
+                if self.in_sandbox(&t.url) {
+                    Ok(())
+                } else {
+                    Err(Error::SandboxViolation(id.clone()))
+                }
+            }
+        }
+    }
+
+    fn sandbox_guard_url(&self, subdomain: &str, url: &str) -> Result<(), Error> {
+        if self.in_sandbox(url) {
+            Ok(())
+        } else {
+            Err(Error::SandboxViolation(TournamentId::Url(
+                subdomain.to_owned(),
+                url.to_owned(),
+            )))
+        }
+    }
+
+    fn in_sandbox(&self, url: &str) -> bool {
+        self.sandbox_prefix
+            .as_ref()
+            .map_or(true, |prefix| url.starts_with(&prefix[..]))
+    }
+
+    /// Asks the confirmation hook of the client, if any, whether the destructive action may proceed.
+    fn confirm(&self, action: &str) -> Result<(), Error> {
+        match self.confirmation.as_ref() {
+            Some(confirmation) if !confirmation(action) => Err(Error::Cancelled(action.to_owned())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the tournament identifier as used in API urls, applying the default subdomain.
+    fn tournament_path(&self, id: &TournamentId) -> String {
+        self.resolve_id(id).to_string()
+    }
+
+    /// Applies the default subdomain to a tournament identifier.
+    fn resolve_id(&self, id: &TournamentId) -> TournamentId {
+        match (id, self.subdomain.as_ref()) {
+            (&TournamentId::Url(ref subdomain, ref url), Some(default)) if subdomain.is_empty() => {
+                TournamentId::Url(default.clone(), url.clone())
+            }
+            _ => id.clone(),
+        }
+    }
+
     // TODO refactor to be better
-    fn add_tournament_includes(url: &mut hyper::Url, includes: &TournamentIncludes) {
+    fn add_tournament_includes(url: &mut url::Url, includes: &TournamentIncludes) {
         let mut pairs = url.query_pairs_mut();
         match *includes {
             TournamentIncludes::All => {
@@ -848,16 +2288,768 @@ impl Challonge {
                     .append_pair("include_participants", "1")
                     .append_pair("include_matches", "0");
             }
+            TournamentIncludes::Nothing => {
+                pairs
+                    .append_pair("include_participants", "0")
+                    .append_pair("include_matches", "0");
+            }
+        }
+    }
+
+    /// Re-fetches a written resource according to the read-your-writes policy of the client.
+    fn confirm_write<T, F, U>(&self, written: T, fetch: F, updated_at: U) -> Result<T, Error>
+    where
+        F: FnMut() -> Result<T, Error>,
+        U: Fn(&T) -> ChallongeDateTime,
+    {
+        let policy = match self.read_your_writes {
+            Some(policy) => policy,
+            None => return Ok(written),
+        };
+        let written_at = updated_at(&written);
+        match try!(policy.confirm(self.clock(), &written_at, fetch, updated_at)) {
+            Some(confirmed) => Ok(confirmed),
+            None => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Change made at {} did not become visible",
+                    written_at
+                );
+                Ok(written)
+            }
+        }
+    }
+
+    /// Sends an authorized API request and checks the response status.
+    /// Parameters are encoded into the body according to the body encoding of the client.
+    /// Transient failures are retried according to the retry policy of the client.
+    fn call(
+        &self,
+        method: Method,
+        url: &str,
+        params: Option<FieldPairs>,
+    ) -> Result<Response, Error> {
+        self.retrying(method, || self.call_once(method, url, params.clone()))
+    }
+
+    /// Sends a HEAD request to a page of the website, e.g. a profile page, without the credentials.
+    /// It is paced, timed, retried and recorded like API calls.
+    fn head(&self, url: &str) -> Result<Response, Error> {
+        self.retrying(Method::Head, || {
+            let mut request = Request::new(Method::Head, url);
+            request.max_response_bytes = Some(self.limits.max_body_bytes);
+            if let Some(user_agent) = self.user_agent.as_ref() {
+                request
+                    .headers
+                    .push(("User-Agent".to_owned(), user_agent.clone()));
+            }
+            self.send(request)
+        })
+    }
+
+    /// Sends a request, retrying it according to the retry policy of the client.
+    fn retrying<F>(&self, method: Method, mut send: F) -> Result<Response, Error>
+    where
+        F: FnMut() -> Result<Response, Error>,
+    {
+        let mut attempt = 1;
+        loop {
+            let error = match send() {
+                Ok(response) => return Ok(response),
+                Err(e) => e,
+            };
+            let policy = match self.retry {
+                Some(ref policy) if policy.should_retry(method, &error, attempt) => policy,
+                _ => return Err(error),
+            };
+            let delay = policy.delay(attempt, &error, &mut rand::thread_rng());
+            match self.time_left() {
+                Ok(Some(left)) if delay >= left => return Err(error),
+                Err(_) => return Err(error),
+                _ => {}
+            }
+            self.clock.sleep(delay);
+            attempt += 1;
+        }
+    }
+
+    /// Returns the time left until the deadline of the call, `Error::Timeout` if it has passed.
+    fn time_left(&self) -> Result<Option<StdDuration>, Error> {
+        match self.deadline {
+            Some(deadline) => match (deadline - self.clock.now()).to_std() {
+                Ok(left) if left > StdDuration::from_secs(0) => Ok(Some(left)),
+                _ => Err(Error::Timeout),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Sends an authorized API request once and checks the response status.
+    fn call_once(
+        &self,
+        method: Method,
+        url: &str,
+        params: Option<FieldPairs>,
+    ) -> Result<Response, Error> {
+        let request = try!(self.prepare_request(method, url, params));
+        self.send(request)
+    }
+
+    /// Sends a request once and checks the response status.
+    fn send(&self, mut request: Request) -> Result<Response, Error> {
+        let method = request.method;
+        let url = request.url.clone();
+        let url = &url[..];
+        if let Some(ref etags) = self.etags {
+            etags.prepare(&mut request);
+        }
+        if let Some(ref breaker) = self.circuit {
+            try!(breaker.check(self.clock.now()));
+        }
+        let left = try!(self.time_left());
+        if let Some(ref limiter) = self.rate_limiter {
+            match left {
+                Some(left) => {
+                    if !limiter.acquire_within(self.priority, left) {
+                        return Err(Error::Timeout);
+                    }
+                }
+                None => limiter.acquire(self.priority),
+            }
+        }
+        request.timeout = try!(self.time_left());
+        #[cfg(feature = "tracing")]
+        let span = trace::call_span(method, url);
+        #[cfg(feature = "tracing")]
+        let _entered = span.enter();
+        let started = Instant::now();
+        let response = self.transport.send(&request);
+        let latency = started.elapsed();
+        let sent = request.body.as_ref().map_or(0, |b| b.len());
+        match response {
+            Ok(ref r) => debug!(
+                target: LOG_TARGET,
+                "{} {} as {} (api key redacted), {} bytes sent: {} with {} bytes in {:?}",
+                method,
+                redact_url(url),
+                self.user_name,
+                sent,
+                r.status,
+                r.body.len(),
+                latency
+            ),
+            Err(ref e) => debug!(
+                target: LOG_TARGET,
+                "{} {} as {} (api key redacted), {} bytes sent: failed in {:?}: {:?}",
+                method,
+                redact_url(url),
+                self.user_name,
+                sent,
+                latency,
+                e
+            ),
+        }
+        #[cfg(feature = "tracing")]
+        trace::record(&span, response.as_ref().ok().map(|r| r.status), latency);
+        let response = match self.etags {
+            Some(ref etags) => response.map(|r| etags.revalidate(&request, r)),
+            None => response,
+        };
+        let failed = response.as_ref().map_or(true, |r| !r.is_success());
+        self.stats.record(method, url, latency, failed);
+        if self.slow_call_threshold.map_or(false, |t| latency > t) {
+            warn!(
+                target: LOG_TARGET,
+                "Slow call: {} took {:?}",
+                stats::endpoint(method, url),
+                latency
+            );
+        }
+        let result = response.and_then(|r| self.check_response(&request, r));
+        if let Some(ref breaker) = self.circuit {
+            breaker.record(self.clock.now(), &result);
+        }
+        result
+    }
+
+    /// Checks the response to a request against the limits of the client and maps failures to errors.
+    fn check_response(&self, request: &Request, response: Response) -> Result<Response, Error> {
+        try!(self.limits.check(&response.body));
+        if !response.is_success() {
+            let mut error = permission_context(Error::error_from_response(response), request);
+            if request.url.contains("/participants") {
+                error = error.duplicate_participant();
+            }
+            return Err(error);
+        }
+        Ok(response)
+    }
+}
+
+/// Fills in the subdomain and the tournament of a permission error from the request it was returned for.
+fn permission_context(error: Error, request: &Request) -> Error {
+    match error {
+        Error::InsufficientPermissions {
+            subdomain: None,
+            tournament: None,
+        } => {}
+        e => return e,
+    }
+    let parsed = match url::Url::parse(&request.url) {
+        Ok(u) => u,
+        Err(_) => {
+            return Error::InsufficientPermissions {
+                subdomain: None,
+                tournament: None,
+            }
+        }
+    };
+
+    let tournament = parsed.path_segments().and_then(|mut segments| {
+        segments.find(|s| *s == "tournaments");
+        segments
+            .next()
+            .map(|s| s.trim_end_matches(".json"))
+            .filter(|s| !s.is_empty())
+            .map(parse_tournament_path)
+    });
+    let mut subdomain = match tournament {
+        Some(TournamentId::Url(ref subdomain, _)) if !subdomain.is_empty() => {
+            Some(subdomain.clone())
+        }
+        _ => None,
+    };
+    if subdomain.is_none() {
+        let body = request.body.as_ref().map_or("", |b| &b[..]);
+        subdomain = parsed
+            .query_pairs()
+            .chain(url::form_urlencoded::parse(body.as_bytes()))
+            .find(|p| p.0 == "subdomain" || p.0 == t!("subdomain"))
+            .map(|p| p.1.into_owned())
+            .filter(|s| !s.is_empty());
+    }
+    Error::InsufficientPermissions {
+        subdomain: subdomain,
+        tournament: tournament,
+    }
+}
+
+/// Fails with `Error::Conflict` if a resource was modified after it was seen.
+fn check_unmodified(seen: &ChallongeDateTime, current: &ChallongeDateTime) -> Result<(), Error> {
+    if current > seen {
+        Err(Error::Conflict {
+            seen: *seen,
+            current: *current,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Parses a tournament identifier as used in API urls: an id, `url` or `subdomain-url`.
+fn parse_tournament_path(path: &str) -> TournamentId {
+    if let Ok(id) = path.parse() {
+        return TournamentId::Id(id);
+    }
+    match path.find('-') {
+        Some(i) => TournamentId::Url(path[..i].to_owned(), path[i + 1..].to_owned()),
+        None => TournamentId::Url(String::new(), path.to_owned()),
+    }
+}
+
+impl Challonge {
+    /// Builds an authorized request.
+    /// If the url is longer than `MAX_URL_LENGTH`, its query is moved into the body,
+    /// requests which can not have a body fail with `Error::UrlTooLong` instead of an opaque 414 from the server.
+    fn prepare_request(
+        &self,
+        method: Method,
+        url: &str,
+        params: Option<FieldPairs>,
+    ) -> Result<Request, Error> {
+        let mut url = url.to_owned();
+        let normalization = self.normalization;
+        let mut body_params: Option<Vec<(String, String)>> = params.map(|p| {
+            p.into_iter()
+                .map(|(k, v)| (k.to_owned(), normalization.apply(&v)))
+                .collect()
+        });
+        if url.len() > MAX_URL_LENGTH {
+            if method == Method::Get || method == Method::Head {
+                return Err(Error::UrlTooLong(url.len()));
+            }
+            let mut parsed = try!(url::Url::parse(&url).map_err(|_| Error::Api("Invalid url")));
+            let mut moved: Vec<(String, String)> = parsed.query_pairs().into_owned().collect();
+            parsed.set_query(None);
+            url = parsed.as_str().to_owned();
+            if let Some(p) = body_params.take() {
+                moved.extend(p);
+            }
+            body_params = Some(moved);
+        }
+
+        let mut request = Request::new(method, url);
+        request.max_response_bytes = Some(self.limits.max_body_bytes);
+        request
+            .headers
+            .push(("Authorization".to_owned(), self.authorization.clone()));
+        if let Some(user_agent) = self.user_agent.as_ref() {
+            request
+                .headers
+                .push(("User-Agent".to_owned(), user_agent.clone()));
+        }
+        if let Some(params) = body_params {
+            let body = try!(self.encoding.encode(&params));
+            request.headers.push((
+                "Content-Type".to_owned(),
+                self.encoding.content_type().to_owned(),
+            ));
+            request.body = Some(body);
         }
+        Ok(request)
     }
+}
 
-    // fn prepare<'a>(&self, url: &str) -> hyper::client::RequestBuilder<'a> {
-    //     self.client.get(url).headers(self.headers.clone())
-    // }
+impl fmt::Debug for Challonge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Challonge")
+            .field("user_name", &self.user_name)
+            .field("api_key", &"REDACTED")
+            .field("encoding", &self.encoding)
+            .field("normalization", &self.normalization)
+            .field("subdomain", &self.subdomain)
+            .field("sandbox_prefix", &self.sandbox_prefix)
+            .field("read_your_writes", &self.read_your_writes)
+            .field("limits", &self.limits)
+            .field("priority", &self.priority)
+            .field("retry", &self.retry)
+            .field("circuit", &self.circuit)
+            .field("settings", &self.settings.as_ref().map(|s| s.ttl()))
+            .field("etags", &self.etags.as_ref().map(|e| e.capacity()))
+            .field("confirmation", &self.confirmation.is_some())
+            .finish()
+    }
+}
+impl fmt::Display for Challonge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(
+            f,
+            "Challonge client for user {} at {}",
+            self.user_name, self.base_url
+        ));
+        if let Some(subdomain) = self.subdomain.as_ref() {
+            try!(write!(f, " (subdomain {})", subdomain));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    extern crate serde_json;
+
+    use chrono::{Duration, TimeZone, UTC};
+    use clock::ManualClock;
+    use error::Error;
+    use std::time::Duration as StdDuration;
+    use tournament::TournamentId;
+    use transport::{BodyEncoding, Method, Normalization, Request, Response, Transport};
+    use url;
+    use {
+        check_unmodified, cleanup, index_url, permission_context, Challonge, ChallongeDateTime,
+        MatchIndexOptions, MatchState, Participant, ParticipantId, ParticipantIndexOptions,
+        ParticipantUpdate, TournamentIndexOptions, TournamentType, API_BASE, MAX_URL_LENGTH,
+    };
+
+    #[test]
+    fn test_index_options() {
+        let base = "https://api.challonge.com/v1/tournaments.json";
+        let mut options = TournamentIndexOptions::new();
+        assert_eq!(
+            index_url(base, &options).as_str(),
+            format!("{}?state=all", base)
+        );
+        options
+            .tournament_type(TournamentType::RoundRobin)
+            .subdomain("mysub");
+        assert_eq!(
+            index_url(base, &options).query(),
+            Some("state=all&type=round_robin&subdomain=mysub")
+        );
+
+        let mut options = MatchIndexOptions::new();
+        assert_eq!(index_url(base, &options).as_str(), base);
+        options
+            .state(MatchState::Open)
+            .participant_id(ParticipantId(16543993));
+        assert_eq!(
+            index_url(base, &options).query(),
+            Some("state=open&participant_id=16543993")
+        );
+        assert_eq!(
+            index_url(base, &ParticipantIndexOptions::new()).as_str(),
+            base
+        );
+    }
+
     #[test]
     fn it_works() {}
+
+    #[test]
+    fn test_subdomain_scoping() {
+        let url = TournamentId::Url(String::new(), "weekly1".to_owned());
+        let scoped = TournamentId::Url("other".to_owned(), "weekly1".to_owned());
+
+        let c = Challonge::builder()
+            .credentials("user", "key")
+            .build()
+            .unwrap();
+        assert_eq!(c.tournament_path(&url), "weekly1");
+
+        let c = c.with_subdomain("mysub");
+        assert_eq!(c.tournament_path(&url), "mysub-weekly1");
+        assert_eq!(c.tournament_path(&scoped), "other-weekly1");
+        assert_eq!(c.tournament_path(&TournamentId::Id(5)), "5");
+    }
+
+    #[test]
+    fn test_url_length_guard() {
+        let c = Challonge::builder()
+            .credentials("user", "key")
+            .build()
+            .unwrap();
+        let long = format!(
+            "{}/tournaments/1.json?misc={}",
+            API_BASE,
+            "x".repeat(MAX_URL_LENGTH)
+        );
+        match c.prepare_request(Method::Get, &long, None) {
+            Err(Error::UrlTooLong(len)) => assert_eq!(len, long.len()),
+            _ => assert!(false),
+        }
+
+        let params = vec![("tournament[name]", "Cup".to_owned())];
+        let r = c.prepare_request(Method::Put, &long, Some(params)).unwrap();
+        assert_eq!(r.url, format!("{}/tournaments/1.json", API_BASE));
+        let body = r.body.unwrap();
+        assert!(body.starts_with("misc=xxx"));
+        assert!(body.ends_with("&tournament%5Bname%5D=Cup"));
+
+        let short = format!("{}/tournaments/1.json?include_matches=1", API_BASE);
+        let r = c.prepare_request(Method::Post, &short, None).unwrap();
+        assert_eq!(r.url, short);
+        assert!(r.body.is_none());
+    }
+
+    #[test]
+    fn test_non_ascii_round_trip() {
+        let names = vec![
+            "Jose\u{301}",
+            "\u{5F20}\u{4F1F}",
+            "Player \u{1F3AE}\u{1F525}",
+            "a&b=c+d %20",
+        ];
+        let c = Challonge::builder()
+            .credentials("user", "key")
+            .build()
+            .unwrap();
+        let url = format!("{}/tournaments/1/participants.json", API_BASE);
+        for name in names {
+            let params = vec![(p!("name"), name.to_owned())];
+            let r = c
+                .prepare_request(Method::Post, &url, Some(params.clone()))
+                .unwrap();
+            let body = r.body.unwrap();
+            assert!(body.is_ascii());
+            let decoded: Vec<(String, String)> = url::form_urlencoded::parse(body.as_bytes())
+                .into_owned()
+                .collect();
+            assert_eq!(decoded.len(), 1);
+            assert_eq!(decoded[0].0, "participant[name]");
+            assert_eq!(decoded[0].1, Normalization::Nfc.apply(name));
+
+            let c = c.clone().with_body_encoding(BodyEncoding::Json);
+            let r = c.prepare_request(Method::Post, &url, Some(params)).unwrap();
+            let json: serde_json::Value = serde_json::from_str(&r.body.unwrap()).unwrap();
+            let value = json.as_object().unwrap().get("participant").unwrap();
+            let value = value.as_object().unwrap().get("name").unwrap().as_string();
+            assert_eq!(value, Some(&Normalization::Nfc.apply(name)[..]));
+        }
+    }
+
+    #[test]
+    fn test_permission_context() {
+        let denied = || Error::InsufficientPermissions {
+            subdomain: None,
+            tournament: None,
+        };
+        let r = Request::new(
+            Method::Put,
+            format!("{}/tournaments/mysub-weekly1.json", API_BASE),
+        );
+        match permission_context(denied(), &r) {
+            Error::InsufficientPermissions {
+                subdomain,
+                tournament,
+            } => {
+                assert_eq!(subdomain, Some("mysub".to_owned()));
+                assert_eq!(
+                    tournament,
+                    Some(TournamentId::Url("mysub".to_owned(), "weekly1".to_owned()))
+                );
+            }
+            _ => assert!(false),
+        }
+
+        let mut r = Request::new(Method::Post, format!("{}/tournaments.json", API_BASE));
+        r.body = Some("tournament%5Bname%5D=Cup&tournament%5Bsubdomain%5D=other".to_owned());
+        match permission_context(denied(), &r) {
+            Error::InsufficientPermissions {
+                subdomain,
+                tournament,
+            } => {
+                assert_eq!(subdomain, Some("other".to_owned()));
+                assert_eq!(tournament, None);
+            }
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_sandbox_guard() {
+        let c = Challonge::builder()
+            .credentials("user", "key")
+            .build()
+            .unwrap();
+        assert!(c
+            .sandbox_guard(&TournamentId::Url(String::new(), "weekly1".to_owned()))
+            .is_ok());
+
+        let c = c.with_sandbox_prefix("test_");
+        let real = TournamentId::Url("mysub".to_owned(), "weekly1".to_owned());
+        match c.sandbox_guard(&real) {
+            Err(Error::SandboxViolation(id)) => assert_eq!(id, real),
+            _ => assert!(false),
+        }
+        assert!(c
+            .sandbox_guard(&TournamentId::Url(String::new(), "test_weekly1".to_owned()))
+            .is_ok());
+        match c.delete_tournament(&real) {
+            Err(Error::SandboxViolation(_)) => {}
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_confirmation() {
+        let c = Challonge::builder()
+            .credentials("user", "key")
+            .confirmation(|action| action.starts_with("Reset"))
+            .build()
+            .unwrap();
+        let id = TournamentId::Url("mysub".to_owned(), "weekly1".to_owned());
+        match c.delete_tournament(&id) {
+            Err(Error::Cancelled(action)) => {
+                assert_eq!(action, "Delete tournament mysub-weekly1")
+            }
+            _ => assert!(false),
+        }
+        match c.clear_participants(&id) {
+            Err(Error::Cancelled(_)) => {}
+            _ => assert!(false),
+        }
+        assert!(c.confirm("Reset tournament mysub-weekly1").is_ok());
+    }
+
+    struct Deleted;
+
+    impl Transport for Deleted {
+        fn send(&self, request: &Request) -> Result<Response, Error> {
+            assert_eq!(request.method, Method::Delete);
+            assert!(request.url.ends_with("/tournaments/1086875.json"));
+            Ok(Response {
+                status: 200,
+                headers: Vec::new(),
+                body: include_bytes!("../benches/fixtures/tournament.json").to_vec(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_delete_tournament() {
+        let c = Challonge::builder()
+            .credentials("user", "key")
+            .transport(Deleted)
+            .confirmation(|action| action == "Delete tournament 1086875")
+            .build()
+            .unwrap();
+        let t = c.delete_tournament(&TournamentId::Id(1086875)).unwrap();
+        assert_eq!(t.id, TournamentId::Id(1086875));
+
+        // nothing is sent once the deadline has passed
+        match c
+            .deadline(StdDuration::from_secs(0))
+            .delete_tournament(&TournamentId::Id(1086875))
+        {
+            Err(Error::Timeout) => {}
+            _ => assert!(false),
+        }
+
+        // the deadline is measured on the clock of the client
+        let c = c.with_clock(ManualClock::new(UTC.ymd(2016, 4, 1).and_hms(12, 0, 0)));
+        let bounded = c.deadline(StdDuration::from_secs(10));
+        assert!(bounded
+            .delete_tournament(&TournamentId::Id(1086875))
+            .is_ok());
+        c.clock().sleep(StdDuration::from_secs(10));
+        match bounded.delete_tournament(&TournamentId::Id(1086875)) {
+            Err(Error::Timeout) => {}
+            _ => assert!(false),
+        }
+    }
+
+    struct Junk;
+
+    impl Transport for Junk {
+        fn send(&self, request: &Request) -> Result<Response, Error> {
+            let tournament = include_str!("../benches/fixtures/tournament.json");
+            let body = match request.method {
+                Method::Get => format!("[{}]", tournament),
+                Method::Delete => tournament.to_owned(),
+                _ => panic!("unexpected {:?} {}", request.method, request.url),
+            };
+            Ok(Response {
+                status: 200,
+                headers: Vec::new(),
+                body: body.into_bytes(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_cleanup_confirmation() {
+        let c = Challonge::builder()
+            .credentials("user", "key")
+            .transport(Junk)
+            .build()
+            .unwrap();
+        let report = cleanup::delete_matching(&c, "sample_", Duration::days(1), |_| true).unwrap();
+        assert_eq!(report.deleted, vec![TournamentId::Id(1086875)]);
+
+        // the client hook declining a deletion is not a failure
+        let c = c.with_confirmation(|_| false);
+        let report = cleanup::delete_matching(&c, "sample_", Duration::days(1), |_| true).unwrap();
+        assert!(report.deleted.is_empty());
+        assert!(report.failed.is_empty());
+        assert_eq!(report.declined, vec![TournamentId::Id(1086875)]);
+    }
+
+    struct Underway;
+
+    impl Transport for Underway {
+        fn send(&self, request: &Request) -> Result<Response, Error> {
+            assert_eq!(request.method, Method::Get);
+            Ok(Response {
+                status: 200,
+                headers: Vec::new(),
+                body: include_bytes!("../benches/fixtures/tournament.json").to_vec(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_resend_invitation_guard() {
+        let c = Challonge::builder()
+            .credentials("user", "key")
+            .transport(Underway)
+            .build()
+            .unwrap();
+        let value = serde_json::from_str(include_str!("../benches/fixtures/participant.json"));
+        let p = Participant::decode(&value.unwrap()).unwrap();
+        match c.resend_invitation(&TournamentId::Id(1086875), &p) {
+            Err(Error::Api(_)) => {}
+            _ => assert!(false),
+        }
+    }
+
+    struct Updated;
+
+    impl Transport for Updated {
+        fn send(&self, request: &Request) -> Result<Response, Error> {
+            assert_eq!(request.method, Method::Put);
+            if request.url.ends_with("/participants/2.json") {
+                return Ok(Response {
+                    status: 422,
+                    headers: Vec::new(),
+                    body: br#"{"errors": ["Seed is invalid"]}"#.to_vec(),
+                });
+            }
+            Ok(Response {
+                status: 200,
+                headers: Vec::new(),
+                body: include_bytes!("../benches/fixtures/participant.json").to_vec(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_update_participants() {
+        let start = UTC.ymd(2016, 4, 1).and_hms(12, 0, 0);
+        let c = Challonge::builder()
+            .credentials("user", "key")
+            .transport(Updated)
+            .build()
+            .unwrap()
+            .with_clock(ManualClock::new(start));
+        let updates: Vec<_> = (1..6)
+            .map(|i| (ParticipantId(i), ParticipantUpdate::new().seed(i).clone()))
+            .collect();
+        let report = c.update_participants(&TournamentId::Id(1086875), &updates);
+        assert!(!report.is_ok());
+        assert_eq!(
+            report.succeeded,
+            vec![
+                ParticipantId(1),
+                ParticipantId(3),
+                ParticipantId(4),
+                ParticipantId(5)
+            ]
+        );
+        assert_eq!(report.failed.len(), 1);
+        match report.failed[0] {
+            (ParticipantId(2), Error::Status(422, _, _)) => {}
+            _ => assert!(false),
+        }
+        // two batches, paced since the client has no rate limiter
+        assert_eq!(c.clock().now(), start + Duration::milliseconds(250));
+    }
+
+    #[test]
+    fn test_precondition() {
+        let seen: ChallongeDateTime = "2015-01-19T16:57:17-05:00".parse().unwrap();
+        assert!(check_unmodified(&seen, &seen).is_ok());
+        let same_instant: ChallongeDateTime = "2015-01-19T21:57:17Z".parse().unwrap();
+        assert!(check_unmodified(&seen, &same_instant).is_ok());
+        let later: ChallongeDateTime = "2015-01-19T16:58:00-05:00".parse().unwrap();
+        match check_unmodified(&seen, &later) {
+            Err(Error::Conflict { current, .. }) => assert_eq!(current, later),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_client_redaction() {
+        let c = Challonge::builder()
+            .credentials("user", "secretkey")
+            .build()
+            .unwrap()
+            .with_subdomain("mysub");
+        let debug = format!("{:?}", c);
+        assert!(debug.contains("user"));
+        assert!(!debug.contains("secretkey"));
+        assert_eq!(
+            c.to_string(),
+            "Challonge client for user user at https://api.challonge.com/v1 (subdomain mysub)"
+        );
+    }
 }
@@ -0,0 +1,105 @@
+//! Limits on responses accepted before decoding.
+//!
+//! A misbehaving proxy or a pathological payload must not balloon the memory of a long-running
+//! watcher, so the client refuses bodies which are too large or nest json too deeply.
+
+use error::Error;
+
+/// Default largest accepted response body, 32 MiB.
+pub const DEFAULT_MAX_BODY_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Default deepest accepted json nesting.
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// Limits on response payloads.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResponseLimits {
+    /// Largest accepted body in bytes
+    pub max_body_bytes: u64,
+
+    /// Deepest accepted nesting of json arrays and objects
+    pub max_depth: usize,
+}
+impl ResponseLimits {
+    /// Creates the default limits.
+    pub fn new() -> ResponseLimits {
+        ResponseLimits {
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    builder!(max_body_bytes, u64);
+    builder!(max_depth, usize);
+
+    /// Checks a response body against the limits without decoding it.
+    pub fn check(&self, body: &[u8]) -> Result<(), Error> {
+        if body.len() as u64 > self.max_body_bytes {
+            return Err(Error::ResponseTooLarge(self.max_body_bytes));
+        }
+        if json_depth(body) > self.max_depth {
+            return Err(Error::JsonTooDeep(self.max_depth));
+        }
+        Ok(())
+    }
+}
+impl Default for ResponseLimits {
+    fn default() -> ResponseLimits {
+        ResponseLimits::new()
+    }
+}
+
+/// Returns the deepest nesting of arrays and objects in a json text, brackets in strings are ignored.
+pub fn json_depth(body: &[u8]) -> usize {
+    let mut depth = 0usize;
+    let mut deepest = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for &b in body {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > deepest {
+                    deepest = depth;
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    deepest
+}
+
+#[cfg(test)]
+mod tests {
+    use error::Error;
+    use limits::{json_depth, ResponseLimits};
+
+    #[test]
+    fn test_limits() {
+        assert_eq!(json_depth(b"1"), 0);
+        assert_eq!(json_depth(br#"{"a": [1, {"b": "[[[\"{"}]}"#), 3);
+
+        let mut limits = ResponseLimits::new();
+        limits.max_body_bytes(16).max_depth(2);
+        assert!(limits.check(br#"{"a": [1]}"#).is_ok());
+        match limits.check(br#"{"a": [[1]]}"#) {
+            Err(Error::JsonTooDeep(2)) => {}
+            _ => assert!(false),
+        }
+        match limits.check(br#"{"name": "a long name"}"#) {
+            Err(Error::ResponseTooLarge(16)) => {}
+            _ => assert!(false),
+        }
+    }
+}
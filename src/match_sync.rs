@@ -0,0 +1,146 @@
+//! Incremental synchronization of matches.
+//!
+//! `MatchSync` keeps the latest `updated_at` seen per tournament and returns only the matches
+//! changed since the previous sync. The API has no server-side filter for matches, so the full
+//! match list is still fetched, but consumers only process the delta, which keeps frequent polls
+//! of big round robins cheap. Cursors can be saved and restored to resume after a restart.
+
+use std::collections::{BTreeSet, HashMap};
+
+use datetime::ChallongeDateTime;
+use error::Error;
+use matches::Match;
+use tournament::TournamentId;
+use Challonge;
+
+/// Latest change seen in a tournament.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncCursor {
+    /// Latest `updated_at` of the seen matches
+    pub updated_at: ChallongeDateTime,
+
+    /// Ids of the seen matches updated exactly at `updated_at`
+    pub match_ids: BTreeSet<u64>,
+}
+impl SyncCursor {
+    fn is_new(&self, m: &Match) -> bool {
+        m.updated_at > self.updated_at
+            || (m.updated_at == self.updated_at && !self.match_ids.contains(&m.id.0))
+    }
+}
+
+/// Per-tournament cursors of incremental match syncs.
+#[derive(Debug, Clone, Default)]
+pub struct MatchSync {
+    cursors: HashMap<String, SyncCursor>,
+}
+impl MatchSync {
+    /// Creates a sync without cursors, the first sync of a tournament returns all its matches.
+    pub fn new() -> MatchSync {
+        MatchSync::default()
+    }
+
+    /// Returns the cursor of a tournament.
+    pub fn cursor(&self, id: &TournamentId) -> Option<&SyncCursor> {
+        self.cursors.get(&id.to_string())
+    }
+
+    /// Restores a previously saved cursor of a tournament.
+    pub fn set_cursor(&mut self, id: &TournamentId, cursor: SyncCursor) {
+        self.cursors.insert(id.to_string(), cursor);
+    }
+
+    /// Forgets the cursor of a tournament, the next sync returns all its matches.
+    pub fn reset(&mut self, id: &TournamentId) {
+        self.cursors.remove(&id.to_string());
+    }
+
+    /// Returns the matches changed since the cursor of the tournament, oldest change first,
+    /// and moves the cursor past them.
+    pub fn delta(&mut self, id: &TournamentId, matches: Vec<Match>) -> Vec<Match> {
+        let key = id.to_string();
+        let mut changed: Vec<Match> = match self.cursors.get(&key) {
+            Some(cursor) => matches.into_iter().filter(|m| cursor.is_new(m)).collect(),
+            None => matches,
+        };
+        changed.sort_by(|a, b| a.updated_at.cmp(&b.updated_at));
+
+        if let Some(latest) = changed.last().map(|m| m.updated_at) {
+            let cursor = self.cursors.entry(key).or_insert_with(|| SyncCursor {
+                updated_at: latest,
+                match_ids: BTreeSet::new(),
+            });
+            if latest > cursor.updated_at {
+                cursor.updated_at = latest;
+                cursor.match_ids.clear();
+            }
+            cursor.match_ids.extend(
+                changed
+                    .iter()
+                    .filter(|m| m.updated_at == latest)
+                    .map(|m| m.id.0),
+            );
+        }
+        changed
+    }
+
+    /// Fetches the matches of a tournament and returns the ones changed since the previous sync.
+    pub fn sync(&mut self, client: &Challonge, id: &TournamentId) -> Result<Vec<Match>, Error> {
+        let matches = try!(client.match_index(id, None, None));
+        Ok(self.delta(id, matches.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use chrono::*;
+    use match_sync::MatchSync;
+    use matches::{Match, MatchId};
+    use tournament::TournamentId;
+
+    const MATCH: &'static str = r#"{"match": {"attachment_count": null, "created_at": "2015-01-19T16:57:17-05:00",
+        "has_attachment": false, "id": 1, "identifier": "A", "loser_id": null,
+        "player1_id": 16543993, "player1_is_prereq_match_loser": false, "player1_prereq_match_id": null,
+        "player1_votes": null, "player2_id": 16543997, "player2_is_prereq_match_loser": false,
+        "player2_prereq_match_id": null, "player2_votes": null, "round": 1, "started_at": null,
+        "state": "open", "tournament_id": 1086875, "updated_at": "2015-01-19T16:57:17-05:00",
+        "winner_id": null, "prerequisite_match_ids_csv": "", "scores_csv": ""}}"#;
+
+    fn match_at(id: u64, minutes: i64) -> Match {
+        let mut m = Match::decode(&serde_json::from_str(MATCH).unwrap()).unwrap();
+        m.id = MatchId(id);
+        m.updated_at = (m.updated_at.0 + Duration::minutes(minutes)).into();
+        m
+    }
+
+    #[test]
+    fn test_delta() {
+        let id = TournamentId::Id(1086875);
+        let mut sync = MatchSync::new();
+        assert_eq!(
+            sync.delta(&id, vec![match_at(1, 0), match_at(2, 0)]).len(),
+            2
+        );
+        assert!(sync
+            .delta(&id, vec![match_at(1, 0), match_at(2, 0)])
+            .is_empty());
+
+        // a match updated within the same second as the cursor is still picked up
+        let delta = sync.delta(&id, vec![match_at(1, 0), match_at(2, 0), match_at(3, 0)]);
+        assert_eq!(delta.len(), 1);
+        assert_eq!(delta[0].id, MatchId(3));
+
+        let delta = sync.delta(&id, vec![match_at(1, 5), match_at(2, 0), match_at(3, 0)]);
+        assert_eq!(delta.len(), 1);
+        assert_eq!(delta[0].id, MatchId(1));
+        assert_eq!(
+            sync.cursor(&id).unwrap().updated_at,
+            match_at(1, 5).updated_at
+        );
+
+        sync.reset(&id);
+        assert_eq!(sync.delta(&id, vec![match_at(1, 5)]).len(), 1);
+    }
+}
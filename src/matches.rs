@@ -2,16 +2,20 @@
 
 extern crate serde_json;
 
-use chrono::*;
+use chrono::{DateTime, UTC};
 use serde_json::Value;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
+use std::num::NonZeroU64;
 use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration as StdDuration;
 
+use datetime::ChallongeDateTime;
 use error::Error;
 use participants::ParticipantId;
 use tournament::TournamentId;
-use util::{decode_array, into_map, remove};
+use util::{as_map, decode_array, enum_key, field};
 
 /// Represents a pair of scores - for player 1 and player 2 respectively.
 #[derive(Debug, Clone, PartialEq)]
@@ -52,6 +56,9 @@ impl MatchScores {
         let mut scores = Vec::new();
         let mut iter = string.split(",");
         while let Some(s) = iter.next() {
+            if s.trim().is_empty() {
+                continue;
+            }
             if let Ok(ms) = MatchScore::decode(s.trim()) {
                 scores.push(ms);
             }
@@ -112,8 +119,9 @@ impl fmt::Display for MatchState {
 }
 impl FromStr for MatchState {
     type Err = ();
+    /// Parses a match state ignoring case and separators.
     fn from_str(s: &str) -> Result<MatchState, ()> {
-        match s {
+        match &enum_key(s)[..] {
             "all" => return Ok(MatchState::All),
             "pending" => return Ok(MatchState::Pending),
             "open" => return Ok(MatchState::Open),
@@ -123,15 +131,55 @@ impl FromStr for MatchState {
     }
 }
 
+/// Filters of `Challonge::match_index_with`. Unset filters are not sent.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct MatchIndexOptions {
+    /// State of the matches
+    pub state: Option<MatchState>,
+
+    /// Only matches of the participant
+    pub participant_id: Option<ParticipantId>,
+}
+impl MatchIndexOptions {
+    /// Creates options requesting all matches.
+    pub fn new() -> MatchIndexOptions {
+        MatchIndexOptions::default()
+    }
+
+    builder_o!(state, MatchState);
+    builder_o!(participant_id, ParticipantId);
+}
+
 /// A list of matches of the tournament.
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct Index(pub Vec<Match>);
 impl Index {
     /// Decodes match index from JSON.
-    pub fn decode(value: Value) -> Result<Index, Error> {
+    pub fn decode(value: &Value) -> Result<Index, Error> {
         Ok(Index(try!(decode_array(value, Match::decode))))
     }
+
+    /// Returns bye "matches" of the tournament.
+    pub fn byes(&self) -> Vec<&Match> {
+        self.0.iter().filter(|m| m.is_bye()).collect()
+    }
+
+    /// Returns matches which are actually played, i.e. all matches except byes.
+    /// Standings and statistics should be computed from these.
+    /// Forfeits of deactivated participants are included, they count as losses without games.
+    pub fn played(&self) -> Vec<&Match> {
+        self.0.iter().filter(|m| !m.is_bye()).collect()
+    }
+
+    /// Returns matches forfeited by the `inactive` participants, see `Match::forfeited_by`.
+    pub fn forfeits(&self, inactive: &[ParticipantId]) -> Vec<&Match> {
+        self.0
+            .iter()
+            .filter(|m| m.forfeited_by(inactive).is_some())
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -165,13 +213,54 @@ impl MatchUpdate {
     builder_o!(winner_id, ParticipantId);
     builder_o!(player1_votes, u64);
     builder_o!(player2_votes, u64);
+
+    /// Returns an idempotency key of the update of a match, e.g. `"12:3-1,2-3:100"`.
+    /// Identical updates of the same match have the same key.
+    pub fn idempotency_key(&self, match_id: &MatchId) -> String {
+        format!(
+            "{}:{}:{}:{}:{}",
+            match_id.0,
+            self.scores_csv,
+            self.winner_id.as_ref().map_or(0, |w| w.0),
+            self.player1_votes.unwrap_or(0),
+            self.player2_votes.unwrap_or(0)
+        )
+    }
+}
+
+/// Acknowledged match updates by idempotency key, shared between clones of a client.
+#[derive(Debug, Default)]
+pub struct SubmissionCache {
+    submissions: Mutex<HashMap<String, (DateTime<UTC>, Match)>>,
+}
+impl SubmissionCache {
+    /// Creates an empty cache.
+    pub fn new() -> SubmissionCache {
+        SubmissionCache::default()
+    }
+
+    /// Returns the match acknowledged for `key` less than `window` before `now`.
+    /// Older submissions are forgotten.
+    pub fn get(&self, key: &str, now: &DateTime<UTC>, window: StdDuration) -> Option<Match> {
+        let mut submissions = self.submissions.lock().unwrap();
+        submissions.retain(|_, &mut (at, _)| (*now - at).to_std().map_or(true, |age| age < window));
+        submissions.get(key).map(|&(_, ref m)| m.clone())
+    }
+
+    /// Remembers an acknowledged update.
+    pub fn insert(&self, key: &str, now: DateTime<UTC>, m: Match) {
+        self.submissions
+            .lock()
+            .unwrap()
+            .insert(key.to_owned(), (now, m));
+    }
 }
 
 /// Player data in match.
 #[derive(Debug, Clone)]
 pub struct Player {
-    /// Unique participant identifier
-    pub id: ParticipantId,
+    /// Unique participant identifier, `None` if the player is not known yet
+    pub id: Option<ParticipantId>,
     /// ???
     pub is_prereq_match_loser: bool,
     /// ???
@@ -181,35 +270,51 @@ pub struct Player {
 }
 impl Player {
     /// Decodes `Player` from JSON
-    pub fn decode(mut map: &mut BTreeMap<String, Value>, prefix: &str) -> Result<Player, Error> {
+    pub fn decode(map: &BTreeMap<String, Value>, prefix: &str) -> Result<Player, Error> {
         Ok(Player {
-            id: ParticipantId(
-                try!(remove(&mut map, &format!("{}id", prefix)))
-                    .as_u64()
-                    .unwrap_or(0),
-            ),
-            is_prereq_match_loser: try!(remove(
-                &mut map,
-                &format!("{}is_prereq_match_loser", prefix)
-            ))
-            .as_boolean()
-            .unwrap_or(false),
-            prereq_match_id: try!(remove(&mut map, &format!("{}prereq_match_id", prefix)))
+            id: try!(field(map, &format!("{}id", prefix)))
+                .as_u64()
+                .map(ParticipantId),
+            is_prereq_match_loser: try!(field(map, &format!("{}is_prereq_match_loser", prefix)))
+                .as_boolean()
+                .unwrap_or(false),
+            prereq_match_id: try!(field(map, &format!("{}prereq_match_id", prefix)))
                 .as_u64()
                 .map_or(None, |i| Some(MatchId(i))),
-            votes: try!(remove(&mut map, &format!("{}votes", prefix)))
+            votes: try!(field(map, &format!("{}votes", prefix)))
                 .as_u64()
                 .unwrap_or(0),
         })
     }
+
+    /// Encodes `Player` into a JSON object in the API format.
+    pub fn encode(&self, map: &mut BTreeMap<String, Value>, prefix: &str) {
+        map.insert(
+            format!("{}id", prefix),
+            self.id.as_ref().map_or(Value::Null, |id| Value::U64(id.0)),
+        );
+        map.insert(
+            format!("{}is_prereq_match_loser", prefix),
+            Value::Bool(self.is_prereq_match_loser),
+        );
+        map.insert(
+            format!("{}prereq_match_id", prefix),
+            self.prereq_match_id
+                .as_ref()
+                .map_or(Value::Null, |id| Value::U64(id.0)),
+        );
+        map.insert(format!("{}votes", prefix), Value::U64(self.votes));
+    }
 }
 
 /// Challonge `Match` definition.
 #[derive(Debug, Clone)]
 pub struct Match {
-    // attachment_count: ,
+    /// Number of attachments of the match.
+    pub attachment_count: Option<u64>,
+
     /// Holds a time when match was created.
-    pub created_at: DateTime<FixedOffset>,
+    pub created_at: ChallongeDateTime,
     // group_id: ,
     /// Does the match has an attachment?
     pub has_attachment: bool,
@@ -233,7 +338,7 @@ pub struct Match {
     pub round: u64,
     // // // scheduled_time:
     /// Holds a time when match was started.
-    pub started_at: Option<DateTime<FixedOffset>>,
+    pub started_at: Option<ChallongeDateTime>,
 
     /// State of the match.
     pub state: MatchState,
@@ -242,7 +347,7 @@ pub struct Match {
     pub tournament_id: TournamentId,
     // // underway_at:
     /// A time when match was updated last time.
-    pub updated_at: DateTime<FixedOffset>,
+    pub updated_at: ChallongeDateTime,
 
     /// An id of user which won the match
     pub winner_id: Option<ParticipantId>,
@@ -255,72 +360,182 @@ pub struct Match {
 }
 impl Match {
     /// Decodes `Match` from JSON
-    pub fn decode(value: Value) -> Result<Match, Error> {
-        let mut value = try!(into_map(value));
-        let t = try!(remove(&mut value, "match"));
-        let mut tv = try!(into_map(t));
-
-        let mut started_at = None;
-        if let Some(sa_str) = try!(remove(&mut tv, "started_at")).as_string() {
-            if let Ok(sa) = DateTime::parse_from_rfc3339(sa_str) {
-                started_at = Some(sa);
-            }
-        }
+    pub fn decode(value: &Value) -> Result<Match, Error> {
+        let value = try!(as_map(value));
+        let t = try!(field(value, "match"));
+        let tv = try!(as_map(t));
 
         Ok(Match {
-            created_at: DateTime::parse_from_rfc3339(
-                try!(remove(&mut tv, "created_at"))
-                    .as_string()
-                    .unwrap_or(""),
-            )
-            .unwrap(),
-            has_attachment: try!(remove(&mut tv, "has_attachment"))
+            attachment_count: try!(field(tv, "attachment_count")).as_u64(),
+            created_at: try!(ChallongeDateTime::decode_required(try!(field(
+                tv,
+                "created_at"
+            )))),
+            has_attachment: try!(field(tv, "has_attachment"))
                 .as_boolean()
                 .unwrap_or(false),
-            id: MatchId(try!(remove(&mut tv, "id")).as_u64().unwrap()),
-            identifier: try!(remove(&mut tv, "identifier"))
+            id: MatchId(try!(field(tv, "id")).as_u64().unwrap()),
+            identifier: try!(field(tv, "identifier"))
                 .as_string()
                 .unwrap_or("")
                 .to_owned(),
-            loser_id: try!(remove(&mut tv, "loser_id"))
+            loser_id: try!(field(tv, "loser_id"))
                 .as_u64()
                 .map_or(None, |i| Some(ParticipantId(i))),
-            player1: Player::decode(&mut tv, "player1_").unwrap(),
-            player2: Player::decode(&mut tv, "player2_").unwrap(),
-            round: try!(remove(&mut tv, "round")).as_u64().unwrap(),
-            started_at: started_at,
-            state: MatchState::from_str(try!(remove(&mut tv, "state")).as_string().unwrap_or(""))
+            player1: Player::decode(tv, "player1_").unwrap(),
+            player2: Player::decode(tv, "player2_").unwrap(),
+            round: try!(field(tv, "round")).as_u64().unwrap(),
+            started_at: try!(ChallongeDateTime::decode(try!(field(tv, "started_at")))),
+            state: MatchState::from_str(try!(field(tv, "state")).as_string().unwrap_or(""))
                 .unwrap_or(MatchState::All),
-            tournament_id: TournamentId::Id(
-                try!(remove(&mut tv, "tournament_id")).as_u64().unwrap(),
-            ),
-            updated_at: DateTime::parse_from_rfc3339(
-                try!(remove(&mut tv, "updated_at"))
-                    .as_string()
-                    .unwrap_or(""),
-            )
-            .unwrap(),
-            winner_id: try!(remove(&mut tv, "winner_id"))
+            tournament_id: TournamentId::Id(try!(field(tv, "tournament_id")).as_u64().unwrap()),
+            updated_at: try!(ChallongeDateTime::decode_required(try!(field(
+                tv,
+                "updated_at"
+            )))),
+            winner_id: try!(field(tv, "winner_id"))
                 .as_u64()
                 .map_or(None, |i| Some(ParticipantId(i))),
-            prerequisite_match_ids_csv: try!(remove(&mut tv, "prerequisite_match_ids_csv"))
+            prerequisite_match_ids_csv: try!(field(tv, "prerequisite_match_ids_csv"))
                 .as_string()
                 .unwrap_or("")
                 .to_owned(),
             scores_csv: MatchScores::decode(
-                try!(remove(&mut tv, "scores_csv"))
+                try!(field(tv, "scores_csv"))
                     .as_string()
                     .unwrap_or("")
                     .to_owned(),
             ),
         })
     }
+
+    /// Encodes the match into JSON in the API format, `Match::decode` reads it back.
+    pub fn encode(&self) -> Value {
+        fn id(v: Option<&ParticipantId>) -> Value {
+            v.map_or(Value::Null, |p| Value::U64(p.0))
+        }
+
+        let mut m = BTreeMap::new();
+        m.insert(
+            "attachment_count".to_owned(),
+            self.attachment_count.map_or(Value::Null, Value::U64),
+        );
+        m.insert(
+            "created_at".to_owned(),
+            Value::String(self.created_at.to_string()),
+        );
+        m.insert(
+            "has_attachment".to_owned(),
+            Value::Bool(self.has_attachment),
+        );
+        m.insert("id".to_owned(), Value::U64(self.id.0));
+        m.insert(
+            "identifier".to_owned(),
+            Value::String(self.identifier.clone()),
+        );
+        m.insert("loser_id".to_owned(), id(self.loser_id.as_ref()));
+        self.player1.encode(&mut m, "player1_");
+        self.player2.encode(&mut m, "player2_");
+        m.insert("round".to_owned(), Value::U64(self.round));
+        m.insert(
+            "started_at".to_owned(),
+            self.started_at
+                .map_or(Value::Null, |d| Value::String(d.to_string())),
+        );
+        m.insert("state".to_owned(), Value::String(self.state.to_string()));
+        m.insert(
+            "tournament_id".to_owned(),
+            match self.tournament_id {
+                TournamentId::Id(id) => Value::U64(id),
+                ref url => Value::String(url.to_string()),
+            },
+        );
+        m.insert(
+            "updated_at".to_owned(),
+            Value::String(self.updated_at.to_string()),
+        );
+        m.insert("winner_id".to_owned(), id(self.winner_id.as_ref()));
+        m.insert(
+            "prerequisite_match_ids_csv".to_owned(),
+            Value::String(self.prerequisite_match_ids_csv.clone()),
+        );
+        m.insert(
+            "scores_csv".to_owned(),
+            Value::String(self.scores_csv.to_string()),
+        );
+
+        let mut root = BTreeMap::new();
+        root.insert("match".to_owned(), Value::Object(m));
+        Value::Object(root)
+    }
+
+    /// Returns the round number, `None` if the API returned no valid round.
+    pub fn round_number(&self) -> Option<NonZeroU64> {
+        NonZeroU64::new(self.round)
+    }
+
+    /// Returns `true` if the match is a bye: only one player is known
+    /// and there is no prerequisite match which could provide the other one.
+    pub fn is_bye(&self) -> bool {
+        match (&self.player1.id, &self.player2.id) {
+            (&Some(_), &None) => self.player2.prereq_match_id.is_none(),
+            (&None, &Some(_)) => self.player1.prereq_match_id.is_none(),
+            _ => false,
+        }
+    }
+
+    /// Returns the participant which advances without playing if the match is a bye.
+    pub fn bye_participant(&self) -> Option<ParticipantId> {
+        if self.is_bye() {
+            self.player1.id.clone().or(self.player2.id.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the participant who forfeited the match.
+    /// Challonge completes the remaining matches of a participant deactivated while the tournament
+    /// is underway as losses without scores, these are told apart from played matches by
+    /// the `inactive` participants.
+    pub fn forfeited_by(&self, inactive: &[ParticipantId]) -> Option<ParticipantId> {
+        if self.state != MatchState::Complete || !self.scores_csv.0.is_empty() {
+            return None;
+        }
+        self.loser_id.clone().filter(|id| inactive.contains(id))
+    }
+
+    /// Returns ids of the known participants of the match.
+    pub fn participants(&self) -> Vec<ParticipantId> {
+        self.player1
+            .id
+            .iter()
+            .chain(self.player2.id.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the opponent of the participant in this match.
+    /// Returns `None` if the participant does not play in the match or the opponent is not known yet.
+    pub fn opponent_of(&self, id: &ParticipantId) -> Option<ParticipantId> {
+        if self.player1.id.as_ref() == Some(id) {
+            self.player2.id.clone()
+        } else if self.player2.id.as_ref() == Some(id) {
+            self.player1.id.clone()
+        } else {
+            None
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     extern crate serde_json;
-    use matches::{Match, MatchScore, MatchState};
+    use chrono::{Duration, TimeZone, UTC};
+    use matches::{
+        Index, Match, MatchId, MatchScore, MatchScores, MatchState, MatchUpdate, SubmissionCache,
+    };
+    use participants::ParticipantId;
+    use std::time::Duration as StdDuration;
     use tournament::TournamentId;
 
     #[test]
@@ -347,6 +562,106 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_bye_detection() {
+        let string = r#"{
+          "match": {
+            "attachment_count": null,
+            "created_at": "2015-01-19T16:57:17-05:00",
+            "group_id": null,
+            "has_attachment": false,
+            "id": 23575259,
+            "identifier": "B",
+            "location": null,
+            "loser_id": null,
+            "player1_id": 16543993,
+            "player1_is_prereq_match_loser": false,
+            "player1_prereq_match_id": null,
+            "player1_votes": null,
+            "player2_id": null,
+            "player2_is_prereq_match_loser": false,
+            "player2_prereq_match_id": null,
+            "player2_votes": null,
+            "round": 1,
+            "scheduled_time": null,
+            "started_at": null,
+            "state": "pending",
+            "tournament_id": 1086875,
+            "underway_at": null,
+            "updated_at": "2015-01-19T16:57:17-05:00",
+            "winner_id": null,
+            "prerequisite_match_ids_csv": "",
+            "scores_csv": ""
+          }
+        }"#;
+        let bye = Match::decode(&serde_json::from_str(string).unwrap()).unwrap();
+        assert!(bye.is_bye());
+        assert_eq!(bye.bye_participant(), Some(ParticipantId(16543993)));
+        assert_eq!(bye.opponent_of(&ParticipantId(16543993)), None);
+
+        let mut pending = bye.clone();
+        pending.player2.prereq_match_id = Some(MatchId(23575258));
+        assert!(!pending.is_bye());
+        assert_eq!(pending.bye_participant(), None);
+
+        let mut forfeit = pending.clone();
+        forfeit.player2.id = Some(ParticipantId(16543997));
+        forfeit.state = MatchState::Complete;
+        forfeit.winner_id = Some(ParticipantId(16543993));
+        forfeit.loser_id = Some(ParticipantId(16543997));
+        let inactive = vec![ParticipantId(16543997)];
+        assert_eq!(
+            forfeit.forfeited_by(&inactive),
+            Some(ParticipantId(16543997))
+        );
+        assert_eq!(forfeit.forfeited_by(&[]), None);
+
+        let index = Index(vec![bye, pending, forfeit]);
+        assert_eq!(index.byes().len(), 1);
+        assert_eq!(index.played().len(), 2);
+        assert_eq!(index.played()[0].id, MatchId(23575259));
+        assert_eq!(index.forfeits(&inactive).len(), 1);
+    }
+
+    #[test]
+    fn test_submission_cache() {
+        let m = Match::decode(
+            &serde_json::from_str(
+                r#"{"match": {"attachment_count": null, "created_at": "2015-01-19T16:57:17-05:00",
+                "has_attachment": false, "id": 1, "identifier": "A", "loser_id": 16543997,
+                "player1_id": 16543993, "player1_is_prereq_match_loser": false, "player1_prereq_match_id": null,
+                "player1_votes": null, "player2_id": 16543997, "player2_is_prereq_match_loser": false,
+                "player2_prereq_match_id": null, "player2_votes": null, "round": 1, "started_at": null,
+                "state": "complete", "tournament_id": 1086875, "updated_at": "2015-01-19T16:57:17-05:00",
+                "winner_id": 16543993, "prerequisite_match_ids_csv": "", "scores_csv": "3-1"}}"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let mut update = MatchUpdate::new();
+        update
+            .scores_csv(MatchScores(vec![MatchScore(3, 1)]))
+            .winner_id(ParticipantId(16543993));
+        let key = update.idempotency_key(&MatchId(1));
+        assert_eq!(key, "1:3-1:16543993:0:0");
+
+        let cache = SubmissionCache::new();
+        let window = StdDuration::from_secs(600);
+        let now = UTC.ymd(2015, 1, 19).and_hms(22, 0, 0);
+        assert!(cache.get(&key, &now, window).is_none());
+        cache.insert(&key, now, m);
+        assert_eq!(
+            cache
+                .get(&key, &(now + Duration::minutes(5)), window)
+                .unwrap()
+                .id,
+            MatchId(1)
+        );
+        assert!(cache
+            .get(&key, &(now + Duration::minutes(10)), window)
+            .is_none());
+    }
+
     #[test]
     fn test_participant_parse() {
         let string = r#"{
@@ -382,21 +697,21 @@ mod tests {
         let json_r = serde_json::from_str(string);
         assert!(json_r.is_ok());
         let json = json_r.unwrap();
-        if let Ok(m) = Match::decode(json) {
-            // assert_eq!(m.attachment_count, );
+        if let Ok(m) = Match::decode(&json) {
+            assert_eq!(m.attachment_count, None);
             // assert_eq!(m.created_at, );
             // assert_eq!(m.group_id, );
             assert_eq!(m.has_attachment, false);
             assert_eq!(m.id.0, 23575258);
             assert_eq!(m.identifier, "A");
             assert_eq!(m.loser_id, None);
-            assert_eq!(m.player1.id.0, 16543993);
+            assert_eq!(m.player1.id, Some(ParticipantId(16543993)));
             assert_eq!(m.player1.is_prereq_match_loser, false);
             assert_eq!(m.player1.prereq_match_id, None);
             assert_eq!(m.player1.votes, 0);
             assert_eq!(m.player2.is_prereq_match_loser, false);
             assert_eq!(m.player2.prereq_match_id, None);
-            assert_eq!(m.player2.id.0, 16543997);
+            assert_eq!(m.player2.id, Some(ParticipantId(16543997)));
             assert_eq!(m.player2.votes, 3);
             assert_eq!(m.round, 1);
             // assert_eq!(m.started_at, );
@@ -405,6 +720,16 @@ mod tests {
             // assert_eq!(m.updated_at, );
             assert_eq!(m.winner_id, None);
             assert!(m.prerequisite_match_ids_csv.is_empty());
+            assert!(!m.is_bye());
+            assert_eq!(
+                m.participants(),
+                vec![ParticipantId(16543993), ParticipantId(16543997)]
+            );
+            assert_eq!(
+                m.opponent_of(&ParticipantId(16543993)),
+                Some(ParticipantId(16543997))
+            );
+            assert_eq!(m.opponent_of(&ParticipantId(1)), None);
             {
                 let correct_scores = vec![MatchScore(3, 1), MatchScore(3, 2)];
                 assert_eq!(m.scores_csv.0.len(), 2);
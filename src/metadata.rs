@@ -0,0 +1,111 @@
+//! Structured per-match metadata stored in attachments.
+//!
+//! Challonge has no place for data like map picks, the ruleset or a VOD link of a match.
+//! By convention this crate stores such metadata as JSON in the description of a text attachment
+//! of the match, prefixed with `METADATA_PREFIX` to tell it apart from the attachments of players.
+//! `Challonge::set_match_metadata` writes it and `Challonge::match_metadata` reads it back typed.
+
+use serde_json::{self, Value};
+use std::collections::BTreeMap;
+
+use attachments::Attachment;
+use error::Error;
+use util::as_map;
+
+/// Start of the description of a metadata attachment, followed by the JSON document.
+pub const METADATA_PREFIX: &'static str = "challonge-rs metadata: ";
+
+/// A type stored as match metadata.
+pub trait MatchMetadata: Sized {
+    /// Encodes the metadata into JSON.
+    fn to_json(&self) -> Value;
+
+    /// Decodes the metadata from JSON.
+    fn from_json(value: &Value) -> Result<Self, Error>;
+}
+impl MatchMetadata for Value {
+    fn to_json(&self) -> Value {
+        self.clone()
+    }
+
+    fn from_json(value: &Value) -> Result<Value, Error> {
+        Ok(value.clone())
+    }
+}
+impl MatchMetadata for BTreeMap<String, String> {
+    fn to_json(&self) -> Value {
+        Value::Object(
+            self.iter()
+                .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+                .collect(),
+        )
+    }
+
+    fn from_json(value: &Value) -> Result<BTreeMap<String, String>, Error> {
+        let mut map = BTreeMap::new();
+        for (k, v) in try!(as_map(value)) {
+            match v.as_string() {
+                Some(s) => map.insert(k.clone(), s.to_owned()),
+                None => return Err(Error::Decode("Expected string", v.clone())),
+            };
+        }
+        Ok(map)
+    }
+}
+
+/// Encodes metadata into the description of a metadata attachment.
+pub fn encode<M: MatchMetadata>(metadata: &M) -> Result<String, Error> {
+    Ok(format!(
+        "{}{}",
+        METADATA_PREFIX,
+        try!(serde_json::to_string(&metadata.to_json()))
+    ))
+}
+
+/// Decodes metadata from the description of an attachment, `None` if it is not a metadata attachment.
+pub fn decode<M: MatchMetadata>(description: &str) -> Option<Result<M, Error>> {
+    if !description.starts_with(METADATA_PREFIX) {
+        return None;
+    }
+    let json = &description[METADATA_PREFIX.len()..];
+    Some(
+        serde_json::from_str(json)
+            .map_err(Error::from)
+            .and_then(|v: Value| M::from_json(&v)),
+    )
+}
+
+/// Returns the metadata attachment among the attachments of a match.
+pub fn find(attachments: &[Attachment]) -> Option<&Attachment> {
+    attachments.iter().find(|a| {
+        a.description
+            .as_ref()
+            .map_or(false, |d| d.starts_with(METADATA_PREFIX))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use metadata::{decode, encode, METADATA_PREFIX};
+    use serde_json::Value;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let mut meta = BTreeMap::new();
+        meta.insert("map".to_owned(), "Dust II".to_owned());
+        meta.insert("vod".to_owned(), "https://example.com/vod/42".to_owned());
+
+        let description = encode(&meta).unwrap();
+        assert!(description.starts_with(METADATA_PREFIX));
+        let decoded: BTreeMap<String, String> = decode(&description).unwrap().unwrap();
+        assert_eq!(decoded, meta);
+
+        assert!(decode::<Value>("gg, rematch next week").is_none());
+        let nested = format!("{}{{\"map\": {{\"name\": \"Dust II\"}}}}", METADATA_PREFIX);
+        assert!(decode::<BTreeMap<String, String>>(&nested)
+            .unwrap()
+            .is_err());
+        assert!(decode::<Value>(&nested).unwrap().is_ok());
+    }
+}
@@ -0,0 +1,172 @@
+//! Sanitation of user-submitted names.
+//!
+//! Names typed in chat (e.g. a Discord signup command) carry leading spaces, line breaks,
+//! invisible characters and sometimes far too many characters, all of which make Challonge reject
+//! the participant or tournament. `sanitize_participant_name` and `sanitize_tournament_name`
+//! clean a name up before it is submitted and report what they changed, so bots can tell the user.
+
+/// Maximum length of a tournament name in characters accepted by Challonge.
+pub const MAX_TOURNAMENT_NAME_LENGTH: usize = 60;
+
+/// Maximum length of a participant name in characters accepted by Challonge.
+pub const MAX_PARTICIPANT_NAME_LENGTH: usize = 255;
+
+/// What sanitation changed in a name.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NameChanges {
+    /// Leading or trailing whitespace was removed
+    pub trimmed: bool,
+
+    /// Runs of whitespace, tabs and line breaks were replaced with a single space
+    pub collapsed_whitespace: bool,
+
+    /// Control and invisible formatting characters were removed
+    pub removed_control: bool,
+
+    /// The name was cut to the maximum length
+    pub truncated: bool,
+}
+impl NameChanges {
+    /// Returns `true` if the name was changed.
+    pub fn any(&self) -> bool {
+        self.trimmed || self.collapsed_whitespace || self.removed_control || self.truncated
+    }
+}
+
+/// A sanitized name and what was changed to get it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SanitizedName {
+    /// The name to submit
+    pub name: String,
+
+    /// What was changed
+    pub changes: NameChanges,
+}
+impl SanitizedName {
+    /// Returns `true` if nothing is left of the name, Challonge rejects blank names.
+    pub fn is_blank(&self) -> bool {
+        self.name.is_empty()
+    }
+}
+
+/// Sanitizes a participant name, see `sanitize_name`.
+pub fn sanitize_participant_name(name: &str) -> SanitizedName {
+    sanitize_name(name, MAX_PARTICIPANT_NAME_LENGTH)
+}
+
+/// Sanitizes a tournament name, see `sanitize_name`.
+pub fn sanitize_tournament_name(name: &str) -> SanitizedName {
+    sanitize_name(name, MAX_TOURNAMENT_NAME_LENGTH)
+}
+
+/// Trims a name, collapses whitespace into single spaces, strips control and invisible characters
+/// and cuts it to `max` characters. Joiners inside emoji sequences are kept.
+pub fn sanitize_name(name: &str, max: usize) -> SanitizedName {
+    let mut changes = NameChanges::default();
+    let mut out = String::with_capacity(name.len());
+    let mut space = false;
+    for c in name.chars() {
+        if c.is_whitespace() {
+            if space || c != ' ' {
+                changes.collapsed_whitespace = true;
+            }
+            space = true;
+            continue;
+        }
+        if c.is_control() || is_invisible(c) {
+            changes.removed_control = true;
+            continue;
+        }
+        if space {
+            if out.is_empty() {
+                changes.trimmed = true;
+            } else {
+                out.push(' ');
+            }
+            space = false;
+        }
+        out.push(c);
+    }
+    if space {
+        changes.trimmed = true;
+    }
+    if changes.trimmed && changes.collapsed_whitespace && !has_inner_run(name) {
+        // whitespace only at the ends is reported as trimmed
+        changes.collapsed_whitespace = false;
+    }
+    if out.chars().count() > max {
+        changes.truncated = true;
+        out = out
+            .chars()
+            .take(max)
+            .collect::<String>()
+            .trim_end()
+            .to_owned();
+    }
+    SanitizedName {
+        name: out,
+        changes: changes,
+    }
+}
+
+/// Returns `true` for zero-width and bidirectional formatting characters which are invisible
+/// in a name. The zero-width joiners are kept, emoji sequences and some scripts need them.
+fn is_invisible(c: char) -> bool {
+    match c {
+        '\u{200B}' | '\u{200E}' | '\u{200F}' | '\u{2060}' | '\u{FEFF}' => true,
+        '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}' => true,
+        _ => false,
+    }
+}
+
+/// Returns `true` if the trimmed name contains whitespace other than single spaces.
+fn has_inner_run(name: &str) -> bool {
+    let mut space = false;
+    for c in name.trim().chars() {
+        if c.is_whitespace() {
+            if space || c != ' ' {
+                return true;
+            }
+            space = true;
+        } else {
+            space = false;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use names::{sanitize_name, sanitize_participant_name, NameChanges};
+
+    #[test]
+    fn test_sanitize_name() {
+        let s = sanitize_participant_name("Ada Lovelace");
+        assert_eq!(s.name, "Ada Lovelace");
+        assert!(!s.changes.any());
+
+        let s = sanitize_participant_name("  Grace\t\u{200B}Hopper\n");
+        assert_eq!(s.name, "Grace Hopper");
+        assert_eq!(
+            s.changes,
+            NameChanges {
+                trimmed: true,
+                collapsed_whitespace: true,
+                removed_control: true,
+                truncated: false,
+            }
+        );
+
+        let s = sanitize_participant_name(" Linus ");
+        assert!(s.changes.trimmed && !s.changes.collapsed_whitespace);
+
+        // emoji sequences keep their joiners
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        assert_eq!(sanitize_participant_name(family).name, family);
+
+        let s = sanitize_name("Friday Night Table Tennis", 13);
+        assert_eq!(s.name, "Friday Night");
+        assert!(s.changes.truncated);
+        assert!(sanitize_participant_name(" \u{202E} ").is_blank());
+    }
+}
@@ -2,11 +2,15 @@
 
 extern crate serde_json;
 
-use chrono::*;
 use serde_json::Value;
+use std::collections::BTreeMap;
+use unicode_normalization::UnicodeNormalization;
 
+use datetime::ChallongeDateTime;
 use error::Error;
-use util::{decode_array, into_map, remove};
+use redaction::RedactionPolicy;
+use serde::{Serialize, Serializer};
+use util::{as_map, decode_array, field};
 
 /// Represents an ID of a participant
 #[derive(Debug, Clone, PartialEq)]
@@ -53,17 +57,295 @@ impl ParticipantCreate {
     builder_s!(misc);
 }
 
+/// A structure for updating a participant. Only provided attributes are changed.
+#[derive(Debug, Clone, Default)]
+pub struct ParticipantUpdate {
+    /// The name displayed in the bracket/schedule. Must be unique per tournament.
+    pub name: Option<String>,
+
+    /// Challonge username of the participant.
+    pub challonge_username: Option<String>,
+
+    /// Email of the participant.
+    pub email: Option<String>,
+
+    /// The participant's new seed.
+    pub seed: Option<u64>,
+
+    /// Multi-purpose field that is only visible via the API.
+    pub misc: Option<String>,
+}
+impl ParticipantUpdate {
+    /// Creates an empty `ParticipantUpdate` structure.
+    pub fn new() -> ParticipantUpdate {
+        ParticipantUpdate::default()
+    }
+
+    builder_so!(name);
+    builder_so!(challonge_username);
+    builder_so!(email);
+    builder_o!(seed, u64);
+    builder_so!(misc);
+}
+
+/// Pause between two batches of `Challonge::update_participants` of a client without a rate limiter,
+/// in milliseconds.
+pub const BATCH_INTERVAL_MILLIS: u64 = 250;
+
+/// Aggregate result of a batch participant operation.
+#[derive(Debug)]
+pub struct BatchReport {
+    /// Participants which were processed successfully.
+    pub succeeded: Vec<ParticipantId>,
+
+    /// Participants which failed along with the errors.
+    pub failed: Vec<(ParticipantId, Error)>,
+}
+impl BatchReport {
+    /// Returns `true` if there were no failures.
+    pub fn is_ok(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Result of checking participants against the signup cap of a tournament.
+#[derive(Debug, Clone)]
+pub struct SignupCapReport {
+    /// Participants which fit into the remaining slots.
+    pub accepted: Vec<ParticipantCreate>,
+
+    /// Participants which would be placed on the waiting list.
+    pub deferred: Vec<ParticipantCreate>,
+}
+impl SignupCapReport {
+    /// Splits participants so that no more than `remaining` of them are accepted.
+    /// All participants are accepted if there is no cap (`None`).
+    pub fn split(participants: Vec<ParticipantCreate>, remaining: Option<u64>) -> SignupCapReport {
+        let mut accepted = participants;
+        let mut deferred = Vec::new();
+        if let Some(r) = remaining {
+            if (r as usize) < accepted.len() {
+                deferred = accepted.split_off(r as usize);
+            }
+        }
+        SignupCapReport {
+            accepted: accepted,
+            deferred: deferred,
+        }
+    }
+
+    /// Returns `true` if some of the participants do not fit under the cap.
+    pub fn exceeds_cap(&self) -> bool {
+        !self.deferred.is_empty()
+    }
+}
+
+/// Options of `Challonge::participant_index_with`.
+/// The API has no participant filters yet, the struct keeps their addition non-breaking.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct ParticipantIndexOptions {}
+impl ParticipantIndexOptions {
+    /// Creates options requesting all participants.
+    pub fn new() -> ParticipantIndexOptions {
+        ParticipantIndexOptions::default()
+    }
+}
+
 /// A list of participants for the tournament.
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct Index(pub Vec<Participant>);
 impl Index {
     /// Decodes participants index from JSON.
-    pub fn decode(value: Value) -> Result<Index, Error> {
+    pub fn decode(value: &Value) -> Result<Index, Error> {
         Ok(Index(try!(decode_array(value, Participant::decode))))
     }
 }
 
+impl Index {
+    /// Detects duplicate registrations: participants with the same invite email,
+    /// challonge username or normalized name.
+    /// In every group the participant with the earliest seed is kept.
+    pub fn find_duplicates(&self) -> Vec<DuplicateGroup> {
+        let participants = &self.0;
+        let mut parents: Vec<usize> = (0..participants.len()).collect();
+        let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+
+        for (i, p) in participants.iter().enumerate() {
+            for key in p.duplicate_keys() {
+                if let Some(&j) = seen.get(&key) {
+                    let (a, b) = (find_root(&mut parents, i), find_root(&mut parents, j));
+                    parents[a] = b;
+                }
+                seen.entry(key).or_insert(i);
+            }
+        }
+
+        let mut groups: BTreeMap<usize, Vec<Participant>> = BTreeMap::new();
+        for (i, p) in participants.iter().enumerate() {
+            let root = find_root(&mut parents, i);
+            groups.entry(root).or_insert_with(Vec::new).push(p.clone());
+        }
+
+        let mut duplicates = Vec::new();
+        for (_, mut group) in groups {
+            if group.len() < 2 {
+                continue;
+            }
+            group.sort_by(|a, b| a.seed.cmp(&b.seed));
+            let keep = group.remove(0);
+            duplicates.push(DuplicateGroup {
+                keep: keep,
+                duplicates: group,
+            });
+        }
+        duplicates.sort_by(|a, b| a.keep.seed.cmp(&b.keep.seed));
+        duplicates
+    }
+}
+
+impl Index {
+    /// Finds the participant invited with the email address, e.g. to resend a check-in link.
+    pub fn find_by_email(&self, email: &str) -> Option<&Participant> {
+        self.0.iter().find(|p| p.has_invite_email(email))
+    }
+
+    /// Returns participants who take part in the tournament.
+    pub fn active(&self) -> Vec<&Participant> {
+        self.0.iter().filter(|p| p.active).collect()
+    }
+
+    /// Returns ids of participants deactivated while the tournament was underway,
+    /// their remaining matches are forfeited.
+    pub fn inactive_ids(&self) -> Vec<ParticipantId> {
+        self.0
+            .iter()
+            .filter(|p| !p.active)
+            .map(|p| p.id.clone())
+            .collect()
+    }
+
+    /// Returns participants whose invitations have not been accepted yet.
+    pub fn pending_invitations(&self) -> Vec<&Participant> {
+        self.0
+            .iter()
+            .filter(|p| p.invitation_status() == InvitationStatus::Pending)
+            .collect()
+    }
+}
+
+fn find_root(parents: &mut Vec<usize>, i: usize) -> usize {
+    let mut root = i;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    parents[i] = root;
+    root
+}
+
+/// Normalizes a participant name for comparison: applies NFC, trims it, collapses inner whitespace and lowercases it.
+pub fn normalize_name(name: &str) -> String {
+    name.nfc()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// A group of participants which look like duplicate registrations.
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    /// Participant with the earliest seed, it is kept.
+    pub keep: Participant,
+
+    /// Duplicate registrations of the same participant.
+    pub duplicates: Vec<Participant>,
+}
+
+/// Invitation state of a participant.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InvitationStatus {
+    /// Participant was added without an invitation.
+    NotInvited,
+
+    /// Invitation was sent but has not been accepted yet.
+    Pending,
+
+    /// Invitation was accepted.
+    Accepted,
+}
+
+/// Result of checking a Challonge username.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UsernameStatus {
+    /// The user exists and can be invited.
+    Exists,
+
+    /// No user with such name was found.
+    NotFound,
+
+    /// The string is not a valid Challonge username.
+    Invalid,
+}
+
+/// Field of a participant which must be unique within a tournament.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuplicateField {
+    /// Display name
+    Name,
+
+    /// Email address the invitation was sent to
+    Email,
+
+    /// Challonge username
+    Username,
+}
+impl DuplicateField {
+    /// Detects the field of a Challonge "has already been taken" validation message.
+    pub fn from_message(message: &str) -> Option<DuplicateField> {
+        let message = message.to_lowercase();
+        let subject = match message.find(" has already been ") {
+            Some(i) => &message[..i],
+            None => return None,
+        };
+        if subject.contains("email") {
+            Some(DuplicateField::Email)
+        } else if subject.contains("username") {
+            Some(DuplicateField::Username)
+        } else if subject.contains("name") {
+            Some(DuplicateField::Name)
+        } else {
+            None
+        }
+    }
+}
+
+/// Checks that a string looks like a Challonge username: non-empty, up to 32 characters
+/// consisting of letters, numbers, dashes, dots and underscores, not starting with a dot.
+pub fn is_valid_username(username: &str) -> bool {
+    !username.is_empty()
+        && !username.starts_with('.')
+        && username.chars().count() <= 32
+        && username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+/// A report of merging duplicate participants.
+#[derive(Debug, Clone)]
+pub struct MergeReport {
+    /// Detected groups of duplicates.
+    pub groups: Vec<DuplicateGroup>,
+
+    /// Participants which were removed (or would be removed in a dry run).
+    pub removed: Vec<ParticipantId>,
+
+    /// `true` if nothing was actually removed.
+    pub dry_run: bool,
+}
+
 /// Challonge `Participant` definition.
 #[derive(Debug, Clone)]
 pub struct Participant {
@@ -71,10 +353,10 @@ pub struct Participant {
     pub active: bool,
 
     /// Time when the participant was checked in
-    pub checked_in_at: Option<DateTime<FixedOffset>>,
+    pub checked_in_at: Option<ChallongeDateTime>,
 
     /// Time when the participant was added to the tournament
-    pub created_at: DateTime<FixedOffset>,
+    pub created_at: ChallongeDateTime,
 
     /// ???
     pub final_rank: Option<u64>,
@@ -110,7 +392,7 @@ pub struct Participant {
     pub tournament_id: u64,
 
     /// Time when the participant was updated last time
-    pub updated_at: DateTime<FixedOffset>,
+    pub updated_at: ChallongeDateTime,
 
     /// A name of a user in challonge system.
     pub challonge_username: String,
@@ -152,126 +434,372 @@ pub struct Participant {
     pub reactivatable: bool,
 }
 impl Participant {
-    /// Decodes `Participant` from JSON.
-    pub fn decode(value: Value) -> Result<Participant, Error> {
-        let mut value = try!(into_map(value));
-        let t = try!(remove(&mut value, "participant"));
-        let mut tv = try!(into_map(t));
-
-        let mut checked_in_at = None;
-        if let Some(ci_str) = try!(remove(&mut tv, "checked_in_at")).as_string() {
-            if let Ok(ci) = DateTime::parse_from_rfc3339(ci_str) {
-                checked_in_at = Some(ci);
+    /// Returns the name which should be displayed for the participant.
+    /// Follows Challonge's precedence: name, then username, then the invite email prefix.
+    pub fn display_name(&self) -> &str {
+        if !self.name.trim().is_empty() {
+            return &self.name;
+        }
+        if !self.challonge_username.is_empty() {
+            return &self.challonge_username;
+        }
+        if !self.username.is_empty() {
+            return &self.username;
+        }
+        if let Some(prefix) = self.invite_email.split('@').next() {
+            if !prefix.is_empty() {
+                return prefix;
             }
         }
+        &self.display_name_with_invitation_email_address
+    }
+
+    /// Returns invitation state of the participant.
+    pub fn invitation_status(&self) -> InvitationStatus {
+        if self.invitation_pending {
+            InvitationStatus::Pending
+        } else if self.invitation_id.is_some() || !self.challonge_username.is_empty() {
+            InvitationStatus::Accepted
+        } else {
+            InvitationStatus::NotInvited
+        }
+    }
+
+    /// Returns `true` if the participant was invited with the email address, compared case-insensitively.
+    /// The invite email is checked first, then the address shown in
+    /// `display_name_with_invitation_email_address` (e.g. "Alice (alice@example.com)").
+    pub fn has_invite_email(&self, email: &str) -> bool {
+        let email = email.trim().to_lowercase();
+        if email.is_empty() {
+            return false;
+        }
+        if self.invite_email.trim().to_lowercase() == email {
+            return true;
+        }
+        let display = self
+            .display_name_with_invitation_email_address
+            .to_lowercase();
+        match (display.rfind('('), display.rfind(')')) {
+            (Some(start), Some(end)) if start < end => display[start + 1..end].trim() == email,
+            _ => display.trim() == email,
+        }
+    }
+
+    /// Creates a `ParticipantCreate` structure which adds the same participant again.
+    pub fn to_create(&self) -> ParticipantCreate {
+        ParticipantCreate {
+            name: if self.name.is_empty() {
+                None
+            } else {
+                Some(self.name.clone())
+            },
+            challonge_username: if self.challonge_username.is_empty() {
+                None
+            } else {
+                Some(self.challonge_username.clone())
+            },
+            email: self.invite_email.clone(),
+            seed: self.seed,
+            misc: self.misc.clone(),
+        }
+    }
+
+    fn duplicate_keys(&self) -> Vec<String> {
+        let mut keys = Vec::new();
+        if !self.invite_email.is_empty() {
+            keys.push(format!("email:{}", self.invite_email.trim().to_lowercase()));
+        }
+        if !self.challonge_username.is_empty() {
+            keys.push(format!(
+                "username:{}",
+                self.challonge_username.trim().to_lowercase()
+            ));
+        }
+        let name = normalize_name(&self.name);
+        if !name.is_empty() {
+            keys.push(format!("name:{}", name));
+        }
+        keys
+    }
+
+    /// Encodes the participant into JSON in the API format, redacting fields according to `policy`.
+    pub fn encode(&self, policy: &RedactionPolicy) -> Value {
+        fn s(v: &str) -> Value {
+            Value::String(v.to_owned())
+        }
+        fn b(v: bool) -> Value {
+            Value::Bool(v)
+        }
+        fn u(v: Option<u64>) -> Value {
+            v.map_or(Value::Null, Value::U64)
+        }
+        fn d(v: Option<&ChallongeDateTime>) -> Value {
+            v.map_or(Value::Null, |d| Value::String(d.to_string()))
+        }
+
+        let mut p = BTreeMap::new();
+        p.insert("active".to_owned(), b(self.active));
+        p.insert("checked_in_at".to_owned(), d(self.checked_in_at.as_ref()));
+        p.insert("created_at".to_owned(), d(Some(&self.created_at)));
+        p.insert("final_rank".to_owned(), u(self.final_rank));
+        p.insert("group_id".to_owned(), u(self.group_id));
+        p.insert("icon".to_owned(), s(&self.icon));
+        p.insert("id".to_owned(), u(Some(self.id.0)));
+        p.insert("invitation_id".to_owned(), u(self.invitation_id));
+        p.insert("invite_email".to_owned(), s(&self.invite_email));
+        p.insert("misc".to_owned(), s(&self.misc));
+        p.insert("name".to_owned(), s(&self.name));
+        p.insert("on_waiting_list".to_owned(), b(self.on_waiting_list));
+        p.insert("seed".to_owned(), u(Some(self.seed)));
+        p.insert("tournament_id".to_owned(), u(Some(self.tournament_id)));
+        p.insert("updated_at".to_owned(), d(Some(&self.updated_at)));
+        p.insert("challonge_username".to_owned(), s(&self.challonge_username));
+        p.insert(
+            "challonge_email_address_verified".to_owned(),
+            s(&self.challonge_email_address_verified),
+        );
+        p.insert("removable".to_owned(), b(self.removable));
+        p.insert(
+            "participatable_or_invitation_attached".to_owned(),
+            b(self.participatable_or_invitation_attached),
+        );
+        p.insert("confirm_remove".to_owned(), b(self.confirm_remove));
+        p.insert("invitation_pending".to_owned(), b(self.invitation_pending));
+        p.insert(
+            "display_name_with_invitation_email_address".to_owned(),
+            s(&self.display_name_with_invitation_email_address),
+        );
+        p.insert("email_hash".to_owned(), s(&self.email_hash));
+        p.insert("username".to_owned(), s(&self.username));
+        p.insert(
+            "attached_participatable_portrait_url".to_owned(),
+            s(&self.attached_participatable_portrait_url),
+        );
+        p.insert("can_check_in".to_owned(), b(self.can_check_in));
+        p.insert("checked_in".to_owned(), b(self.checked_in));
+        p.insert("reactivatable".to_owned(), b(self.reactivatable));
+
+        let mut root = BTreeMap::new();
+        root.insert("participant".to_owned(), Value::Object(p));
+        let mut value = Value::Object(root);
+        policy.apply(&mut value);
+        value
+    }
+
+    /// Decodes `Participant` from JSON.
+    pub fn decode(value: &Value) -> Result<Participant, Error> {
+        let value = try!(as_map(value));
+        let t = try!(field(value, "participant"));
+        let tv = try!(as_map(t));
 
         Ok(Participant {
-            active: try!(remove(&mut tv, "active"))
-                .as_boolean()
-                .unwrap_or(false),
-            checked_in_at: checked_in_at,
-            created_at: DateTime::parse_from_rfc3339(
-                try!(remove(&mut tv, "created_at"))
-                    .as_string()
-                    .unwrap_or(""),
-            )
-            .unwrap(),
-            final_rank: try!(remove(&mut tv, "final_rank")).as_u64(),
-            group_id: try!(remove(&mut tv, "group_id")).as_u64(),
-            icon: try!(remove(&mut tv, "icon"))
-                .as_string()
-                .unwrap_or("")
-                .to_owned(),
-            id: ParticipantId(try!(remove(&mut tv, "id")).as_u64().unwrap()),
-            invitation_id: try!(remove(&mut tv, "invitation_id")).as_u64(),
-            invite_email: try!(remove(&mut tv, "invite_email"))
-                .as_string()
-                .unwrap_or("")
-                .to_owned(),
-            misc: try!(remove(&mut tv, "misc"))
+            active: try!(field(tv, "active")).as_boolean().unwrap_or(false),
+            checked_in_at: try!(ChallongeDateTime::decode(try!(field(tv, "checked_in_at")))),
+            created_at: try!(ChallongeDateTime::decode_required(try!(field(
+                tv,
+                "created_at"
+            )))),
+            final_rank: try!(field(tv, "final_rank")).as_u64(),
+            group_id: try!(field(tv, "group_id")).as_u64(),
+            icon: try!(field(tv, "icon")).as_string().unwrap_or("").to_owned(),
+            id: ParticipantId(try!(field(tv, "id")).as_u64().unwrap()),
+            invitation_id: try!(field(tv, "invitation_id")).as_u64(),
+            invite_email: try!(field(tv, "invite_email"))
                 .as_string()
                 .unwrap_or("")
                 .to_owned(),
-            name: try!(remove(&mut tv, "name"))
+            misc: try!(field(tv, "misc")).as_string().unwrap_or("").to_owned(),
+            name: try!(field(tv, "name")).as_string().unwrap_or("").to_owned(),
+            on_waiting_list: try!(field(tv, "on_waiting_list"))
+                .as_boolean()
+                .unwrap_or(false),
+            seed: try!(field(tv, "seed")).as_u64().unwrap(),
+            tournament_id: try!(field(tv, "tournament_id")).as_u64().unwrap(),
+            updated_at: try!(ChallongeDateTime::decode_required(try!(field(
+                tv,
+                "updated_at"
+            )))),
+            challonge_username: try!(field(tv, "challonge_username"))
                 .as_string()
                 .unwrap_or("")
                 .to_owned(),
-            on_waiting_list: try!(remove(&mut tv, "on_waiting_list"))
-                .as_boolean()
-                .unwrap_or(false),
-            seed: try!(remove(&mut tv, "seed")).as_u64().unwrap(),
-            tournament_id: try!(remove(&mut tv, "tournament_id")).as_u64().unwrap(),
-            updated_at: DateTime::parse_from_rfc3339(
-                try!(remove(&mut tv, "updated_at"))
-                    .as_string()
-                    .unwrap_or(""),
-            )
-            .unwrap(),
-            challonge_username: try!(remove(&mut tv, "challonge_username"))
+            challonge_email_address_verified: try!(field(tv, "challonge_email_address_verified"))
                 .as_string()
                 .unwrap_or("")
                 .to_owned(),
-            challonge_email_address_verified: try!(remove(
-                &mut tv,
-                "challonge_email_address_verified"
-            ))
-            .as_string()
-            .unwrap_or("")
-            .to_owned(),
-            removable: try!(remove(&mut tv, "removable"))
-                .as_boolean()
-                .unwrap_or(false),
-            participatable_or_invitation_attached: try!(remove(
-                &mut tv,
+            removable: try!(field(tv, "removable")).as_boolean().unwrap_or(false),
+            participatable_or_invitation_attached: try!(field(
+                tv,
                 "participatable_or_invitation_attached"
             ))
             .as_boolean()
             .unwrap_or(false),
-            confirm_remove: try!(remove(&mut tv, "confirm_remove"))
+            confirm_remove: try!(field(tv, "confirm_remove"))
                 .as_boolean()
                 .unwrap_or(false),
-            invitation_pending: try!(remove(&mut tv, "invitation_pending"))
+            invitation_pending: try!(field(tv, "invitation_pending"))
                 .as_boolean()
                 .unwrap_or(false),
-            display_name_with_invitation_email_address: try!(remove(
-                &mut tv,
+            display_name_with_invitation_email_address: try!(field(
+                tv,
                 "display_name_with_invitation_email_address"
             ))
             .as_string()
             .unwrap_or("")
             .to_owned(),
-            email_hash: try!(remove(&mut tv, "email_hash"))
+            email_hash: try!(field(tv, "email_hash"))
                 .as_string()
                 .unwrap_or("")
                 .to_owned(),
-            username: try!(remove(&mut tv, "username"))
+            username: try!(field(tv, "username"))
                 .as_string()
                 .unwrap_or("")
                 .to_owned(),
-            attached_participatable_portrait_url: try!(remove(
-                &mut tv,
+            attached_participatable_portrait_url: try!(field(
+                tv,
                 "attached_participatable_portrait_url"
             ))
             .as_string()
             .unwrap_or("")
             .to_owned(),
-            checked_in: try!(remove(&mut tv, "checked_in"))
+            checked_in: try!(field(tv, "checked_in")).as_boolean().unwrap_or(false),
+            can_check_in: try!(field(tv, "can_check_in"))
                 .as_boolean()
                 .unwrap_or(false),
-            can_check_in: try!(remove(&mut tv, "can_check_in"))
-                .as_boolean()
-                .unwrap_or(false),
-            reactivatable: try!(remove(&mut tv, "reactivatable"))
+            reactivatable: try!(field(tv, "reactivatable"))
                 .as_boolean()
                 .unwrap_or(false),
         })
     }
 }
 
+/// Serializes the participant in the API format with `RedactionPolicy::default()`,
+/// so emails are never exposed by accident. Use `Participant::encode` for another policy.
+impl Serialize for Participant {
+    fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        self.encode(&RedactionPolicy::default())
+            .serialize(serializer)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     extern crate serde_json;
-    use participants::Participant;
+    use participants::{
+        is_valid_username, normalize_name, Index, InvitationStatus, Participant, ParticipantCreate,
+        SignupCapReport,
+    };
+    use redaction::RedactionPolicy;
+
+    #[test]
+    fn test_username_validation() {
+        assert!(is_valid_username("vityafx"));
+        assert!(is_valid_username("some_user-1.2"));
+        assert!(!is_valid_username(""));
+        assert!(!is_valid_username("user name"));
+        assert!(!is_valid_username("user/../admin"));
+        assert!(!is_valid_username(".."));
+        assert!(!is_valid_username(".vityafx"));
+        assert!(!is_valid_username("ユーザー"));
+    }
+
+    fn participant(id: u64, name: &str, seed: u64, email: &str) -> Participant {
+        let json = include_str!("../benches/fixtures/participant.json");
+        let mut p = Participant::decode(&serde_json::from_str(json).unwrap()).unwrap();
+        p.id.0 = id;
+        p.name = name.to_owned();
+        p.display_name_with_invitation_email_address = String::new();
+        p.seed = seed;
+        p.invite_email = email.to_owned();
+        p
+    }
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("  John   DOE "), "john doe");
+        assert_eq!(normalize_name("Jose\u{301}"), normalize_name("Jos\u{e9}"));
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let index = Index(vec![
+            participant(1, "John Doe", 3, ""),
+            participant(2, "john  doe", 1, "john@example.com"),
+            participant(3, "Johnny", 5, "JOHN@example.com"),
+            participant(4, "Jane", 2, ""),
+        ]);
+        let groups = index.find_duplicates();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].keep.id.0, 2);
+        let mut ids: Vec<u64> = groups[0].duplicates.iter().map(|p| p.id.0).collect();
+        ids.sort();
+        assert_eq!(ids, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_invitation_status() {
+        let mut invited = participant(5, "Invited", 4, "invited@example.com");
+        invited.invitation_pending = true;
+        assert_eq!(invited.invitation_status(), InvitationStatus::Pending);
+        assert_eq!(invited.to_create().email, "invited@example.com");
+        assert_eq!(Index(vec![invited.clone()]).pending_invitations().len(), 1);
+        assert!(invited.has_invite_email(" Invited@Example.com"));
+
+        invited.invite_email = String::new();
+        invited.display_name_with_invitation_email_address =
+            "Invited (invited@example.com)".to_owned();
+        let index = Index(vec![participant(6, "Other", 1, ""), invited]);
+        assert_eq!(index.find_by_email("INVITED@example.com").unwrap().id.0, 5);
+        assert!(index.find_by_email("").is_none());
+    }
+
+    #[test]
+    fn test_display_name() {
+        let mut p = participant(5, "Invited", 4, "invited@example.com");
+        assert_eq!(p.display_name(), "Invited");
+        p.name = String::new();
+        assert_eq!(p.display_name(), "invited");
+        p.username = "invited_user".to_owned();
+        assert_eq!(p.display_name(), "invited_user");
+    }
+
+    #[test]
+    fn test_signup_cap_split() {
+        let mut participants = Vec::new();
+        for i in 0..5 {
+            let mut pc = ParticipantCreate::new();
+            pc.name(format!("Participant #{}", i));
+            participants.push(pc);
+        }
+
+        let report = SignupCapReport::split(participants.clone(), None);
+        assert_eq!(report.accepted.len(), 5);
+        assert!(!report.exceeds_cap());
+
+        let report = SignupCapReport::split(participants.clone(), Some(3));
+        assert_eq!(report.accepted.len(), 3);
+        assert_eq!(report.deferred.len(), 2);
+        assert_eq!(report.deferred[0].name, Some("Participant #3".to_owned()));
+        assert!(report.exceeds_cap());
+
+        let report = SignupCapReport::split(participants, Some(0));
+        assert!(report.accepted.is_empty());
+        assert_eq!(report.deferred.len(), 5);
+    }
+
+    #[test]
+    fn test_participant_encode() {
+        let p = participant(7, "Bob", 3, "bob@example.com");
+        let full = p.encode(&RedactionPolicy::none());
+        let decoded = Participant::decode(&full).unwrap();
+        assert_eq!(decoded.id, p.id);
+        assert_eq!(decoded.invite_email, "bob@example.com");
+        assert_eq!(decoded.created_at, p.created_at);
+
+        let json = serde_json::to_string(&p).unwrap();
+        assert!(json.contains("\"name\":\"Bob\""));
+        assert!(!json.contains("bob@example.com"));
+    }
 
     #[test]
     fn test_participant_parse() {
@@ -310,7 +838,7 @@ mod tests {
         let json_r = serde_json::from_str(string);
         assert!(json_r.is_ok());
         let json = json_r.unwrap();
-        if let Ok(p) = Participant::decode(json) {
+        if let Ok(p) = Participant::decode(&json) {
             assert_eq!(p.active, true);
             assert_eq!(p.checked_in_at, None);
             // assert_eq!(p.created_at, );
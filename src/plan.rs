@@ -0,0 +1,228 @@
+//! Reviewable plans of API mutations.
+//!
+//! Tools which change many things at once (reconcilers, bulk editors, CLIs) can first build a
+//! `Plan`, show it with `Display` or export it with `to_json` for review, and only then `apply` it.
+//! A plan lists every request it will send with its exact parameters.
+
+extern crate serde_json;
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use error::Error;
+use matches::{MatchId, MatchUpdate};
+use participants::{ParticipantCreate, ParticipantId, ParticipantUpdate};
+use tournament::{TournamentCreate, TournamentId};
+use transaction;
+use transport::Method;
+use {mu_to_pairs, pc_to_pairs, pu_to_pairs, tc_to_pairs, Challonge};
+
+/// A single intended API mutation.
+#[derive(Debug, Clone)]
+pub enum Mutation {
+    /// Create a tournament
+    CreateTournament(TournamentCreate),
+
+    /// Update attributes of a tournament
+    UpdateTournament(TournamentId, TournamentCreate),
+
+    /// Delete a tournament
+    DeleteTournament(TournamentId),
+
+    /// Add a participant to a tournament
+    CreateParticipant(TournamentId, ParticipantCreate),
+
+    /// Update attributes of a participant
+    UpdateParticipant(TournamentId, ParticipantId, ParticipantUpdate),
+
+    /// Remove a participant from a tournament
+    DeleteParticipant(TournamentId, ParticipantId),
+
+    /// Update scores of a match
+    UpdateMatch(TournamentId, MatchId, MatchUpdate),
+}
+impl Mutation {
+    /// Returns the request method of the mutation.
+    pub fn method(&self) -> Method {
+        match *self {
+            Mutation::CreateTournament(_) | Mutation::CreateParticipant(..) => Method::Post,
+            Mutation::DeleteTournament(_) | Mutation::DeleteParticipant(..) => Method::Delete,
+            _ => Method::Put,
+        }
+    }
+
+    /// Returns the request path of the mutation relative to the API base, e.g. `/tournaments/weekly1.json`.
+    pub fn path(&self) -> String {
+        match *self {
+            Mutation::CreateTournament(_) => "/tournaments.json".to_owned(),
+            Mutation::UpdateTournament(ref id, _) | Mutation::DeleteTournament(ref id) => {
+                format!("/tournaments/{}.json", id)
+            }
+            Mutation::CreateParticipant(ref id, _) => {
+                format!("/tournaments/{}/participants.json", id)
+            }
+            Mutation::UpdateParticipant(ref id, ref participant, _)
+            | Mutation::DeleteParticipant(ref id, ref participant) => {
+                format!("/tournaments/{}/participants/{}.json", id, participant.0)
+            }
+            Mutation::UpdateMatch(ref id, ref m, _) => {
+                format!("/tournaments/{}/matches/{}.json", id, m.0)
+            }
+        }
+    }
+
+    /// Returns the parameters sent with the mutation.
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        match *self {
+            Mutation::CreateTournament(ref t) | Mutation::UpdateTournament(_, ref t) => {
+                tc_to_pairs(t)
+            }
+            Mutation::CreateParticipant(_, ref p) => pc_to_pairs(p),
+            Mutation::UpdateParticipant(_, _, ref p) => pu_to_pairs(p),
+            Mutation::UpdateMatch(_, _, ref m) => mu_to_pairs(m),
+            Mutation::DeleteTournament(_) | Mutation::DeleteParticipant(..) => Vec::new(),
+        }
+    }
+
+    /// Encodes the mutation as `{"method": .., "path": .., "params": {..}}`.
+    pub fn to_json(&self) -> Value {
+        let mut map = BTreeMap::new();
+        map.insert(
+            "method".to_owned(),
+            Value::String(self.method().to_string()),
+        );
+        map.insert("path".to_owned(), Value::String(self.path()));
+        map.insert(
+            "params".to_owned(),
+            Value::Object(
+                self.params()
+                    .into_iter()
+                    .map(|(k, v)| (k.to_owned(), Value::String(v)))
+                    .collect(),
+            ),
+        );
+        Value::Object(map)
+    }
+
+    fn apply(&self, tx: &mut transaction::Transaction) -> Result<(), Error> {
+        let client = tx.client();
+        match *self {
+            Mutation::CreateTournament(ref t) => tx.create_tournament(t).map(|_| ()),
+            Mutation::UpdateTournament(ref id, ref t) => {
+                client.update_tournament(id, t).map(|_| ())
+            }
+            Mutation::DeleteTournament(ref id) => client.delete_tournament(id).map(|_| ()),
+            Mutation::CreateParticipant(ref id, ref p) => tx.create_participant(id, p).map(|_| ()),
+            Mutation::UpdateParticipant(ref id, ref participant, ref p) => {
+                client.update_participant_with(id, participant, p)
+            }
+            Mutation::DeleteParticipant(ref id, ref participant) => {
+                client.delete_participant(id, participant)
+            }
+            Mutation::UpdateMatch(ref id, ref m, ref u) => {
+                client.update_match(id, m, u).map(|_| ())
+            }
+        }
+    }
+}
+impl fmt::Display for Mutation {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(fmt, "{} {}", self.method(), self.path()));
+        for (key, value) in self.params() {
+            try!(write!(fmt, "\n    {} = {:?}", key, value));
+        }
+        Ok(())
+    }
+}
+
+/// Ordered list of intended mutations.
+#[derive(Debug, Clone, Default)]
+pub struct Plan {
+    /// Mutations in the order they are applied
+    pub mutations: Vec<Mutation>,
+}
+impl Plan {
+    /// Creates an empty plan.
+    pub fn new() -> Plan {
+        Plan::default()
+    }
+
+    /// Adds a mutation to the end of the plan.
+    pub fn push(&mut self, mutation: Mutation) -> &mut Self {
+        self.mutations.push(mutation);
+        self
+    }
+
+    /// Returns `true` if the plan changes nothing.
+    pub fn is_empty(&self) -> bool {
+        self.mutations.is_empty()
+    }
+
+    /// Encodes the plan as a json array of mutations.
+    pub fn to_json(&self) -> Value {
+        Value::Array(self.mutations.iter().map(Mutation::to_json).collect())
+    }
+
+    /// Applies the mutations in order. If one fails, the tournaments and participants created
+    /// by the plan are removed again and `Error::RolledBack` is returned.
+    /// Updates and deletions are not undone. Returns the number of applied mutations.
+    pub fn apply(&self, client: &Challonge) -> Result<usize, Error> {
+        transaction::run(client, |tx| {
+            for m in &self.mutations {
+                try!(m.apply(tx));
+            }
+            Ok(self.mutations.len())
+        })
+    }
+}
+impl fmt::Display for Plan {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        if self.mutations.is_empty() {
+            return fmt.write_str("No changes.");
+        }
+        for (i, m) in self.mutations.iter().enumerate() {
+            try!(writeln!(fmt, "{}. {}", i + 1, m));
+        }
+        write!(fmt, "{} change(s) planned.", self.mutations.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use matches::{MatchId, MatchUpdate};
+    use participants::{ParticipantCreate, ParticipantId};
+    use plan::{Mutation, Plan};
+    use tournament::TournamentId;
+
+    #[test]
+    fn test_plan_output() {
+        let id = TournamentId::Url("mysub".to_owned(), "weekly1".to_owned());
+        let mut pc = ParticipantCreate::new();
+        pc.name("Alice");
+        let mut plan = Plan::new();
+        plan.push(Mutation::CreateParticipant(id.clone(), pc))
+            .push(Mutation::DeleteParticipant(id.clone(), ParticipantId(5)))
+            .push(Mutation::UpdateMatch(
+                id.clone(),
+                MatchId(7),
+                MatchUpdate::new(),
+            ));
+
+        let text = plan.to_string();
+        assert!(text.starts_with("1. POST /tournaments/mysub-weekly1/participants.json\n"));
+        assert!(text.contains("\n    participant[name] = \"Alice\"\n"));
+        assert!(text.contains("2. DELETE /tournaments/mysub-weekly1/participants/5.json\n"));
+        assert!(text.ends_with("3 change(s) planned."));
+
+        let json = plan.to_json();
+        let first = json.as_array().unwrap()[0].as_object().unwrap();
+        assert_eq!(first.get("method").unwrap().as_string(), Some("POST"));
+        let params = first.get("params").unwrap().as_object().unwrap();
+        assert_eq!(
+            params.get("participant[name]").unwrap().as_string(),
+            Some("Alice")
+        );
+        assert_eq!(Plan::new().to_string(), "No changes.");
+    }
+}
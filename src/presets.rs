@@ -0,0 +1,200 @@
+//! Tournament settings presets.
+//!
+//! A preset is a `TournamentCreate` template used for a series of events (e.g. weekly tournaments).
+//! `Preset::diff_against` audits a live tournament against it.
+
+use tournament::{GamePoints, Tournament, TournamentCreate, TournamentType};
+
+/// A setting of a live tournament which deviates from the preset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettingDiff {
+    /// Name of the setting as used by the API
+    pub setting: &'static str,
+
+    /// Value of the preset
+    pub expected: String,
+
+    /// Value of the live tournament
+    pub actual: String,
+}
+
+/// A named template of tournament settings.
+#[derive(Debug, Clone)]
+pub struct Preset {
+    /// Name of the preset
+    pub name: String,
+
+    /// Settings of the preset. Per-event attributes (name, url, description, start time) are not compared.
+    pub settings: TournamentCreate,
+}
+impl Preset {
+    /// Creates a preset.
+    pub fn new<S: Into<String>>(name: S, settings: TournamentCreate) -> Preset {
+        Preset {
+            name: name.into(),
+            settings: settings,
+        }
+    }
+
+    /// Returns the settings of `tournament` which deviate from the preset.
+    /// Points and rounds of swiss and round robin systems are compared only for tournaments of that type.
+    pub fn diff_against(&self, tournament: &Tournament) -> Vec<SettingDiff> {
+        let p = &self.settings;
+        let mut diffs = Vec::new();
+        {
+            let mut check = |setting: &'static str, expected: String, actual: String| {
+                if expected != actual {
+                    diffs.push(SettingDiff {
+                        setting: setting,
+                        expected: expected,
+                        actual: actual,
+                    });
+                }
+            };
+
+            check(
+                "tournament_type",
+                p.tournament_type.to_string(),
+                tournament.tournament_type.to_string(),
+            );
+            check(
+                "open_signup",
+                p.open_signup.to_string(),
+                tournament.open_signup.to_string(),
+            );
+            check(
+                "hold_third_place_match",
+                p.hold_third_place_match.to_string(),
+                tournament.hold_third_place_match.to_string(),
+            );
+            check(
+                "show_rounds",
+                p.show_rounds.to_string(),
+                tournament.show_rounds.to_string(),
+            );
+            check(
+                "private",
+                p.private.to_string(),
+                tournament.private.to_string(),
+            );
+            check(
+                "notify_users_when_matches_open",
+                p.notify_users_when_matches_open.to_string(),
+                tournament.notify_users_when_matches_open.to_string(),
+            );
+            check(
+                "notify_users_when_the_tournament_ends",
+                p.notify_users_when_the_tournament_ends.to_string(),
+                tournament.notify_users_when_the_tournament_ends.to_string(),
+            );
+            check(
+                "sequential_pairings",
+                p.sequential_pairings.to_string(),
+                tournament.sequential_pairings.to_string(),
+            );
+            check(
+                "signup_cap",
+                optional(if p.signup_cap == 0 {
+                    None
+                } else {
+                    Some(p.signup_cap)
+                }),
+                optional(tournament.signup_cap),
+            );
+            if p.category.is_some() {
+                check(
+                    "category",
+                    optional(p.category.as_ref()),
+                    optional(tournament.category.as_ref()),
+                );
+            }
+            if let Some(game) = p.game_name.as_ref() {
+                check("game_name", game.clone(), tournament.game_name.clone());
+            }
+
+            match p.tournament_type {
+                TournamentType::Swiss => {
+                    check(
+                        "swiss_rounds",
+                        optional(p.swiss_round_count()),
+                        optional(tournament.swiss_round_count()),
+                    );
+                    check(
+                        "swiss_points",
+                        points(&p.swiss_points),
+                        points(&tournament.swiss_points),
+                    );
+                }
+                TournamentType::RoundRobin => check(
+                    "round_robin_points",
+                    points(&p.round_robin_points),
+                    points(&tournament.round_robin_points),
+                ),
+                _ => {}
+            }
+        }
+        diffs
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "none".to_owned(), |v| v.to_string())
+}
+
+fn points(p: &GamePoints) -> String {
+    format!(
+        "match win {}, match tie {}, game win {}, game tie {}, bye {}",
+        p.match_win,
+        p.match_tie,
+        p.game_win,
+        p.game_tie,
+        optional(p.bye)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use presets::{Preset, SettingDiff};
+    use tournament::{Tournament, TournamentCreate, TournamentType};
+
+    #[test]
+    fn test_diff_against() {
+        let t = Tournament::decode(
+            &serde_json::from_str(include_str!("../benches/fixtures/tournament.json")).unwrap(),
+        )
+        .unwrap();
+
+        let mut settings = TournamentCreate::new();
+        settings
+            .tournament_type(t.tournament_type.clone())
+            .open_signup(t.open_signup)
+            .hold_third_place_match(t.hold_third_place_match);
+        settings.show_rounds = t.show_rounds;
+        settings.private = t.private;
+        settings.notify_users_when_matches_open = t.notify_users_when_matches_open;
+        settings.notify_users_when_the_tournament_ends = t.notify_users_when_the_tournament_ends;
+        settings.sequential_pairings = t.sequential_pairings;
+        settings.signup_cap = t.signup_cap.unwrap_or(0);
+        let preset = Preset::new("weekly", settings);
+        assert!(preset.diff_against(&t).is_empty());
+
+        let mut preset = preset.clone();
+        preset
+            .settings
+            .tournament_type(TournamentType::DoubleElimination);
+        preset.settings.private = !t.private;
+        let diffs = preset.diff_against(&t);
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(
+            diffs[0],
+            SettingDiff {
+                setting: "tournament_type",
+                expected: "double elimination".to_owned(),
+                actual: t.tournament_type.to_string(),
+            }
+        );
+        assert_eq!(diffs[1].setting, "private");
+    }
+}
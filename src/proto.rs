@@ -0,0 +1,277 @@
+//! Protobuf messages of the core models and watcher events (`protobuf` feature).
+//!
+//! The messages are defined in `proto/challonge.proto` and encoded with `prost`, so pipelines
+//! talking gRPC can use them instead of a hand-written mirror of the models.
+//! Timestamps are RFC 3339 strings, missing ids and timestamps are encoded as zero and empty values.
+
+use datetime::ChallongeDateTime;
+use matches::Match;
+use participants::{Participant, ParticipantId};
+use tournament::{Tournament, TournamentId};
+use watcher::Event as WatcherEvent;
+
+/// Protobuf `Tournament` message.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProtoTournament {
+    /// Numeric id, 0 if the tournament is only known by url
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    /// Name
+    #[prost(string, tag = "2")]
+    pub name: String,
+    /// Url
+    #[prost(string, tag = "3")]
+    pub url: String,
+    /// State
+    #[prost(string, tag = "4")]
+    pub state: String,
+    /// Tournament type
+    #[prost(string, tag = "5")]
+    pub tournament_type: String,
+    /// Number of participants
+    #[prost(uint64, tag = "6")]
+    pub participants_count: u64,
+    /// Planned start
+    #[prost(string, tag = "7")]
+    pub start_at: String,
+    /// Actual start
+    #[prost(string, tag = "8")]
+    pub started_at: String,
+    /// Creation time
+    #[prost(string, tag = "9")]
+    pub created_at: String,
+    /// Last update time
+    #[prost(string, tag = "10")]
+    pub updated_at: String,
+    /// Web page of the tournament
+    #[prost(string, tag = "11")]
+    pub full_challonge_url: String,
+    /// Live bracket image
+    #[prost(string, tag = "12")]
+    pub live_image_url: String,
+    /// Game name
+    #[prost(string, tag = "13")]
+    pub game_name: String,
+}
+
+/// Protobuf `Participant` message.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProtoParticipant {
+    /// Id
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    /// Id of the tournament
+    #[prost(uint64, tag = "2")]
+    pub tournament_id: u64,
+    /// Display name
+    #[prost(string, tag = "3")]
+    pub name: String,
+    /// Seed
+    #[prost(uint64, tag = "4")]
+    pub seed: u64,
+    /// Is the participant active
+    #[prost(bool, tag = "5")]
+    pub active: bool,
+    /// Misc field
+    #[prost(string, tag = "6")]
+    pub misc: String,
+    /// Final rank, 0 if not ranked yet
+    #[prost(uint64, tag = "7")]
+    pub final_rank: u64,
+    /// Check-in time
+    #[prost(string, tag = "8")]
+    pub checked_in_at: String,
+    /// Creation time
+    #[prost(string, tag = "9")]
+    pub created_at: String,
+    /// Last update time
+    #[prost(string, tag = "10")]
+    pub updated_at: String,
+}
+
+/// Protobuf `Match` message.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProtoMatch {
+    /// Id
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    /// Id of the tournament, 0 if the tournament is only known by url
+    #[prost(uint64, tag = "2")]
+    pub tournament_id: u64,
+    /// Human readable identifier
+    #[prost(string, tag = "3")]
+    pub identifier: String,
+    /// Round
+    #[prost(uint64, tag = "4")]
+    pub round: u64,
+    /// State
+    #[prost(string, tag = "5")]
+    pub state: String,
+    /// First player, 0 if not known yet
+    #[prost(uint64, tag = "6")]
+    pub player1_id: u64,
+    /// Second player, 0 if not known yet
+    #[prost(uint64, tag = "7")]
+    pub player2_id: u64,
+    /// Winner, 0 if there is none yet
+    #[prost(uint64, tag = "8")]
+    pub winner_id: u64,
+    /// Loser, 0 if there is none yet
+    #[prost(uint64, tag = "9")]
+    pub loser_id: u64,
+    /// Scores
+    #[prost(string, tag = "10")]
+    pub scores_csv: String,
+    /// Start time
+    #[prost(string, tag = "11")]
+    pub started_at: String,
+    /// Creation time
+    #[prost(string, tag = "12")]
+    pub created_at: String,
+    /// Last update time
+    #[prost(string, tag = "13")]
+    pub updated_at: String,
+}
+
+/// Protobuf `Event` message.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProtoEvent {
+    /// The event
+    #[prost(oneof = "EventKind", tags = "1, 2, 3, 4, 5")]
+    pub event: Option<EventKind>,
+}
+
+/// The `event` oneof of the `Event` message.
+#[derive(Clone, PartialEq, ::prost::Oneof)]
+pub enum EventKind {
+    /// Tournament attributes were updated
+    #[prost(message, tag = "1")]
+    TournamentUpdated(ProtoTournament),
+    /// A new participant has signed up
+    #[prost(message, tag = "2")]
+    SignupReceived(ProtoParticipant),
+    /// A participant was removed, with its id
+    #[prost(uint64, tag = "3")]
+    ParticipantRemoved(u64),
+    /// The signup cap was reached
+    #[prost(bool, tag = "4")]
+    SignupCapReached(bool),
+    /// A match was created or updated
+    #[prost(message, tag = "5")]
+    MatchUpdated(ProtoMatch),
+}
+
+fn timestamp(t: Option<&ChallongeDateTime>) -> String {
+    t.map_or(String::new(), |t| t.to_string())
+}
+
+fn numeric_id(id: &TournamentId) -> u64 {
+    match *id {
+        TournamentId::Id(id) => id,
+        TournamentId::Url(..) => 0,
+    }
+}
+
+fn participant_id(id: Option<&ParticipantId>) -> u64 {
+    id.map_or(0, |id| id.0)
+}
+
+impl<'a> From<&'a Tournament> for ProtoTournament {
+    fn from(t: &'a Tournament) -> ProtoTournament {
+        ProtoTournament {
+            id: numeric_id(&t.id),
+            name: t.name.clone(),
+            url: t.url.clone(),
+            state: t.state.to_string(),
+            tournament_type: t.tournament_type.to_string(),
+            participants_count: t.participants_count,
+            start_at: timestamp(t.start_at.as_ref()),
+            started_at: timestamp(t.started_at.as_ref()),
+            created_at: timestamp(Some(&t.created_at)),
+            updated_at: timestamp(Some(&t.updated_at)),
+            full_challonge_url: t.full_challonge_url.clone(),
+            live_image_url: t.live_image_url.clone(),
+            game_name: t.game_name.clone(),
+        }
+    }
+}
+
+impl<'a> From<&'a Participant> for ProtoParticipant {
+    fn from(p: &'a Participant) -> ProtoParticipant {
+        ProtoParticipant {
+            id: p.id.0,
+            tournament_id: p.tournament_id,
+            name: p.display_name().to_owned(),
+            seed: p.seed,
+            active: p.active,
+            misc: p.misc.clone(),
+            final_rank: p.final_rank.unwrap_or(0),
+            checked_in_at: timestamp(p.checked_in_at.as_ref()),
+            created_at: timestamp(Some(&p.created_at)),
+            updated_at: timestamp(Some(&p.updated_at)),
+        }
+    }
+}
+
+impl<'a> From<&'a Match> for ProtoMatch {
+    fn from(m: &'a Match) -> ProtoMatch {
+        ProtoMatch {
+            id: m.id.0,
+            tournament_id: numeric_id(&m.tournament_id),
+            identifier: m.identifier.clone(),
+            round: m.round,
+            state: m.state.to_string(),
+            player1_id: participant_id(m.player1.id.as_ref()),
+            player2_id: participant_id(m.player2.id.as_ref()),
+            winner_id: participant_id(m.winner_id.as_ref()),
+            loser_id: participant_id(m.loser_id.as_ref()),
+            scores_csv: m.scores_csv.to_string(),
+            started_at: timestamp(m.started_at.as_ref()),
+            created_at: timestamp(Some(&m.created_at)),
+            updated_at: timestamp(Some(&m.updated_at)),
+        }
+    }
+}
+
+impl<'a> From<&'a WatcherEvent> for ProtoEvent {
+    fn from(e: &'a WatcherEvent) -> ProtoEvent {
+        let kind = match *e {
+            WatcherEvent::TournamentUpdated(ref t) => EventKind::TournamentUpdated(t.into()),
+            WatcherEvent::SignupReceived(ref p) => EventKind::SignupReceived(p.into()),
+            WatcherEvent::ParticipantRemoved(ref id) => EventKind::ParticipantRemoved(id.0),
+            WatcherEvent::SignupCapReached => EventKind::SignupCapReached(true),
+            WatcherEvent::MatchUpdated(ref m) => EventKind::MatchUpdated(m.into()),
+        };
+        ProtoEvent { event: Some(kind) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use prost::Message;
+    use proto::{EventKind, ProtoEvent, ProtoTournament};
+    use tournament::Tournament;
+    use watcher::Event;
+
+    #[test]
+    fn test_event_round_trip() {
+        let t = Tournament::decode(
+            &serde_json::from_str(include_str!("../benches/fixtures/tournament.json")).unwrap(),
+        )
+        .unwrap();
+        let event = ProtoEvent::from(&Event::TournamentUpdated(t.clone()));
+        let mut bytes = Vec::new();
+        event.encode(&mut bytes).unwrap();
+        let decoded = ProtoEvent::decode(&bytes[..]).unwrap();
+        match decoded.event {
+            Some(EventKind::TournamentUpdated(ref p)) => {
+                assert_eq!(*p, ProtoTournament::from(&t));
+                assert_eq!(p.name, t.name);
+                assert_eq!(p.updated_at, t.updated_at.to_string());
+            }
+            _ => assert!(false),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Client-side rate limiting with request priorities.
+//!
+//! A `RateLimiter` is a token bucket shared by every client it is attached to. When it is
+//! saturated, waiting requests are let through by priority, so interactive operations
+//! (e.g. score reports) are not starved behind background polling.
+
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Burst allowed by `Challonge::with_rate_limit`, in seconds worth of requests.
+pub const BURST_SECS: u32 = 10;
+
+/// Priority of the requests of a client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Background work such as watcher polls and archiving
+    Background,
+
+    /// Default priority
+    Normal,
+
+    /// Operations a person is waiting for, such as score reports
+    Interactive,
+}
+impl Priority {
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+impl Default for Priority {
+    fn default() -> Priority {
+        Priority::Normal
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    waiting: [usize; 3],
+}
+
+/// Token bucket limiting the number of requests per minute.
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_minute: u32,
+    burst: u32,
+    bucket: Mutex<Bucket>,
+    released: Condvar,
+}
+impl RateLimiter {
+    /// Creates a limiter allowing `per_minute` requests per minute, with bursts of up to `burst` requests.
+    pub fn new(per_minute: u32, burst: u32) -> RateLimiter {
+        let per_minute = per_minute.max(1);
+        let burst = burst.max(1);
+        RateLimiter {
+            per_minute: per_minute,
+            burst: burst,
+            bucket: Mutex::new(Bucket {
+                tokens: burst as f64,
+                refilled_at: Instant::now(),
+                waiting: [0; 3],
+            }),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Creates a limiter allowing `per_minute` requests per minute, with bursts of `BURST_SECS`
+    /// seconds worth of requests.
+    pub fn from_rate(per_minute: u32) -> RateLimiter {
+        RateLimiter::new(per_minute, per_minute.saturating_mul(BURST_SECS) / 60)
+    }
+
+    /// Returns the number of requests allowed per minute.
+    pub fn per_minute(&self) -> u32 {
+        self.per_minute
+    }
+
+    /// Returns the largest burst of requests.
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    /// Blocks until a request of the given priority may be sent.
+    /// Requests of a higher priority which are waiting at the same time go first.
+    pub fn acquire(&self, priority: Priority) {
+        let mut bucket = self.bucket.lock().unwrap();
+        bucket.waiting[priority.index()] += 1;
+        loop {
+            self.refill(&mut bucket);
+            if bucket.tokens >= 1.0 && !Self::outranked(&bucket, priority) {
+                bucket.tokens -= 1.0;
+                bucket.waiting[priority.index()] -= 1;
+                self.released.notify_all();
+                return;
+            }
+            let wait = self.time_to_token(&bucket);
+            bucket = self.released.wait_timeout(bucket, wait).unwrap().0;
+        }
+    }
+
+    /// Blocks until a request of the given priority may be sent, but no longer than `timeout`.
+    /// Returns `false` if no token was taken in time.
+    pub fn acquire_within(&self, priority: Priority, timeout: Duration) -> bool {
+        let until = Instant::now() + timeout;
+        let mut bucket = self.bucket.lock().unwrap();
+        bucket.waiting[priority.index()] += 1;
+        loop {
+            self.refill(&mut bucket);
+            if bucket.tokens >= 1.0 && !Self::outranked(&bucket, priority) {
+                bucket.tokens -= 1.0;
+                bucket.waiting[priority.index()] -= 1;
+                self.released.notify_all();
+                return true;
+            }
+            let now = Instant::now();
+            if now >= until {
+                bucket.waiting[priority.index()] -= 1;
+                self.released.notify_all();
+                return false;
+            }
+            let wait = self.time_to_token(&bucket).min(until - now);
+            bucket = self.released.wait_timeout(bucket, wait).unwrap().0;
+        }
+    }
+
+    /// Takes a token without waiting, returns `false` if the request may not be sent now.
+    pub fn try_acquire(&self, priority: Priority) -> bool {
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket);
+        if bucket.tokens >= 1.0 && !Self::outranked(&bucket, priority) {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn outranked(bucket: &Bucket, priority: Priority) -> bool {
+        bucket.waiting[priority.index() + 1..]
+            .iter()
+            .any(|&w| w > 0)
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at);
+        let seconds = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1e9;
+        bucket.tokens =
+            (bucket.tokens + seconds * self.per_minute as f64 / 60.0).min(self.burst as f64);
+        bucket.refilled_at = now;
+    }
+
+    fn time_to_token(&self, bucket: &Bucket) -> Duration {
+        let missing = (1.0 - bucket.tokens).max(0.0);
+        let nanos = missing * 60e9 / self.per_minute as f64;
+        Duration::from_millis(1) + Duration::from_nanos(nanos as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratelimit::{Priority, RateLimiter};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+    use Challonge;
+
+    #[test]
+    fn test_shared_by_clones() {
+        let c = Challonge::builder()
+            .credentials("user", "key")
+            .build()
+            .unwrap()
+            .with_rate_limit(120);
+        let polling = c.clone();
+        let limiter = c.rate_limiter.as_ref().unwrap();
+        assert_eq!((limiter.per_minute(), limiter.burst()), (120, 20));
+        assert!(Arc::ptr_eq(limiter, polling.rate_limiter.as_ref().unwrap()));
+    }
+
+    #[test]
+    fn test_priorities() {
+        let limiter = Arc::new(RateLimiter::new(60, 2));
+        assert!(limiter.try_acquire(Priority::Background));
+        assert!(limiter.try_acquire(Priority::Normal));
+        assert!(!limiter.try_acquire(Priority::Interactive));
+
+        // an interactive request waiting for a token outranks background requests
+        let waiting = limiter.clone();
+        let handle = thread::spawn(move || waiting.acquire(Priority::Interactive));
+        while limiter.bucket.lock().unwrap().waiting[2] == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        limiter.bucket.lock().unwrap().tokens = 1.0;
+        assert!(!limiter.try_acquire(Priority::Background));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_acquire_within() {
+        let limiter = RateLimiter::new(1, 1);
+        assert!(limiter.acquire_within(Priority::Normal, Duration::from_millis(10)));
+        assert!(!limiter.acquire_within(Priority::Normal, Duration::from_millis(10)));
+        assert_eq!(limiter.bucket.lock().unwrap().waiting, [0; 3]);
+    }
+}
@@ -0,0 +1,150 @@
+//! Redaction of personal data when models are serialized again.
+//!
+//! Applications exposing crate models over their own APIs should not leak participant emails,
+//! so serialization applies `RedactionPolicy::default()` unless a policy is given explicitly.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use anonymize::Anonymizer;
+
+/// What happens to a redacted field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Redaction {
+    /// The field is left out
+    Skip,
+
+    /// The value is replaced with its salted hash, so equal values still compare equal
+    Hash,
+}
+
+/// A set of json fields which are redacted when a model is serialized.
+#[derive(Debug, Clone)]
+pub struct RedactionPolicy {
+    fields: BTreeMap<String, Redaction>,
+    anonymizer: Anonymizer,
+}
+impl RedactionPolicy {
+    /// Creates a policy which redacts nothing.
+    pub fn none() -> RedactionPolicy {
+        RedactionPolicy {
+            fields: BTreeMap::new(),
+            anonymizer: Anonymizer::new(""),
+        }
+    }
+
+    /// Redacts a field.
+    pub fn field<'a, S: Into<String>>(&'a mut self, name: S, redaction: Redaction) -> &'a mut Self {
+        self.fields.insert(name.into(), redaction);
+        self
+    }
+
+    /// Keeps a field which would be redacted otherwise.
+    pub fn keep<'a>(&'a mut self, name: &str) -> &'a mut Self {
+        self.fields.remove(name);
+        self
+    }
+
+    /// Sets the salt of hashed fields.
+    pub fn salt<'a, S: Into<String>>(&'a mut self, salt: S) -> &'a mut Self {
+        self.anonymizer = Anonymizer::new(salt);
+        self
+    }
+
+    /// Returns how a field is redacted, if it is.
+    pub fn redaction(&self, name: &str) -> Option<&Redaction> {
+        self.fields.get(name)
+    }
+
+    /// Applies the policy to every object in a json document.
+    pub fn apply(&self, value: &mut Value) {
+        match *value {
+            Value::Object(ref mut map) => {
+                let keys: Vec<String> = map.keys().cloned().collect();
+                for key in keys {
+                    match self.fields.get(&key) {
+                        Some(&Redaction::Skip) => {
+                            map.remove(&key);
+                        }
+                        Some(&Redaction::Hash) => {
+                            let hashed = match map[&key] {
+                                Value::Null => Value::Null,
+                                Value::String(ref s) if s.is_empty() => {
+                                    Value::String(String::new())
+                                }
+                                Value::String(ref s) => Value::String(self.anonymizer.pseudonym(s)),
+                                ref other => {
+                                    Value::String(self.anonymizer.pseudonym(&other.to_string()))
+                                }
+                            };
+                            map.insert(key, hashed);
+                        }
+                        None => {
+                            if let Some(v) = map.get_mut(&key) {
+                                self.apply(v);
+                            }
+                        }
+                    }
+                }
+            }
+            Value::Array(ref mut items) => {
+                for item in items.iter_mut() {
+                    self.apply(item);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+impl Default for RedactionPolicy {
+    /// Skips participant emails and their hashes, hashes usernames and strips `misc`.
+    fn default() -> RedactionPolicy {
+        let mut policy = RedactionPolicy::none();
+        policy
+            .field("invite_email", Redaction::Skip)
+            .field("email_hash", Redaction::Skip)
+            .field("challonge_email_address_verified", Redaction::Skip)
+            .field(
+                "display_name_with_invitation_email_address",
+                Redaction::Skip,
+            )
+            .field("challonge_username", Redaction::Hash)
+            .field("username", Redaction::Hash)
+            .field("misc", Redaction::Skip);
+        policy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use redaction::{Redaction, RedactionPolicy};
+
+    #[test]
+    fn test_redaction_policy() {
+        let source = r#"{"participant": {"name": "Bob", "invite_email": "bob@example.com",
+                         "username": "bob", "misc": "user:12", "seed": 1}}"#;
+        let mut value: serde_json::Value = serde_json::from_str(source).unwrap();
+        RedactionPolicy::default().apply(&mut value);
+        let p = value.as_object().unwrap().get("participant").unwrap();
+        let p = p.as_object().unwrap();
+        assert!(p.get("invite_email").is_none());
+        assert!(p.get("misc").is_none());
+        assert_eq!(p.get("name").unwrap().as_string(), Some("Bob"));
+        assert!(p
+            .get("username")
+            .unwrap()
+            .as_string()
+            .unwrap()
+            .starts_with("anon-"));
+
+        let mut value: serde_json::Value = serde_json::from_str(source).unwrap();
+        let mut policy = RedactionPolicy::default();
+        policy.keep("misc").field("name", Redaction::Skip);
+        policy.apply(&mut value);
+        let p = value.as_object().unwrap().get("participant").unwrap();
+        assert!(p.as_object().unwrap().get("name").is_none());
+        assert!(p.as_object().unwrap().get("misc").is_some());
+    }
+}
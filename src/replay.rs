@@ -0,0 +1,144 @@
+//! Replay of a recorded event journal.
+//!
+//! Feeds journal entries back through an event channel keeping the gaps between them,
+//! so overlays and bots can be developed against a realistic event stream without a live tournament.
+
+use std::io::BufRead;
+use std::sync::mpsc::Sender;
+use std::time::Duration as StdDuration;
+
+use clock::{Clock, SystemClock};
+use datetime::ChallongeDateTime;
+use error::Error;
+use journal::{read_journal, JournalEntry};
+use watcher::Event;
+
+/// Speed of a replay.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Speed {
+    /// Events are sent with the gaps they were recorded with
+    RealTime,
+
+    /// Gaps between events are divided by the factor, e.g. `Accelerated(60.0)` replays an hour in a minute
+    Accelerated(f64),
+
+    /// Events are sent without waiting
+    Instant,
+}
+impl Speed {
+    fn scale(&self, gap: StdDuration) -> StdDuration {
+        let millis = gap.as_secs() * 1000 + (gap.subsec_nanos() / 1_000_000) as u64;
+        match *self {
+            Speed::RealTime => gap,
+            Speed::Accelerated(factor) if factor > 0.0 => {
+                StdDuration::from_millis((millis as f64 / factor) as u64)
+            }
+            _ => StdDuration::from_millis(0),
+        }
+    }
+}
+
+/// Sends the events of `entries` to `sender`, sleeping on `clock` between them.
+///
+/// Returns the number of sent events. Stops early when the receiving end of the channel hangs up.
+pub fn replay(
+    entries: &[JournalEntry],
+    speed: Speed,
+    clock: &dyn Clock,
+    sender: &Sender<Event>,
+) -> usize {
+    let mut sent = 0;
+    let mut previous: Option<ChallongeDateTime> = None;
+    for entry in entries {
+        if let Some(previous) = previous {
+            let gap = (entry.at.0 - previous.0)
+                .to_std()
+                .unwrap_or(StdDuration::from_millis(0));
+            let delay = speed.scale(gap);
+            if delay > StdDuration::from_millis(0) {
+                clock.sleep(delay);
+            }
+        }
+        previous = Some(entry.at.clone());
+        if sender.send(entry.event.clone()).is_err() {
+            break;
+        }
+        sent += 1;
+    }
+    sent
+}
+
+/// Reads a journal and replays it on the system clock.
+/// # Example
+/// ```ignore
+/// extern crate challonge;
+///
+/// use self::challonge::replay::{replay_journal, Speed};
+/// use std::fs::File;
+/// use std::io::BufReader;
+/// use std::sync::mpsc::channel;
+/// use std::thread;
+///
+/// let (sender, receiver) = channel();
+/// thread::spawn(move || {
+///     let journal = BufReader::new(File::open("weekly-42.jsonl").unwrap());
+///     replay_journal(journal, Speed::Accelerated(30.0), &sender).unwrap();
+/// });
+/// for event in receiver {
+///     println!("{:?}", event);
+/// }
+/// ```
+pub fn replay_journal<R: BufRead>(
+    reader: R,
+    speed: Speed,
+    sender: &Sender<Event>,
+) -> Result<usize, Error> {
+    let entries = try!(read_journal(reader));
+    Ok(replay(&entries, speed, &SystemClock, sender))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::*;
+    use clock::{Clock, ManualClock};
+    use datetime::ChallongeDateTime;
+    use journal::JournalEntry;
+    use participants::ParticipantId;
+    use replay::{replay, Speed};
+    use std::sync::mpsc::channel;
+    use tournament::TournamentId;
+    use watcher::Event;
+
+    #[test]
+    fn test_accelerated_replay() {
+        let entry = |at: &str, event: Event| JournalEntry {
+            at: ChallongeDateTime::parse(at).unwrap(),
+            tournament: TournamentId::Id(1),
+            event: event,
+        };
+        let entries = vec![
+            entry(
+                "2016-04-01T12:00:00Z",
+                Event::ParticipantRemoved(ParticipantId(1)),
+            ),
+            entry(
+                "2016-04-01T12:10:00Z",
+                Event::ParticipantRemoved(ParticipantId(2)),
+            ),
+            entry("2016-04-01T13:00:00Z", Event::SignupCapReached),
+        ];
+        let start = UTC.ymd(2016, 4, 1).and_hms(12, 0, 0);
+        let clock = ManualClock::new(start);
+        let (sender, receiver) = channel();
+
+        assert_eq!(
+            replay(&entries, Speed::Accelerated(60.0), &clock, &sender),
+            3
+        );
+        assert_eq!(clock.now(), start + Duration::minutes(1));
+        assert_eq!(receiver.try_iter().count(), 3);
+
+        drop(receiver);
+        assert_eq!(replay(&entries, Speed::Instant, &clock, &sender), 0);
+    }
+}
@@ -0,0 +1,183 @@
+//! Automatic retries of failed requests.
+//!
+//! With `Challonge::with_retry_policy` the client retries requests which failed with a transient
+//! error (see `Error::is_retryable`: rate limiting, 5xx, maintenance, I/O failures and timeouts)
+//! after an exponentially growing, jittered backoff. The `Retry-After` of a 429 or a maintenance
+//! response is honored when it asks for a longer wait. Reads are always retried, mutations only if
+//! the policy allows it, since a mutation which failed with a timeout may have been applied anyway.
+
+use rand::Rng;
+use std::cmp;
+use std::time::Duration;
+
+use error::Error;
+use transport::Method;
+
+/// How requests which failed with a transient error are retried.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Number of attempts of a request, including the first one
+    pub max_attempts: u32,
+
+    /// Delay before the first retry, doubled before every next one
+    pub backoff: Duration,
+
+    /// Longest delay between two attempts
+    pub max_backoff: Duration,
+
+    /// Fraction of every delay which is randomized, from 0 (fixed delays) to 1 (full jitter)
+    pub jitter: f64,
+
+    /// Whether mutating requests (POST, PUT, DELETE) are retried as well
+    pub retry_mutations: bool,
+}
+impl RetryPolicy {
+    /// Creates a policy of 3 attempts of reads starting with a 500 ms backoff of up to 30 seconds,
+    /// half of which is randomized.
+    pub fn new() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            jitter: 0.5,
+            retry_mutations: false,
+        }
+    }
+
+    builder!(max_attempts, u32);
+    builder!(backoff, Duration);
+    builder!(max_backoff, Duration);
+    builder!(jitter, f64);
+    builder!(retry_mutations, bool);
+
+    /// Returns `true` if a request which failed with `error` on its `attempt` (counted from 1)
+    /// should be sent again.
+    pub fn should_retry(&self, method: Method, error: &Error, attempt: u32) -> bool {
+        let idempotent = method == Method::Get || method == Method::Head;
+        attempt < self.max_attempts && error.is_retryable() && (idempotent || self.retry_mutations)
+    }
+
+    /// Returns the delay before the retry after the failed `attempt` (counted from 1), without jitter.
+    pub fn backoff_for(&self, attempt: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u32::max_value());
+        self.backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |d| cmp::min(d, self.max_backoff))
+    }
+
+    /// Returns the jittered delay before the retry after the failed `attempt`.
+    /// A longer delay asked for by the error (the `Retry-After` of a 429 or a maintenance response) is kept.
+    pub fn delay<R: Rng>(&self, attempt: u32, error: &Error, rng: &mut R) -> Duration {
+        let base = self.backoff_for(attempt);
+        let millis = base.as_secs() * 1000 + u64::from(base.subsec_nanos() / 1_000_000);
+        let jitter = (millis as f64 * self.jitter.max(0.0).min(1.0)) as u64;
+        let delay = Duration::from_millis(millis - rng.gen_range(0, jitter + 1));
+        match *error {
+            Error::Maintenance {
+                retry_after: Some(after),
+            }
+            | Error::RateLimited {
+                retry_after: Some(after),
+            } => cmp::max(after, delay),
+            _ => delay,
+        }
+    }
+}
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use error::Error;
+    use rand;
+    use retry::RetryPolicy;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tournament::{TournamentId, TournamentIncludes};
+    use transport::{Method, Request, Response, Transport};
+    use Challonge;
+
+    #[test]
+    fn test_backoff() {
+        let mut policy = RetryPolicy::new();
+        policy
+            .backoff(Duration::from_secs(1))
+            .max_backoff(Duration::from_secs(5))
+            .jitter(0.0);
+        assert_eq!(policy.backoff_for(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff_for(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff_for(4), Duration::from_secs(5));
+        assert_eq!(policy.backoff_for(40), Duration::from_secs(5));
+
+        let unavailable = Error::status(503, None);
+        let mut rng = rand::thread_rng();
+        assert_eq!(
+            policy.delay(2, &unavailable, &mut rng),
+            Duration::from_secs(2)
+        );
+        let maintenance = Error::Maintenance {
+            retry_after: Some(Duration::from_secs(60)),
+        };
+        assert_eq!(
+            policy.delay(1, &maintenance, &mut rng),
+            Duration::from_secs(60)
+        );
+        let throttled = Error::RateLimited {
+            retry_after: Some(Duration::from_secs(10)),
+        };
+        assert_eq!(
+            policy.delay(1, &throttled, &mut rng),
+            Duration::from_secs(10)
+        );
+
+        policy.jitter(1.0);
+        assert!(policy.delay(3, &unavailable, &mut rng) <= Duration::from_secs(4));
+
+        assert!(policy.should_retry(Method::Get, &unavailable, 2));
+        assert!(!policy.should_retry(Method::Get, &unavailable, 3));
+        assert!(!policy.should_retry(Method::Post, &unavailable, 1));
+        assert!(!policy.should_retry(Method::Get, &Error::status(404, None), 1));
+        policy.retry_mutations(true);
+        assert!(policy.should_retry(Method::Post, &unavailable, 1));
+    }
+
+    struct Flaky(Arc<AtomicUsize>);
+
+    impl Transport for Flaky {
+        fn send(&self, _: &Request) -> Result<Response, Error> {
+            let status = match self.0.fetch_add(1, Ordering::SeqCst) {
+                0 => 502,
+                _ => 200,
+            };
+            Ok(Response {
+                status: status,
+                headers: Vec::new(),
+                body: include_bytes!("../benches/fixtures/tournament.json").to_vec(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_retry_transient_failure() {
+        let sent = Arc::new(AtomicUsize::new(0));
+        let mut policy = RetryPolicy::new();
+        policy.backoff(Duration::from_millis(1));
+        let c = Challonge::builder()
+            .credentials("user", "key")
+            .transport(Flaky(sent.clone()))
+            .retry_policy(policy)
+            .build()
+            .unwrap();
+        let t = c
+            .get_tournament(&TournamentId::Id(1086875), &TournamentIncludes::All)
+            .unwrap();
+        assert_eq!(t.id, TournamentId::Id(1086875));
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,72 @@
+//! JSON Schemas of the documents the crate writes (`schema` feature).
+//!
+//! The schemas live in the `schemas` directory of the repository and are embedded at build time,
+//! so consumers written in other languages can validate archives, journals and problem details.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use error::Error;
+
+/// Embedded schemas as `(file name, contents)`.
+pub const SCHEMAS: &'static [(&'static str, &'static str)] = &[
+    (
+        "archive-manifest.schema.json",
+        include_str!("../schemas/archive-manifest.schema.json"),
+    ),
+    (
+        "journal-entry.schema.json",
+        include_str!("../schemas/journal-entry.schema.json"),
+    ),
+    (
+        "match.schema.json",
+        include_str!("../schemas/match.schema.json"),
+    ),
+    (
+        "participant.schema.json",
+        include_str!("../schemas/participant.schema.json"),
+    ),
+    (
+        "problem.schema.json",
+        include_str!("../schemas/problem.schema.json"),
+    ),
+    (
+        "tournament.schema.json",
+        include_str!("../schemas/tournament.schema.json"),
+    ),
+];
+
+/// Returns the schema with the given file name.
+pub fn schema(name: &str) -> Option<&'static str> {
+    SCHEMAS.iter().find(|s| s.0 == name).map(|s| s.1)
+}
+
+/// Writes every schema into `dir`, creating it if needed.
+pub fn write_schemas<P: AsRef<Path>>(dir: P) -> Result<(), Error> {
+    let dir = dir.as_ref();
+    try!(fs::create_dir_all(dir));
+    for &(name, contents) in SCHEMAS {
+        let mut file = try!(fs::File::create(dir.join(name)));
+        try!(file.write_all(contents.as_bytes()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use schema::{schema, SCHEMAS};
+
+    #[test]
+    fn test_schemas_are_json() {
+        for &(name, contents) in SCHEMAS {
+            let value: serde_json::Value = serde_json::from_str(contents).unwrap();
+            let id = value.as_object().unwrap().get("$id").unwrap();
+            assert!(id.as_string().unwrap().ends_with(name));
+        }
+        assert!(schema("journal-entry.schema.json").is_some());
+        assert!(schema("missing.json").is_none());
+    }
+}
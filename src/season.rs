@@ -0,0 +1,172 @@
+//! Creation of a series of tournaments from a season plan.
+//!
+//! A plan describes a season once (e.g. 12 weeklies starting on a date) and `create_all`
+//! creates every tournament of it. Names and urls are computed from patterns where
+//! `{n}` is the number of the event and `{date}` its start date.
+
+use chrono::*;
+
+use datetime::ChallongeDateTime;
+use description::{DescriptionTemplate, TemplateVars};
+use error::Error;
+use tournament::{Tournament, TournamentCreate};
+use transaction;
+use Challonge;
+
+/// Declarative description of a season.
+#[derive(Debug, Clone)]
+pub struct SeasonPlan {
+    /// Name pattern, e.g. "Weekly #{n}"
+    pub name: String,
+
+    /// Url pattern, e.g. "weekly_{n}". Characters not allowed in urls are replaced with underscores
+    pub url: String,
+
+    /// Start of the first event
+    pub first_start: DateTime<UTC>,
+
+    /// Time between two events
+    pub interval: Duration,
+
+    /// Number of events
+    pub count: u32,
+
+    /// Number of the first event
+    pub first_number: u32,
+
+    /// Settings shared by every event
+    pub settings: TournamentCreate,
+
+    /// Description of every event, rendered with `number`, `date` and `time` added to `vars`
+    pub description: Option<DescriptionTemplate>,
+
+    /// Variables of the description template
+    pub vars: TemplateVars,
+}
+impl SeasonPlan {
+    /// Creates a plan of weekly events numbered from 1.
+    pub fn new<N: Into<String>, U: Into<String>>(
+        name: N,
+        url: U,
+        first_start: DateTime<UTC>,
+        count: u32,
+    ) -> SeasonPlan {
+        SeasonPlan {
+            name: name.into(),
+            url: url.into(),
+            first_start: first_start,
+            interval: Duration::weeks(1),
+            count: count,
+            first_number: 1,
+            settings: TournamentCreate::new(),
+            description: None,
+            vars: TemplateVars::new(),
+        }
+    }
+
+    builder!(interval, Duration);
+    builder!(first_number, u32);
+    builder!(settings, TournamentCreate);
+    builder!(vars, TemplateVars);
+
+    /// Sets the description template of the events.
+    pub fn description<'a>(&'a mut self, template: DescriptionTemplate) -> &'a mut Self {
+        self.description = Some(template);
+        self
+    }
+
+    /// Computes the tournaments of the season without creating them.
+    pub fn tournaments(&self) -> Result<Vec<TournamentCreate>, Error> {
+        (0..self.count)
+            .map(|i| {
+                let number = (self.first_number + i).to_string();
+                let start = self.first_start + self.interval * i as i32;
+                let date = start.format("%Y-%m-%d").to_string();
+                let fill = |pattern: &str| pattern.replace("{n}", &number).replace("{date}", &date);
+
+                let mut t = self.settings.clone();
+                t.name = fill(&self.name);
+                t.url = slug(&fill(&self.url));
+                t.start_at = Some(start);
+                if let Some(ref template) = self.description {
+                    let mut vars = self.vars.clone();
+                    vars.var("number", number.clone())
+                        .date(&ChallongeDateTime::from(start));
+                    t.description = try!(template.render(&vars));
+                }
+                Ok(t)
+            })
+            .collect()
+    }
+}
+
+/// Creates every tournament of the plan in order.
+///
+/// If a tournament can not be created, the ones created before it are deleted and
+/// `Error::RolledBack` is returned, so a failed run leaves no half-created season behind.
+/// # Example
+/// ```ignore
+/// extern crate challonge;
+/// extern crate chrono;
+///
+/// use self::challonge::Challonge;
+/// use self::challonge::season::{create_all, SeasonPlan};
+/// use self::chrono::*;
+///
+/// let c = Challonge::builder()
+///     .credentials("myusername", "myapikey")
+///     .build()
+///     .unwrap();
+/// let plan = SeasonPlan::new("Weekly #{n}", "weekly_{n}", UTC.ymd(2016, 4, 1).and_hms(18, 0, 0), 12);
+/// let tournaments = create_all(&c, &plan).unwrap();
+/// ```
+pub fn create_all(client: &Challonge, plan: &SeasonPlan) -> Result<Vec<Tournament>, Error> {
+    let planned = try!(plan.tournaments());
+    transaction::run(client, |tx| {
+        planned
+            .iter()
+            .map(|t| tx.create_tournament(t))
+            .collect::<Result<Vec<Tournament>, Error>>()
+    })
+}
+
+/// Makes a valid tournament url: lowercase letters, digits and underscores.
+fn slug(url: &str) -> String {
+    url.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::*;
+    use description::DescriptionTemplate;
+    use season::SeasonPlan;
+
+    #[test]
+    fn test_season_plan() {
+        let mut plan = SeasonPlan::new(
+            "Weekly #{n} ({date})",
+            "Weekly-{n}",
+            UTC.ymd(2016, 4, 1).and_hms(18, 0, 0),
+            3,
+        );
+        plan.first_number(10)
+            .description(DescriptionTemplate::new("Event {{number}} on {{date}}"));
+        let tournaments = plan.tournaments().unwrap();
+        assert_eq!(tournaments.len(), 3);
+        assert_eq!(tournaments[0].name, "Weekly #10 (2016-04-01)");
+        assert_eq!(tournaments[2].url, "weekly_12");
+        assert_eq!(
+            tournaments[2].start_at,
+            Some(UTC.ymd(2016, 4, 15).and_hms(18, 0, 0))
+        );
+        assert_eq!(tournaments[1].description, "Event 11 on 2016-04-08");
+    }
+}
@@ -0,0 +1,71 @@
+//! Local seeding helpers.
+//!
+//! Every helper takes the random number generator as a parameter,
+//! so event scripts can be made reproducible by using a seeded generator.
+
+use rand::Rng;
+
+use participants::{Index, ParticipantCreate, ParticipantId, ParticipantUpdate};
+
+/// Shuffles participants and numbers their seeds from 1 in the new order.
+pub fn shuffle<R: Rng>(participants: &mut [ParticipantCreate], rng: &mut R) {
+    rng.shuffle(participants);
+    for (i, p) in participants.iter_mut().enumerate() {
+        p.seed = i as u64 + 1;
+    }
+}
+
+/// Returns seed updates which put the active participants of the index into a random order.
+/// Updates are ordered by the new seed, so they can be applied one by one.
+pub fn shuffled_seeds<R: Rng>(
+    index: &Index,
+    rng: &mut R,
+) -> Vec<(ParticipantId, ParticipantUpdate)> {
+    let mut ids: Vec<ParticipantId> = index
+        .0
+        .iter()
+        .filter(|p| p.active && !p.on_waiting_list)
+        .map(|p| p.id.clone())
+        .collect();
+    rng.shuffle(&mut ids);
+    ids.into_iter()
+        .enumerate()
+        .map(|(i, id)| {
+            let mut update = ParticipantUpdate::new();
+            update.seed(i as u64 + 1);
+            (id, update)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use participants::ParticipantCreate;
+    use rand::{SeedableRng, XorShiftRng};
+    use seeding::shuffle;
+
+    fn participants() -> Vec<ParticipantCreate> {
+        (0..16)
+            .map(|i| {
+                let mut p = ParticipantCreate::new();
+                p.name(format!("Player {}", i));
+                p
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_shuffle_is_reproducible() {
+        let mut first = participants();
+        let mut second = participants();
+        shuffle(&mut first, &mut XorShiftRng::from_seed([1, 2, 3, 4]));
+        shuffle(&mut second, &mut XorShiftRng::from_seed([1, 2, 3, 4]));
+
+        let names = |v: &Vec<ParticipantCreate>| -> Vec<Option<String>> {
+            v.iter().map(|p| p.name.clone()).collect()
+        };
+        assert_eq!(names(&first), names(&second));
+        let seeds: Vec<u64> = first.iter().map(|p| p.seed).collect();
+        assert_eq!(seeds, (1..17).collect::<Vec<u64>>());
+    }
+}
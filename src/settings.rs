@@ -0,0 +1,116 @@
+//! Cache of tournament settings.
+//!
+//! Helpers like `create_participant_bulk_capped` or `create_attachment` need a few settings of the
+//! tournament (signup cap, whether attachments are accepted, its url) and used to fetch the whole
+//! tournament on every call. With `Challonge::with_settings_cache` the fetched tournament is kept
+//! for a while and shared by the clones of the client. Calls of the client which change a tournament,
+//! its participants or its matches drop its entry; changes made elsewhere (e.g. on the website) are seen once the entry expires or
+//! after `Challonge::invalidate_tournament_settings`.
+
+use chrono::{DateTime, Duration, UTC};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration as StdDuration;
+
+use tournament::{Tournament, TournamentId};
+
+/// Tournaments of a client keyed by id, kept for a limited time.
+#[derive(Debug)]
+pub struct SettingsCache {
+    ttl: StdDuration,
+    entries: Mutex<HashMap<String, (DateTime<UTC>, Tournament)>>,
+}
+impl SettingsCache {
+    /// Creates an empty cache keeping tournaments for `ttl`.
+    pub fn new(ttl: StdDuration) -> SettingsCache {
+        SettingsCache {
+            ttl: ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns how long tournaments are kept.
+    pub fn ttl(&self) -> StdDuration {
+        self.ttl
+    }
+
+    /// Returns the tournament cached under `id` unless it expired at `now`.
+    pub fn get(&self, id: &TournamentId, now: DateTime<UTC>) -> Option<Tournament> {
+        let ttl = Duration::from_std(self.ttl).unwrap_or(Duration::max_value());
+        let mut entries = self.entries.lock().unwrap();
+        let key = id.to_string();
+        let expired = match entries.get(&key) {
+            Some(&(fetched_at, ref tournament)) if now - fetched_at < ttl => {
+                return Some(tournament.clone())
+            }
+            Some(_) => true,
+            None => false,
+        };
+        if expired {
+            entries.remove(&key);
+        }
+        None
+    }
+
+    /// Caches a tournament fetched at `now` under `id` and under its numeric id.
+    pub fn insert(&self, id: &TournamentId, tournament: &Tournament, now: DateTime<UTC>) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(id.to_string(), (now, tournament.clone()));
+        entries.insert(tournament.id.to_string(), (now, tournament.clone()));
+    }
+
+    /// Drops every entry of the tournament, whether it was cached by id or by url.
+    pub fn invalidate(&self, id: &TournamentId) {
+        let key = id.to_string();
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|k, &mut (_, ref t)| *k != key && !is_same(id, t));
+    }
+
+    /// Drops all entries.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+fn is_same(id: &TournamentId, tournament: &Tournament) -> bool {
+    match *id {
+        TournamentId::Id(_) => tournament.id == *id,
+        TournamentId::Url(_, ref url) => tournament.url == *url,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use chrono::*;
+    use settings::SettingsCache;
+    use std::time::Duration as StdDuration;
+    use tournament::{Tournament, TournamentId};
+
+    #[test]
+    fn test_settings_cache() {
+        let t = Tournament::decode(
+            &serde_json::from_str(include_str!("../benches/fixtures/tournament.json")).unwrap(),
+        )
+        .unwrap();
+        let now = UTC.ymd(2016, 4, 1).and_hms(12, 0, 0);
+        let by_url = TournamentId::Url(String::new(), t.url.clone());
+        let cache = SettingsCache::new(StdDuration::from_secs(60));
+
+        cache.insert(&by_url, &t, now);
+        assert!(cache.get(&by_url, now).is_some());
+        assert!(cache.get(&t.id, now + Duration::seconds(59)).is_some());
+        assert!(cache.get(&t.id, now + Duration::seconds(60)).is_none());
+
+        // an update by id drops the entry cached by url as well
+        cache.insert(&by_url, &t, now);
+        cache.invalidate(&t.id);
+        assert!(cache.get(&by_url, now).is_none());
+        cache.insert(&t.id, &t, now);
+        cache.invalidate(&by_url);
+        assert!(cache.get(&t.id, now).is_none());
+    }
+}
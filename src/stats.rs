@@ -0,0 +1,286 @@
+//! Per-endpoint request statistics of a client.
+//!
+//! Every request sent by `Challonge` is counted under its endpoint, the method and the url path
+//! with tournament, participant, match and attachment ids replaced by `:id`
+//! (e.g. `PUT /tournaments/:id/matches/:id.json`). Statistics are shared between clones of the client.
+//!
+//! Next to the statistics, which can be reset, the recorder keeps `Metrics`: counters which only
+//! grow over the lifetime of the client, in the shape monitoring systems like Prometheus expect.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use transport::Method;
+
+/// Number of latest latencies kept per endpoint to compute percentiles.
+pub const LATENCY_SAMPLES: usize = 1000;
+
+/// Statistics of a single endpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointStats {
+    /// Number of requests sent
+    pub calls: u64,
+
+    /// Number of requests which failed or returned a non-success status
+    pub errors: u64,
+
+    /// Median latency
+    pub p50: Duration,
+
+    /// 95th percentile latency
+    pub p95: Duration,
+
+    /// Slowest latency
+    pub max: Duration,
+}
+
+/// Counters of a client since it was built, keyed by endpoint. They are never reset.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct Metrics {
+    /// Number of requests sent
+    pub requests: BTreeMap<String, u64>,
+
+    /// Number of requests which failed or returned a non-success status
+    pub errors: BTreeMap<String, u64>,
+
+    /// Cumulative latency of the requests
+    pub latency: BTreeMap<String, Duration>,
+}
+impl Metrics {
+    /// Returns the number of requests sent to all endpoints.
+    pub fn total_requests(&self) -> u64 {
+        self.requests.values().sum()
+    }
+
+    /// Returns the number of failed requests to all endpoints.
+    pub fn total_errors(&self) -> u64 {
+        self.errors.values().sum()
+    }
+
+    /// Returns the cumulative latency of the requests to all endpoints.
+    pub fn total_latency(&self) -> Duration {
+        self.latency
+            .values()
+            .fold(Duration::from_secs(0), |total, l| total + *l)
+    }
+
+    /// Renders the counters in the Prometheus text format as `challonge_requests_total`,
+    /// `challonge_errors_total` and `challonge_request_duration_seconds_total`, labelled by endpoint.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# TYPE challonge_requests_total counter\n");
+        for (endpoint, n) in &self.requests {
+            out.push_str(&format!(
+                "challonge_requests_total{{endpoint=\"{}\"}} {}\n",
+                endpoint, n
+            ));
+        }
+        out.push_str("# TYPE challonge_errors_total counter\n");
+        for (endpoint, n) in &self.errors {
+            out.push_str(&format!(
+                "challonge_errors_total{{endpoint=\"{}\"}} {}\n",
+                endpoint, n
+            ));
+        }
+        out.push_str("# TYPE challonge_request_duration_seconds_total counter\n");
+        for (endpoint, l) in &self.latency {
+            out.push_str(&format!(
+                "challonge_request_duration_seconds_total{{endpoint=\"{}\"}} {}\n",
+                endpoint,
+                l.as_secs() as f64 + f64::from(l.subsec_nanos()) / 1e9
+            ));
+        }
+        out
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    calls: u64,
+    errors: u64,
+    latencies: VecDeque<Duration>,
+    max: Duration,
+}
+impl Counters {
+    fn summary(&self) -> EndpointStats {
+        let mut sorted: Vec<Duration> = self.latencies.iter().cloned().collect();
+        sorted.sort();
+        EndpointStats {
+            calls: self.calls,
+            errors: self.errors,
+            p50: percentile(&sorted, 50),
+            p95: percentile(&sorted, 95),
+            max: self.max,
+        }
+    }
+}
+
+/// Collects request statistics.
+#[derive(Debug, Default)]
+pub struct StatsRecorder {
+    endpoints: Mutex<BTreeMap<String, Counters>>,
+    metrics: Mutex<Metrics>,
+}
+impl StatsRecorder {
+    /// Creates an empty recorder.
+    pub fn new() -> StatsRecorder {
+        StatsRecorder::default()
+    }
+
+    /// Records a request to `url` which took `latency`.
+    pub fn record(&self, method: Method, url: &str, latency: Duration, failed: bool) {
+        let endpoint = endpoint(method, url);
+        {
+            let mut metrics = self.metrics.lock().unwrap();
+            *metrics.requests.entry(endpoint.clone()).or_insert(0) += 1;
+            if failed {
+                *metrics.errors.entry(endpoint.clone()).or_insert(0) += 1;
+            }
+            let total = metrics
+                .latency
+                .entry(endpoint.clone())
+                .or_insert_with(|| Duration::from_secs(0));
+            *total += latency;
+        }
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let counters = endpoints.entry(endpoint).or_insert_with(Counters::default);
+        counters.calls += 1;
+        if failed {
+            counters.errors += 1;
+        }
+        if counters.latencies.len() == LATENCY_SAMPLES {
+            counters.latencies.pop_front();
+        }
+        counters.latencies.push_back(latency);
+        if latency > counters.max {
+            counters.max = latency;
+        }
+    }
+
+    /// Returns the statistics of every endpoint called so far, keyed by endpoint.
+    pub fn snapshot(&self) -> BTreeMap<String, EndpointStats> {
+        self.endpoints
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(endpoint, counters)| (endpoint.clone(), counters.summary()))
+            .collect()
+    }
+
+    /// Returns the counters collected since the recorder was created.
+    pub fn metrics(&self) -> Metrics {
+        self.metrics.lock().unwrap().clone()
+    }
+
+    /// Forgets every recorded request. Metrics are kept.
+    pub fn reset(&self) {
+        self.endpoints.lock().unwrap().clear();
+    }
+}
+
+/// Returns the endpoint of a request url, e.g. `GET /tournaments/:id.json`.
+pub fn endpoint(method: Method, url: &str) -> String {
+    let path = url.split('?').next().unwrap_or("");
+    let path = match path.find("://") {
+        Some(i) => {
+            let rest = &path[i + 3..];
+            // skip the host and the API version
+            let rest = rest.find('/').map_or("", |j| &rest[j + 1..]);
+            rest.find('/').map_or("", |j| &rest[j..])
+        }
+        None => path,
+    };
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let mut normalized = String::new();
+    for (i, segment) in segments.iter().enumerate() {
+        let (name, extension) = match segment.find('.') {
+            Some(j) => (&segment[..j], &segment[j..]),
+            None => (&segment[..], ""),
+        };
+        let is_id = i % 2 == 1
+            && (segments[i - 1] == "tournaments" || name.chars().all(|c| c.is_ascii_digit()));
+        normalized.push('/');
+        normalized.push_str(if is_id { ":id" } else { name });
+        normalized.push_str(extension);
+    }
+    format!("{} {}", method, normalized)
+}
+
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::from_secs(0);
+    }
+    let rank = (sorted.len() * percent + 99) / 100;
+    sorted[rank.max(1) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use stats::{endpoint, StatsRecorder};
+    use std::time::Duration;
+    use transport::Method;
+
+    #[test]
+    fn test_endpoint() {
+        let base = "https://api.challonge.com/v1";
+        assert_eq!(
+            endpoint(
+                Method::Put,
+                &format!("{}/tournaments/mysub-weekly1/matches/42.json", base)
+            ),
+            "PUT /tournaments/:id/matches/:id.json"
+        );
+        assert_eq!(
+            endpoint(
+                Method::Post,
+                &format!("{}/tournaments/5/participants/bulk_add.json", base)
+            ),
+            "POST /tournaments/:id/participants/bulk_add.json"
+        );
+        assert_eq!(
+            endpoint(Method::Get, &format!("{}/tournaments.json?state=all", base)),
+            "GET /tournaments.json"
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = StatsRecorder::new();
+        for ms in 1..101 {
+            stats.record(
+                Method::Get,
+                "https://api.challonge.com/v1/tournaments/1.json",
+                Duration::from_millis(ms),
+                ms % 10 == 0,
+            );
+        }
+        let snapshot = stats.snapshot();
+        let s = &snapshot["GET /tournaments/:id.json"];
+        assert_eq!(s.calls, 100);
+        assert_eq!(s.errors, 10);
+        assert_eq!(s.p50, Duration::from_millis(50));
+        assert_eq!(s.p95, Duration::from_millis(95));
+        assert_eq!(s.max, Duration::from_millis(100));
+
+        stats.reset();
+        assert!(stats.snapshot().is_empty());
+
+        // metrics survive the reset
+        stats.record(
+            Method::Post,
+            "https://api.challonge.com/v1/tournaments.json",
+            Duration::from_millis(500),
+            true,
+        );
+        let metrics = stats.metrics();
+        assert_eq!(metrics.total_requests(), 101);
+        assert_eq!(metrics.total_errors(), 11);
+        assert_eq!(metrics.requests["GET /tournaments/:id.json"], 100);
+        assert_eq!(metrics.total_latency(), Duration::from_millis(5550));
+        assert!(metrics
+            .to_prometheus()
+            .contains("challonge_errors_total{endpoint=\"POST /tournaments.json\"} 1\n"));
+    }
+}
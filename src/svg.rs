@@ -0,0 +1,265 @@
+//! SVG rendering of the bracket graph (`svg` feature).
+//!
+//! Rounds are laid out as columns. Open matches and the path of the champion are highlighted
+//! with the colors of the `Theme`.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use bracket::{Bracket, BracketNode};
+use matches::MatchState;
+
+/// Colors, fonts and sizes of a rendered bracket.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    /// Background color
+    pub background: String,
+
+    /// Fill color of match boxes
+    pub box_fill: String,
+
+    /// Border color of match boxes
+    pub box_stroke: String,
+
+    /// Color of player names and scores
+    pub text_color: String,
+
+    /// Color of lines between matches
+    pub line_color: String,
+
+    /// Border color of open matches
+    pub open_color: String,
+
+    /// Color of the matches and lines on the path of the champion
+    pub champion_color: String,
+
+    /// Font family of the text
+    pub font_family: String,
+
+    /// Font size of the text in pixels
+    pub font_size: u32,
+
+    /// Width of a match box in pixels
+    pub box_width: u32,
+
+    /// Height of a match box in pixels
+    pub box_height: u32,
+
+    /// Horizontal gap between rounds in pixels
+    pub column_gap: u32,
+
+    /// Vertical gap between matches in pixels
+    pub row_gap: u32,
+}
+impl Theme {
+    /// Creates a light theme.
+    pub fn new() -> Theme {
+        Theme {
+            background: "#ffffff".to_owned(),
+            box_fill: "#f4f4f4".to_owned(),
+            box_stroke: "#999999".to_owned(),
+            text_color: "#222222".to_owned(),
+            line_color: "#bbbbbb".to_owned(),
+            open_color: "#ff7324".to_owned(),
+            champion_color: "#e8b500".to_owned(),
+            font_family: "sans-serif".to_owned(),
+            font_size: 12,
+            box_width: 180,
+            box_height: 44,
+            column_gap: 40,
+            row_gap: 16,
+        }
+    }
+
+    builder_s!(background);
+    builder_s!(box_fill);
+    builder_s!(box_stroke);
+    builder_s!(text_color);
+    builder_s!(line_color);
+    builder_s!(open_color);
+    builder_s!(champion_color);
+    builder_s!(font_family);
+    builder!(font_size, u32);
+    builder!(box_width, u32);
+    builder!(box_height, u32);
+    builder!(column_gap, u32);
+    builder!(row_gap, u32);
+}
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::new()
+    }
+}
+
+/// Renders the bracket to an SVG document.
+pub fn render(bracket: &Bracket, theme: &Theme) -> String {
+    let mut columns: BTreeMap<u64, Vec<&BracketNode>> = BTreeMap::new();
+    for n in &bracket.nodes {
+        columns.entry(n.round).or_insert_with(Vec::new).push(n);
+    }
+    let mut positions = HashMap::new();
+    let mut rows = 0;
+    for (column, nodes) in columns.values().enumerate() {
+        for (row, n) in nodes.iter().enumerate() {
+            let x = 10 + column as u32 * (theme.box_width + theme.column_gap);
+            let y = 10 + row as u32 * (theme.box_height + theme.row_gap);
+            positions.insert(n.id, (x, y));
+        }
+        rows = ::std::cmp::max(rows, nodes.len() as u32);
+    }
+    let width = 20 + columns.len() as u32 * (theme.box_width + theme.column_gap);
+    let height = 20 + rows * (theme.box_height + theme.row_gap);
+    let champions = champion_path(bracket);
+
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"{}\" font-size=\"{}\">\n",
+        width,
+        height,
+        escape(&theme.font_family),
+        theme.font_size
+    );
+    out.push_str(&format!(
+        "  <rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+        escape(&theme.background)
+    ));
+    for e in &bracket.edges {
+        let (from, to) = match (positions.get(&e.from), positions.get(&e.to)) {
+            (Some(from), Some(to)) => (from, to),
+            _ => continue,
+        };
+        let color = if !e.loser && champions.contains(&e.from) && champions.contains(&e.to) {
+            &theme.champion_color
+        } else {
+            &theme.line_color
+        };
+        out.push_str(&format!(
+            "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\"{}/>\n",
+            from.0 + theme.box_width,
+            from.1 + theme.box_height / 2,
+            to.0,
+            to.1 + theme.box_height / 2,
+            escape(color),
+            if e.loser {
+                " stroke-dasharray=\"4 3\""
+            } else {
+                ""
+            }
+        ));
+    }
+    for n in &bracket.nodes {
+        let (x, y) = positions[&n.id];
+        let stroke = if champions.contains(&n.id) {
+            &theme.champion_color
+        } else if n.state == MatchState::Open {
+            &theme.open_color
+        } else {
+            &theme.box_stroke
+        };
+        out.push_str(&format!(
+            "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"3\" fill=\"{}\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+            x, y, theme.box_width, theme.box_height, escape(&theme.box_fill), escape(stroke)
+        ));
+        let names = [n.players.0.as_ref(), n.players.1.as_ref()];
+        for (i, name) in names.iter().enumerate() {
+            let name = match *name {
+                Some(name) => &name[..],
+                None => "TBD",
+            };
+            out.push_str(&format!(
+                "  <text x=\"{}\" y=\"{}\" fill=\"{}\">{}</text>\n",
+                x + 6,
+                y + theme.box_height * (i as u32 * 2 + 1) / 4 + theme.font_size / 3,
+                escape(&theme.text_color),
+                escape(name)
+            ));
+        }
+        let result = if !n.scores.is_empty() {
+            Some(&n.scores[..])
+        } else if n.forfeit.is_some() {
+            Some("FF")
+        } else {
+            None
+        };
+        if let Some(result) = result {
+            out.push_str(&format!(
+                "  <text x=\"{}\" y=\"{}\" fill=\"{}\" text-anchor=\"end\">{}</text>\n",
+                x + theme.box_width - 6,
+                y + theme.box_height / 2 + theme.font_size / 3,
+                escape(&theme.text_color),
+                escape(result)
+            ));
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Returns the matches won by the champion: the winner of the last completed match of the last round.
+fn champion_path(bracket: &Bracket) -> HashSet<u64> {
+    let last_round = bracket.nodes.iter().map(|n| n.round).max();
+    let champion = bracket
+        .nodes
+        .iter()
+        .filter(|n| Some(n.round) == last_round && n.state == MatchState::Complete)
+        .filter_map(|n| n.winner.as_ref())
+        .last();
+    match champion {
+        Some(champion) => bracket
+            .nodes
+            .iter()
+            .filter(|n| n.winner.as_ref() == Some(champion))
+            .map(|n| n.id)
+            .collect(),
+        None => HashSet::new(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use bracket::{Bracket, BracketEdge, BracketNode};
+    use matches::MatchState;
+    use participants::ParticipantId;
+    use svg::{render, Theme};
+
+    fn node(id: u64, round: u64, state: MatchState, winner: Option<u64>) -> BracketNode {
+        BracketNode {
+            id: id,
+            round: round,
+            identifier: id.to_string(),
+            players: (Some("Alice & Co".to_owned()), None),
+            scores: String::new(),
+            state: state,
+            winner: winner.map(ParticipantId),
+            forfeit: None,
+        }
+    }
+
+    #[test]
+    fn test_render_highlights() {
+        let mut theme = Theme::new();
+        theme.open_color("#00ff00").champion_color("#ffd700");
+        let bracket = Bracket {
+            nodes: vec![
+                node(1, 1, MatchState::Complete, Some(7)),
+                node(2, 1, MatchState::Open, None),
+                node(3, 2, MatchState::Complete, Some(7)),
+            ],
+            edges: vec![BracketEdge {
+                from: 1,
+                to: 3,
+                loser: false,
+            }],
+        };
+        let svg = render(&bracket, &theme);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains("Alice &amp; Co"));
+        assert_eq!(svg.matches("stroke=\"#ffd700\"").count(), 3);
+        assert_eq!(svg.matches("stroke=\"#00ff00\"").count(), 1);
+    }
+}
@@ -6,10 +6,17 @@ use chrono::*;
 use serde_json::Value;
 use std::collections::BTreeMap;
 use std::fmt;
+use std::num::NonZeroU64;
 use std::str::FromStr;
+use std::time::Duration as StdDuration;
 
+use datetime::ChallongeDateTime;
+use description::{self, DescriptionTemplate, TemplateVars};
 use error::Error;
-use util::{decode_array, into_map, remove};
+use images::{ImageFormat, ImageSize, LiveImage};
+use legacy;
+use participants::{ParticipantCreate, SignupCapReport};
+use util::{as_map, decode_array, enum_key, field};
 
 /// Tournament includes.
 #[derive(Debug, Clone)]
@@ -22,6 +29,9 @@ pub enum TournamentIncludes {
 
     /// Includes participants
     Participants,
+
+    /// Includes neither matches nor participants
+    Nothing,
 }
 
 /// Tournament ranking order.
@@ -64,6 +74,22 @@ impl fmt::Display for RankedBy {
         Ok(())
     }
 }
+impl FromStr for RankedBy {
+    type Err = ();
+    /// Parses a ranking order ignoring case and separators, e.g. `points-difference`.
+    fn from_str(s: &str) -> Result<RankedBy, ()> {
+        match &enum_key(s)[..] {
+            "match_wins" | "wins" => Ok(RankedBy::MatchWins),
+            "game_wins" | "games" => Ok(RankedBy::GameWins),
+            "points_scored" | "points" => Ok(RankedBy::PointsScored),
+            "points_difference" | "point_difference" | "points_diff" => {
+                Ok(RankedBy::PointsDifference)
+            }
+            "custom" => Ok(RankedBy::Custom),
+            _ => Err(()),
+        }
+    }
+}
 
 /// Tournament ID is an integer value or pair of strings (subdomain and tournament url)
 #[derive(Debug, Clone, PartialEq)]
@@ -92,6 +118,44 @@ impl fmt::Display for TournamentId {
     }
 }
 
+/// Category of a tournament's game, as named by Challonge.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Category(String);
+impl Category {
+    /// Creates a category from its name.
+    pub fn new<S: Into<String>>(name: S) -> Category {
+        Category(name.into())
+    }
+
+    /// Returns the category name.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn decode(value: &Value) -> Option<Category> {
+        value
+            .as_string()
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(Category::new)
+    }
+}
+impl fmt::Display for Category {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(&self.0)
+    }
+}
+impl<'a> From<&'a str> for Category {
+    fn from(name: &'a str) -> Category {
+        Category::new(name)
+    }
+}
+impl From<String> for Category {
+    fn from(name: String) -> Category {
+        Category(name)
+    }
+}
+
 /// Game points definition.
 #[derive(Debug, Clone, PartialEq)]
 pub struct GamePoints {
@@ -129,37 +193,34 @@ impl GamePoints {
     }
 
     /// Decode `GamePoints` from JSON.
-    pub fn decode(
-        mut map: &mut BTreeMap<String, Value>,
-        prefix: &str,
-    ) -> Result<GamePoints, Error> {
+    pub fn decode(map: &BTreeMap<String, Value>, prefix: &str) -> Result<GamePoints, Error> {
         let mut bye = None;
-        if let Ok(bye_pts) = remove(&mut map, &format!("{}pts_for_bye", prefix)) {
+        if let Ok(bye_pts) = field(map, &format!("{}pts_for_bye", prefix)) {
             if let Ok(b) = bye_pts.as_string().unwrap_or("").to_owned().parse::<f64>() {
                 bye = Some(b);
             }
         }
 
         Ok(GamePoints {
-            match_win: try!(remove(&mut map, &format!("{}pts_for_match_win", prefix)))
+            match_win: try!(field(map, &format!("{}pts_for_match_win", prefix)))
                 .as_string()
                 .unwrap_or("")
                 .to_owned()
                 .parse::<f64>()
                 .unwrap_or(0f64),
-            match_tie: try!(remove(&mut map, &format!("{}pts_for_match_tie", prefix)))
+            match_tie: try!(field(map, &format!("{}pts_for_match_tie", prefix)))
                 .as_string()
                 .unwrap_or("")
                 .to_owned()
                 .parse::<f64>()
                 .unwrap_or(0f64),
-            game_win: try!(remove(&mut map, &format!("{}pts_for_game_win", prefix)))
+            game_win: try!(field(map, &format!("{}pts_for_game_win", prefix)))
                 .as_string()
                 .unwrap_or("")
                 .to_owned()
                 .parse::<f64>()
                 .unwrap_or(0f64),
-            game_tie: try!(remove(&mut map, &format!("{}pts_for_game_tie", prefix)))
+            game_tie: try!(field(map, &format!("{}pts_for_game_tie", prefix)))
                 .as_string()
                 .unwrap_or("")
                 .to_owned()
@@ -168,6 +229,21 @@ impl GamePoints {
             bye: bye,
         })
     }
+
+    /// Encodes `GamePoints` into a JSON object in the API format.
+    pub fn encode(&self, map: &mut BTreeMap<String, Value>, prefix: &str) {
+        let mut put = |name: &str, v: Option<f64>| {
+            map.insert(
+                format!("{}{}", prefix, name),
+                v.map_or(Value::Null, |v| Value::String(v.to_string())),
+            );
+        };
+        put("pts_for_match_win", Some(self.match_win));
+        put("pts_for_match_tie", Some(self.match_tie));
+        put("pts_for_game_win", Some(self.game_win));
+        put("pts_for_game_tie", Some(self.game_tie));
+        put("pts_for_bye", self.bye);
+    }
 }
 impl Default for GamePoints {
     fn default() -> GamePoints {
@@ -226,6 +302,9 @@ pub struct TournamentCreate {
     /// Name of the game to which this tournament belongs to.
     pub game_name: Option<String>,
 
+    /// Category of the tournament (e.g. "table_tennis").
+    pub category: Option<Category>,
+
     /// Email registered Challonge participants when matches open up for them (default: false)
     pub notify_users_when_matches_open: bool,
 
@@ -248,6 +327,9 @@ pub struct TournamentCreate {
 
     /// This option only affects double elimination. null/blank (default) - give the winners bracket finalist two chances to beat the losers bracket finalist, 'single match' - create only one grand finals match, 'skip' - don't create a finals match between winners and losers bracket finalists
     pub grand_finals_modifier: Option<String>,
+
+    /// Scoring of predictions, the tournament can be opened for predictions when it is set
+    pub prediction_method: Option<PredictionMethod>,
 }
 impl TournamentCreate {
     /// Creates new `TournamentCreate` structure with default values.
@@ -267,6 +349,7 @@ impl TournamentCreate {
             show_rounds: false,
             private: false,
             game_name: None,
+            category: None,
             notify_users_when_matches_open: true,
             notify_users_when_the_tournament_ends: true,
             sequential_pairings: false,
@@ -274,6 +357,7 @@ impl TournamentCreate {
             start_at: None,
             check_in_duration: 60,
             grand_finals_modifier: None,
+            prediction_method: None,
         }
     }
 
@@ -283,6 +367,22 @@ impl TournamentCreate {
     builder_s!(subdomain);
     builder_s!(description);
     builder!(open_signup, bool);
+
+    /// Sets the description rendered from Markdown.
+    pub fn description_markdown<'a>(&'a mut self, markdown: &str) -> &'a mut Self {
+        self.description = description::markdown_to_html(markdown);
+        self
+    }
+
+    /// Sets the description rendered from a template, see `DescriptionTemplate`.
+    pub fn description_template<'a>(
+        &'a mut self,
+        template: &DescriptionTemplate,
+        vars: &TemplateVars,
+    ) -> Result<&'a mut Self, Error> {
+        self.description = try!(template.render(vars));
+        Ok(self)
+    }
     builder!(hold_third_place_match, bool);
     builder!(swiss_points, GamePoints);
     builder!(swiss_rounds, u64);
@@ -291,12 +391,24 @@ impl TournamentCreate {
     builder!(show_rounds, bool);
     builder!(private, bool);
     builder_so!(game_name);
+    builder_o!(category, Category);
     builder!(notify_users_when_matches_open, bool);
     builder!(notify_users_when_the_tournament_ends, bool);
     builder!(sequential_pairings, bool);
     builder!(signup_cap, u64);
     builder!(check_in_duration, u64);
     builder!(grand_finals_modifier, Option<String>);
+    builder_o!(prediction_method, PredictionMethod);
+
+    /// Returns the number of swiss rounds, `None` if Challonge picks it from the participant count.
+    pub fn swiss_round_count(&self) -> Option<NonZeroU64> {
+        NonZeroU64::new(self.swiss_rounds)
+    }
+
+    /// Returns the length of the check-in window.
+    pub fn check_in_window(&self) -> StdDuration {
+        StdDuration::from_secs(self.check_in_duration * 60)
+    }
 }
 
 /// Challonge `Tournament` definition.
@@ -310,11 +422,15 @@ pub struct Tournament {
 
     /// Tournament supports anonymous voting
     pub anonymous_voting: bool,
-    // category: ??,
-    // check_in_duration: ??,
+
+    /// Category of the tournament
+    pub category: Option<Category>,
+
+    /// Length of the participant check-in window in minutes, `None` if check-in is disabled
+    pub check_in_duration: Option<u64>,
     // completed_at: ??,
     /// Time when the tournament was created
-    pub created_at: DateTime<FixedOffset>,
+    pub created_at: ChallongeDateTime,
 
     /// `true` if created by the API
     pub created_by_api: bool,
@@ -362,7 +478,10 @@ pub struct Tournament {
 
     /// ???
     pub prediction_method: u64,
-    // <predictions-opened-at nil="true"/>
+
+    /// Time when predictions were opened
+    pub predictions_opened_at: Option<ChallongeDateTime>,
+
     /// ???
     pub private: bool,
 
@@ -386,13 +505,19 @@ pub struct Tournament {
 
     /// Show rounds on the web page
     pub show_rounds: bool,
-    // <signup-cap nil="true"/>
-    // <start-at nil="true"/>
-    //
+
+    /// Maximum number of participants in the bracket
+    pub signup_cap: Option<u64>,
+
+    /// The planned start time of the tournament
+    pub start_at: Option<ChallongeDateTime>,
+
     /// Time when the tournament was started
-    pub started_at: Option<DateTime<FixedOffset>>, //2015-01-19T16:57:17-05:00</started-at>
+    pub started_at: Option<ChallongeDateTime>, //2015-01-19T16:57:17-05:00</started-at>
     // <started-checking-in-at nil="true"/>
-    // <state>underway</state>
+    /// Current state of the tournament
+    pub state: TournamentStatus,
+
     /// Number of rounds in swiss system
     pub swiss_rounds: u64,
 
@@ -407,12 +532,12 @@ pub struct Tournament {
     pub tournament_type: TournamentType,
 
     /// Time when the tournament was updated last time
-    pub updated_at: DateTime<FixedOffset>,
+    pub updated_at: ChallongeDateTime,
 
     /// Tournament url
     pub url: String,
 
-    /// ???
+    /// Description of the tournament as it was entered, `description` is its rendered HTML
     pub description_source: String,
     // <subdomain nil="true"/>
     /// Full url to the web page of the tournament in challonge system
@@ -444,159 +569,479 @@ pub struct Tournament {
 }
 impl Tournament {
     /// Decodes `Tournament` from JSON.
-    pub fn decode(value: Value) -> Result<Tournament, Error> {
-        let mut value = try!(into_map(value));
-        let t = try!(remove(&mut value, "tournament"));
-        let mut tv = try!(into_map(t));
-
-        let mut started_at = None;
-        if let Some(dt_str) = try!(remove(&mut tv, "started_at")).as_string() {
-            if let Ok(dt) = DateTime::parse_from_rfc3339(dt_str) {
-                started_at = Some(dt);
-            }
-        }
+    pub fn decode(value: &Value) -> Result<Tournament, Error> {
+        let value = try!(as_map(value));
+        let t = try!(field(value, "tournament"));
+        let tv = try!(as_map(t));
 
         Ok(Tournament {
-            accept_attachments: try!(remove(&mut tv, "accept_attachments"))
+            accept_attachments: try!(field(tv, "accept_attachments"))
                 .as_boolean()
                 .unwrap_or(false),
-            allow_participant_match_reporting: try!(remove(
-                &mut tv,
-                "allow_participant_match_reporting"
-            ))
-            .as_boolean()
-            .unwrap_or(false),
-            anonymous_voting: try!(remove(&mut tv, "anonymous_voting"))
+            allow_participant_match_reporting: try!(field(tv, "allow_participant_match_reporting"))
                 .as_boolean()
                 .unwrap_or(false),
-            created_at: DateTime::parse_from_rfc3339(
-                try!(remove(&mut tv, "created_at"))
-                    .as_string()
-                    .unwrap_or(""),
-            )
-            .unwrap(),
-            created_by_api: try!(remove(&mut tv, "created_by_api"))
+            anonymous_voting: try!(field(tv, "anonymous_voting"))
                 .as_boolean()
                 .unwrap_or(false),
-            credit_capped: try!(remove(&mut tv, "credit_capped"))
+            category: tv.get("category").and_then(Category::decode),
+            check_in_duration: tv.get("check_in_duration").and_then(|d| d.as_u64()),
+            created_at: try!(ChallongeDateTime::decode_required(try!(field(
+                tv,
+                "created_at"
+            )))),
+            created_by_api: try!(field(tv, "created_by_api"))
                 .as_boolean()
                 .unwrap_or(false),
-            description: try!(remove(&mut tv, "description"))
-                .as_string()
+            credit_capped: try!(field(tv, "credit_capped"))
+                .as_boolean()
+                .unwrap_or(false),
+            description: tv
+                .get("description")
+                .and_then(|d| d.as_string())
                 .unwrap_or("")
                 .to_string(),
-            game_id: try!(remove(&mut tv, "game_id")).as_u64().unwrap_or(0),
-            id: TournamentId::Id(try!(remove(&mut tv, "id")).as_u64().unwrap_or(0)),
-            name: try!(remove(&mut tv, "name"))
+            game_id: try!(field(tv, "game_id")).as_u64().unwrap_or(0),
+            id: TournamentId::Id(try!(field(tv, "id")).as_u64().unwrap_or(0)),
+            name: try!(field(tv, "name"))
                 .as_string()
                 .unwrap_or("")
                 .to_string(),
-            group_stages_enabled: try!(remove(&mut tv, "group_stages_enabled"))
-                .as_boolean()
-                .unwrap_or(false),
-            hide_forum: try!(remove(&mut tv, "hide_forum"))
+            group_stages_enabled: try!(field(tv, "group_stages_enabled"))
                 .as_boolean()
                 .unwrap_or(false),
-            hide_seeds: try!(remove(&mut tv, "hide_seeds"))
+            hide_forum: try!(field(tv, "hide_forum")).as_boolean().unwrap_or(false),
+            hide_seeds: try!(field(tv, "hide_seeds")).as_boolean().unwrap_or(false),
+            hold_third_place_match: try!(field(tv, "hold_third_place_match"))
                 .as_boolean()
                 .unwrap_or(false),
-            hold_third_place_match: try!(remove(&mut tv, "hold_third_place_match"))
-                .as_boolean()
-                .unwrap_or(false),
-            max_predictions_per_user: try!(remove(&mut tv, "max_predictions_per_user"))
+            max_predictions_per_user: try!(field(tv, "max_predictions_per_user"))
                 .as_u64()
                 .unwrap_or(0),
-            notify_users_when_matches_open: try!(remove(&mut tv, "notify_users_when_matches_open"))
+            notify_users_when_matches_open: try!(field(tv, "notify_users_when_matches_open"))
                 .as_boolean()
                 .unwrap_or(false),
-            notify_users_when_the_tournament_ends: try!(remove(
-                &mut tv,
+            notify_users_when_the_tournament_ends: try!(field(
+                tv,
                 "notify_users_when_the_tournament_ends"
             ))
             .as_boolean()
             .unwrap_or(false),
-            open_signup: try!(remove(&mut tv, "open_signup"))
+            open_signup: try!(field(tv, "open_signup")).as_boolean().unwrap_or(false),
+            participants_count: try!(field(tv, "participants_count")).as_u64().unwrap_or(0),
+            prediction_method: try!(field(tv, "prediction_method")).as_u64().unwrap_or(0),
+            predictions_opened_at: try!(tv
+                .get("predictions_opened_at")
+                .map_or(Ok(None), ChallongeDateTime::decode)),
+            private: try!(field(tv, "private")).as_boolean().unwrap_or(false),
+            progress_meter: try!(field(tv, "progress_meter")).as_u64().unwrap_or(0),
+            swiss_points: GamePoints::decode(tv, "").unwrap(),
+            quick_advance: try!(field(tv, "quick_advance"))
                 .as_boolean()
                 .unwrap_or(false),
-            participants_count: try!(remove(&mut tv, "participants_count"))
-                .as_u64()
-                .unwrap_or(0),
-            prediction_method: try!(remove(&mut tv, "prediction_method"))
-                .as_u64()
-                .unwrap_or(0),
-            private: try!(remove(&mut tv, "private"))
+            require_score_agreement: try!(field(tv, "require_score_agreement"))
                 .as_boolean()
                 .unwrap_or(false),
-            progress_meter: try!(remove(&mut tv, "progress_meter"))
-                .as_u64()
-                .unwrap_or(0),
-            swiss_points: GamePoints::decode(&mut tv, "").unwrap(),
-            quick_advance: try!(remove(&mut tv, "quick_advance"))
+            round_robin_points: GamePoints::decode(tv, "rr_").unwrap(),
+            sequential_pairings: try!(field(tv, "sequential_pairings"))
                 .as_boolean()
                 .unwrap_or(false),
-            require_score_agreement: try!(remove(&mut tv, "require_score_agreement"))
-                .as_boolean()
-                .unwrap_or(false),
-            round_robin_points: GamePoints::decode(&mut tv, "rr_").unwrap(),
-            sequential_pairings: try!(remove(&mut tv, "sequential_pairings"))
-                .as_boolean()
-                .unwrap_or(false),
-            show_rounds: try!(remove(&mut tv, "show_rounds"))
-                .as_boolean()
-                .unwrap_or(false),
-            started_at: started_at,
-            swiss_rounds: try!(remove(&mut tv, "swiss_rounds")).as_u64().unwrap_or(0),
-            teams: try!(remove(&mut tv, "teams")).as_boolean().unwrap_or(false),
+            show_rounds: try!(field(tv, "show_rounds")).as_boolean().unwrap_or(false),
+            signup_cap: tv.get("signup_cap").and_then(|c| c.as_u64()),
+            start_at: try!(tv
+                .get("start_at")
+                .map_or(Ok(None), ChallongeDateTime::decode)),
+            started_at: try!(ChallongeDateTime::decode(try!(field(tv, "started_at")))),
+            state: TournamentStatus::from_str(
+                tv.get("state").and_then(|s| s.as_string()).unwrap_or(""),
+            )
+            .unwrap(),
+            swiss_rounds: try!(field(tv, "swiss_rounds")).as_u64().unwrap_or(0),
+            teams: try!(field(tv, "teams")).as_boolean().unwrap_or(false),
             tournament_type: TournamentType::from_str(
-                try!(remove(&mut tv, "tournament_type"))
-                    .as_string()
-                    .unwrap_or(""),
+                try!(field(tv, "tournament_type")).as_string().unwrap_or(""),
             )
             .unwrap_or(TournamentType::SingleElimination),
-            updated_at: DateTime::parse_from_rfc3339(
-                try!(remove(&mut tv, "updated_at")).as_string().unwrap(),
-            )
-            .unwrap(),
-            url: try!(remove(&mut tv, "url"))
-                .as_string()
-                .unwrap_or("")
-                .to_string(),
-            description_source: try!(remove(&mut tv, "description_source"))
-                .as_string()
+            updated_at: try!(ChallongeDateTime::decode_required(try!(field(
+                tv,
+                "updated_at"
+            )))),
+            url: try!(field(tv, "url")).as_string().unwrap_or("").to_string(),
+            description_source: tv
+                .get("description_source")
+                .and_then(|d| d.as_string())
                 .unwrap_or("")
                 .to_string(),
-            full_challonge_url: try!(remove(&mut tv, "full_challonge_url"))
+            full_challonge_url: try!(field(tv, "full_challonge_url"))
                 .as_string()
                 .unwrap_or("")
                 .to_string(),
-            live_image_url: try!(remove(&mut tv, "live_image_url"))
+            live_image_url: try!(field(tv, "live_image_url"))
                 .as_string()
                 .unwrap_or("")
                 .to_string(),
-            review_before_finalizing: try!(remove(&mut tv, "review_before_finalizing"))
+            review_before_finalizing: try!(field(tv, "review_before_finalizing"))
                 .as_boolean()
                 .unwrap_or(false),
-            accepting_predictions: try!(remove(&mut tv, "accepting_predictions"))
+            accepting_predictions: try!(field(tv, "accepting_predictions"))
                 .as_boolean()
                 .unwrap_or(false),
-            participants_locked: try!(remove(&mut tv, "participants_locked"))
+            participants_locked: try!(field(tv, "participants_locked"))
                 .as_boolean()
                 .unwrap_or(false),
-            game_name: try!(remove(&mut tv, "game_name"))
+            game_name: try!(field(tv, "game_name"))
                 .as_string()
                 .unwrap_or("")
                 .to_string(),
-            participants_swappable: try!(remove(&mut tv, "participants_swappable"))
+            participants_swappable: try!(field(tv, "participants_swappable"))
                 .as_boolean()
                 .unwrap_or(false),
-            team_convertable: try!(remove(&mut tv, "team_convertable"))
+            team_convertable: try!(field(tv, "team_convertable"))
                 .as_boolean()
                 .unwrap_or(false),
-            group_stages_were_started: try!(remove(&mut tv, "group_stages_were_started"))
+            group_stages_were_started: try!(field(tv, "group_stages_were_started"))
                 .as_boolean()
                 .unwrap_or(false),
         })
     }
+
+    /// Decodes `Tournament` from JSON, tolerating the missing fields and loose types of
+    /// tournaments created years ago, see the `legacy` module.
+    pub fn decode_legacy(value: &Value) -> Result<Tournament, Error> {
+        Tournament::decode(&try!(legacy::upgrade_tournament(value)))
+    }
+
+    /// Encodes the tournament into JSON in the API format, `Tournament::decode` reads it back.
+    pub fn encode(&self) -> Value {
+        fn s(v: &str) -> Value {
+            Value::String(v.to_owned())
+        }
+        fn b(v: bool) -> Value {
+            Value::Bool(v)
+        }
+        fn u(v: Option<u64>) -> Value {
+            v.map_or(Value::Null, Value::U64)
+        }
+        fn d(v: Option<&ChallongeDateTime>) -> Value {
+            v.map_or(Value::Null, |d| Value::String(d.to_string()))
+        }
+
+        let mut t = BTreeMap::new();
+        t.insert("accept_attachments".to_owned(), b(self.accept_attachments));
+        t.insert(
+            "allow_participant_match_reporting".to_owned(),
+            b(self.allow_participant_match_reporting),
+        );
+        t.insert("anonymous_voting".to_owned(), b(self.anonymous_voting));
+        t.insert(
+            "category".to_owned(),
+            self.category
+                .as_ref()
+                .map_or(Value::Null, |c| s(c.as_str())),
+        );
+        t.insert("check_in_duration".to_owned(), u(self.check_in_duration));
+        t.insert("created_at".to_owned(), d(Some(&self.created_at)));
+        t.insert("created_by_api".to_owned(), b(self.created_by_api));
+        t.insert("credit_capped".to_owned(), b(self.credit_capped));
+        t.insert("description".to_owned(), s(&self.description));
+        t.insert("game_id".to_owned(), u(Some(self.game_id)));
+        t.insert(
+            "group_stages_enabled".to_owned(),
+            b(self.group_stages_enabled),
+        );
+        t.insert("hide_forum".to_owned(), b(self.hide_forum));
+        t.insert("hide_seeds".to_owned(), b(self.hide_seeds));
+        t.insert(
+            "hold_third_place_match".to_owned(),
+            b(self.hold_third_place_match),
+        );
+        t.insert(
+            "id".to_owned(),
+            match self.id {
+                TournamentId::Id(id) => u(Some(id)),
+                ref url => s(&url.to_string()),
+            },
+        );
+        t.insert(
+            "max_predictions_per_user".to_owned(),
+            u(Some(self.max_predictions_per_user)),
+        );
+        t.insert("name".to_owned(), s(&self.name));
+        t.insert(
+            "notify_users_when_matches_open".to_owned(),
+            b(self.notify_users_when_matches_open),
+        );
+        t.insert(
+            "notify_users_when_the_tournament_ends".to_owned(),
+            b(self.notify_users_when_the_tournament_ends),
+        );
+        t.insert("open_signup".to_owned(), b(self.open_signup));
+        t.insert(
+            "participants_count".to_owned(),
+            u(Some(self.participants_count)),
+        );
+        t.insert(
+            "prediction_method".to_owned(),
+            u(Some(self.prediction_method)),
+        );
+        t.insert(
+            "predictions_opened_at".to_owned(),
+            d(self.predictions_opened_at.as_ref()),
+        );
+        t.insert("private".to_owned(), b(self.private));
+        t.insert("progress_meter".to_owned(), u(Some(self.progress_meter)));
+        self.swiss_points.encode(&mut t, "");
+        t.insert("quick_advance".to_owned(), b(self.quick_advance));
+        t.insert(
+            "require_score_agreement".to_owned(),
+            b(self.require_score_agreement),
+        );
+        self.round_robin_points.encode(&mut t, "rr_");
+        t.insert(
+            "sequential_pairings".to_owned(),
+            b(self.sequential_pairings),
+        );
+        t.insert("show_rounds".to_owned(), b(self.show_rounds));
+        t.insert("signup_cap".to_owned(), u(self.signup_cap));
+        t.insert("start_at".to_owned(), d(self.start_at.as_ref()));
+        t.insert("started_at".to_owned(), d(self.started_at.as_ref()));
+        t.insert("state".to_owned(), s(&self.state.to_string()));
+        t.insert("swiss_rounds".to_owned(), u(Some(self.swiss_rounds)));
+        t.insert("teams".to_owned(), b(self.teams));
+        t.insert(
+            "tournament_type".to_owned(),
+            s(&self.tournament_type.to_string()),
+        );
+        t.insert("updated_at".to_owned(), d(Some(&self.updated_at)));
+        t.insert("url".to_owned(), s(&self.url));
+        t.insert("description_source".to_owned(), s(&self.description_source));
+        t.insert("full_challonge_url".to_owned(), s(&self.full_challonge_url));
+        t.insert("live_image_url".to_owned(), s(&self.live_image_url));
+        t.insert(
+            "review_before_finalizing".to_owned(),
+            b(self.review_before_finalizing),
+        );
+        t.insert(
+            "accepting_predictions".to_owned(),
+            b(self.accepting_predictions),
+        );
+        t.insert(
+            "participants_locked".to_owned(),
+            b(self.participants_locked),
+        );
+        t.insert("game_name".to_owned(), s(&self.game_name));
+        t.insert(
+            "participants_swappable".to_owned(),
+            b(self.participants_swappable),
+        );
+        t.insert("team_convertable".to_owned(), b(self.team_convertable));
+        t.insert(
+            "group_stages_were_started".to_owned(),
+            b(self.group_stages_were_started),
+        );
+
+        let mut root = BTreeMap::new();
+        root.insert("tournament".to_owned(), Value::Object(t));
+        Value::Object(root)
+    }
+}
+
+impl Tournament {
+    /// Returns the description as plain text.
+    pub fn description_text(&self) -> String {
+        description::strip_html(&self.description)
+    }
+
+    /// Returns the description as it was entered, falling back to the plain text of the rendered one.
+    pub fn description_source_or_text(&self) -> String {
+        if self.description_source.is_empty() {
+            self.description_text()
+        } else {
+            self.description_source.clone()
+        }
+    }
+
+    /// Returns game metadata of the tournament.
+    pub fn game(&self) -> Game {
+        Game {
+            id: if self.game_id == 0 {
+                None
+            } else {
+                Some(self.game_id)
+            },
+            name: if self.game_name.is_empty() {
+                None
+            } else {
+                Some(self.game_name.clone())
+            },
+            category: self.category.clone(),
+        }
+    }
+
+    /// Returns number of free slots left before the signup cap is reached.
+    /// Returns `None` if the tournament has no signup cap.
+    pub fn remaining_slots(&self) -> Option<u64> {
+        self.signup_cap
+            .map(|cap| cap.saturating_sub(self.participants_count))
+    }
+
+    /// Returns the number of swiss rounds, `None` if Challonge picks it from the participant count.
+    pub fn swiss_round_count(&self) -> Option<NonZeroU64> {
+        NonZeroU64::new(self.swiss_rounds)
+    }
+
+    /// Returns the length of the check-in window, `None` if check-in is disabled.
+    pub fn check_in_window(&self) -> Option<StdDuration> {
+        self.check_in_duration
+            .and_then(NonZeroU64::new)
+            .map(|m| StdDuration::from_secs(m.get() * 60))
+    }
+
+    /// Returns time when check-in opens: the check-in window before the planned start.
+    pub fn check_in_opens_at(&self) -> Option<ChallongeDateTime> {
+        match (self.start_at, self.check_in_duration) {
+            (Some(start), Some(minutes)) if minutes > 0 => Some(ChallongeDateTime(
+                start.0 - Duration::minutes(minutes as i64),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Returns a variant of the live bracket image, `None` if the tournament has no live image.
+    pub fn live_image(&self, format: ImageFormat, size: ImageSize) -> Option<LiveImage> {
+        LiveImage::from_url(&self.live_image_url, format, size)
+    }
+
+    /// Returns the prediction scoring method of the tournament.
+    pub fn prediction_scoring(&self) -> PredictionMethod {
+        PredictionMethod::from(self.prediction_method)
+    }
+
+    /// Returns time when predictions close: predictions are accepted until the tournament starts.
+    pub fn predictions_close_at(&self) -> Option<ChallongeDateTime> {
+        self.started_at.or(self.start_at)
+    }
+
+    /// Returns `true` if predictions are accepted at `now`.
+    pub fn predictions_open(&self, now: &DateTime<UTC>) -> bool {
+        if !self.accepting_predictions || self.prediction_scoring() == PredictionMethod::Disabled {
+            return false;
+        }
+        let opened = self
+            .predictions_opened_at
+            .map_or(true, |o| o.with_timezone(&UTC) <= *now);
+        let closed = self
+            .predictions_close_at()
+            .map_or(false, |c| c.with_timezone(&UTC) <= *now);
+        opened && !closed
+    }
+
+    /// Returns time left until predictions close, if they are open at `now` and the close time is known.
+    pub fn predictions_time_left(&self, now: &DateTime<UTC>) -> Option<Duration> {
+        if !self.predictions_open(now) {
+            return None;
+        }
+        self.predictions_close_at()
+            .map(|c| c.with_timezone(&UTC) - *now)
+    }
+
+    /// Checks participants against the remaining slots of the tournament.
+    pub fn check_signup_cap(&self, participants: Vec<ParticipantCreate>) -> SignupCapReport {
+        SignupCapReport::split(participants, self.remaining_slots())
+    }
+}
+
+/// Prediction scoring method of a tournament.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PredictionMethod {
+    /// Predictions are disabled
+    Disabled,
+
+    /// Exponential scoring of predictions
+    Exponential,
+
+    /// Linear scoring of predictions
+    Linear,
+
+    /// Unknown scoring method
+    Other(u64),
+}
+impl PredictionMethod {
+    /// Returns the numeric code of the method used by the API.
+    pub fn code(&self) -> u64 {
+        match *self {
+            PredictionMethod::Disabled => 0,
+            PredictionMethod::Exponential => 1,
+            PredictionMethod::Linear => 2,
+            PredictionMethod::Other(m) => m,
+        }
+    }
+}
+impl From<u64> for PredictionMethod {
+    fn from(method: u64) -> PredictionMethod {
+        match method {
+            0 => PredictionMethod::Disabled,
+            1 => PredictionMethod::Exponential,
+            2 => PredictionMethod::Linear,
+            m => PredictionMethod::Other(m),
+        }
+    }
+}
+
+/// Game metadata of a tournament.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Game {
+    /// An id of the game in challonge system
+    pub id: Option<u64>,
+
+    /// Name of the game
+    pub name: Option<String>,
+
+    /// Category of the tournament
+    pub category: Option<Category>,
+}
+
+/// Filters of `Challonge::tournament_index_with`. Unset filters are not sent.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct TournamentIndexOptions {
+    /// State of the tournaments, `TournamentState::All` by default
+    pub state: TournamentState,
+
+    /// Type of the tournaments
+    pub tournament_type: Option<TournamentType>,
+
+    /// Only tournaments created on or after the date
+    pub created_after: Option<Date<Local>>,
+
+    /// Only tournaments created on or before the date
+    pub created_before: Option<Date<Local>>,
+
+    /// Subdomain of the tournaments, the subdomain of the client by default
+    pub subdomain: Option<String>,
+}
+impl TournamentIndexOptions {
+    /// Creates options requesting all tournaments.
+    pub fn new() -> TournamentIndexOptions {
+        TournamentIndexOptions {
+            state: TournamentState::All,
+            tournament_type: None,
+            created_after: None,
+            created_before: None,
+            subdomain: None,
+        }
+    }
+
+    builder!(state, TournamentState);
+    builder_o!(tournament_type, TournamentType);
+    builder_o!(created_after, Date<Local>);
+    builder_o!(created_before, Date<Local>);
+    builder_so!(subdomain);
+}
+impl Default for TournamentIndexOptions {
+    fn default() -> TournamentIndexOptions {
+        TournamentIndexOptions::new()
+    }
 }
 
 /// A list of tournaments of the account/organization.
@@ -604,13 +1049,94 @@ impl Tournament {
 pub struct Index(pub Vec<Tournament>);
 impl Index {
     /// Decodes tournament index from JSON.
-    pub fn decode(value: Value) -> Result<Index, Error> {
+    pub fn decode(value: &Value) -> Result<Index, Error> {
         Ok(Index(try!(decode_array(value, Tournament::decode))))
     }
+
+    /// Decodes tournament index from JSON, tolerating legacy tournaments.
+    pub fn decode_legacy(value: &Value) -> Result<Index, Error> {
+        Ok(Index(try!(decode_array(value, Tournament::decode_legacy))))
+    }
+
+    /// Returns tournaments updated at or after `since`, most recently updated first.
+    pub fn updated_since(&self, since: &DateTime<UTC>) -> Vec<&Tournament> {
+        let mut tournaments: Vec<&Tournament> = self
+            .0
+            .iter()
+            .filter(|t| t.updated_at.with_timezone(&UTC) >= *since)
+            .collect();
+        tournaments.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        tournaments
+    }
+
+    /// Groups tournaments by state, in lifecycle order.
+    pub fn group_by_state(&self) -> BTreeMap<TournamentStatus, Vec<&Tournament>> {
+        let mut groups = BTreeMap::new();
+        for t in &self.0 {
+            groups
+                .entry(t.state.clone())
+                .or_insert_with(Vec::new)
+                .push(t);
+        }
+        groups
+    }
+
+    /// Groups tournaments by type.
+    pub fn group_by_type(&self) -> BTreeMap<TournamentType, Vec<&Tournament>> {
+        let mut groups = BTreeMap::new();
+        for t in &self.0 {
+            groups
+                .entry(t.tournament_type.clone())
+                .or_insert_with(Vec::new)
+                .push(t);
+        }
+        groups
+    }
+
+    /// Counts tournaments by state and by type.
+    pub fn summary(&self) -> IndexSummary {
+        IndexSummary {
+            total: self.0.len(),
+            by_state: self
+                .group_by_state()
+                .into_iter()
+                .map(|(k, v)| (k, v.len()))
+                .collect(),
+            by_type: self
+                .group_by_type()
+                .into_iter()
+                .map(|(k, v)| (k, v.len()))
+                .collect(),
+        }
+    }
+
+    /// Returns tournaments whose name or url contains `query` (case-insensitive).
+    pub fn search(&self, query: &str) -> Vec<&Tournament> {
+        let query = query.to_lowercase();
+        self.0
+            .iter()
+            .filter(|t| {
+                t.name.to_lowercase().contains(&query) || t.url.to_lowercase().contains(&query)
+            })
+            .collect()
+    }
 }
 
-/// A type of a tournament.
+/// Number of tournaments in an index by state and by type.
 #[derive(Debug, Clone, PartialEq)]
+pub struct IndexSummary {
+    /// Total number of tournaments
+    pub total: usize,
+
+    /// Number of tournaments in every state, in lifecycle order
+    pub by_state: BTreeMap<TournamentStatus, usize>,
+
+    /// Number of tournaments of every type
+    pub by_type: BTreeMap<TournamentType, usize>,
+}
+
+/// A type of a tournament.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum TournamentType {
     /// [Single elimination system](https://en.wikipedia.org/wiki/Single-elimination_tournament)
     SingleElimination,
@@ -656,22 +1182,90 @@ impl fmt::Display for TournamentType {
 }
 impl FromStr for TournamentType {
     type Err = ();
+    /// Parses a tournament type ignoring case and separators. Abbreviations like `SE`, `DE`
+    /// and `RR` are accepted as well, since types are often typed in by users.
     fn from_str(s: &str) -> Result<TournamentType, ()> {
-        match s {
-            "single_elimination" => return Ok(TournamentType::SingleElimination),
-            "single elimination" => return Ok(TournamentType::SingleElimination),
-            "double_elimination" => return Ok(TournamentType::DoubleElimination),
-            "double elimination" => return Ok(TournamentType::DoubleElimination),
-            "round_robin" => return Ok(TournamentType::RoundRobin),
-            "round robin" => return Ok(TournamentType::RoundRobin),
-            "swiss" => return Ok(TournamentType::Swiss),
+        match &enum_key(s)[..] {
+            "single_elimination" | "single" | "se" => Ok(TournamentType::SingleElimination),
+            "double_elimination" | "double" | "de" => Ok(TournamentType::DoubleElimination),
+            "round_robin" | "roundrobin" | "rr" => Ok(TournamentType::RoundRobin),
+            "swiss" | "swiss_system" => Ok(TournamentType::Swiss),
             _ => Err(()),
         }
     }
 }
 
+/// State of a single tournament as reported by Challonge.
+/// Variants are declared in lifecycle order, so sorting by state sorts by progress.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TournamentStatus {
+    /// The tournament has not started yet
+    Pending,
+
+    /// The tournament is open for predictions and has not started yet
+    AcceptingPredictions,
+
+    /// The check-in window of the tournament is open
+    CheckingIn,
+
+    /// The check-in window is closed, the tournament waits to be started
+    CheckedIn,
+
+    /// The group stage of a two-stage tournament is in progress
+    GroupStagesUnderway,
+
+    /// The group stage of a two-stage tournament is over, the final stage has not started yet
+    GroupStagesFinalized,
+
+    /// The tournament is in progress
+    Underway,
+
+    /// All matches are played, the tournament waits to be finalized
+    AwaitingReview,
+
+    /// The tournament is finalized
+    Complete,
+
+    /// A state unknown to this library
+    Other(String),
+}
+impl fmt::Display for TournamentStatus {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TournamentStatus::Pending => fmt.write_str("pending"),
+            TournamentStatus::AcceptingPredictions => fmt.write_str("accepting_predictions"),
+            TournamentStatus::CheckingIn => fmt.write_str("checking_in"),
+            TournamentStatus::CheckedIn => fmt.write_str("checked_in"),
+            TournamentStatus::GroupStagesUnderway => fmt.write_str("group_stages_underway"),
+            TournamentStatus::GroupStagesFinalized => fmt.write_str("group_stages_finalized"),
+            TournamentStatus::Underway => fmt.write_str("underway"),
+            TournamentStatus::AwaitingReview => fmt.write_str("awaiting_review"),
+            TournamentStatus::Complete => fmt.write_str("complete"),
+            TournamentStatus::Other(ref s) => fmt.write_str(s),
+        }
+    }
+}
+impl FromStr for TournamentStatus {
+    type Err = ();
+    /// Parses a state ignoring case and separators, unknown states are kept as they are.
+    fn from_str(s: &str) -> Result<TournamentStatus, ()> {
+        Ok(match &enum_key(s)[..] {
+            "pending" => TournamentStatus::Pending,
+            "accepting_predictions" => TournamentStatus::AcceptingPredictions,
+            "checking_in" => TournamentStatus::CheckingIn,
+            "checked_in" => TournamentStatus::CheckedIn,
+            "group_stages_underway" => TournamentStatus::GroupStagesUnderway,
+            "group_stages_finalized" => TournamentStatus::GroupStagesFinalized,
+            "underway" => TournamentStatus::Underway,
+            "awaiting_review" => TournamentStatus::AwaitingReview,
+            "complete" => TournamentStatus::Complete,
+            _ => TournamentStatus::Other(s.to_owned()),
+        })
+    }
+}
+
 /// Current tournament state.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TournamentState {
     /// Tournament is in any state
     All,
@@ -684,6 +1278,21 @@ pub enum TournamentState {
 
     /// Tournament is finished
     Ended,
+
+    /// Tournament is open for predictions
+    AcceptingPredictions,
+
+    /// Check-in window of the tournament is open
+    CheckingIn,
+
+    /// Check-in window of the tournament is closed
+    CheckedIn,
+
+    /// Group stage of a two-stage tournament is in progress
+    GroupStagesUnderway,
+
+    /// Tournament waits to be finalized
+    AwaitingReview,
 }
 impl fmt::Display for TournamentState {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
@@ -700,19 +1309,57 @@ impl fmt::Display for TournamentState {
             TournamentState::Ended => {
                 try!(fmt.write_str("ended"));
             }
+            TournamentState::AcceptingPredictions => {
+                try!(fmt.write_str("accepting_predictions"));
+            }
+            TournamentState::CheckingIn => {
+                try!(fmt.write_str("checking_in"));
+            }
+            TournamentState::CheckedIn => {
+                try!(fmt.write_str("checked_in"));
+            }
+            TournamentState::GroupStagesUnderway => {
+                try!(fmt.write_str("group_stages_underway"));
+            }
+            TournamentState::AwaitingReview => {
+                try!(fmt.write_str("awaiting_review"));
+            }
         }
         Ok(())
     }
 }
+impl FromStr for TournamentState {
+    type Err = ();
+    /// Parses a state filter ignoring case and separators, e.g. `In Progress`.
+    fn from_str(s: &str) -> Result<TournamentState, ()> {
+        match &enum_key(s)[..] {
+            "all" | "any" => Ok(TournamentState::All),
+            "pending" => Ok(TournamentState::Pending),
+            "in_progress" => Ok(TournamentState::InProgress),
+            "ended" | "finished" => Ok(TournamentState::Ended),
+            "accepting_predictions" => Ok(TournamentState::AcceptingPredictions),
+            "checking_in" => Ok(TournamentState::CheckingIn),
+            "checked_in" => Ok(TournamentState::CheckedIn),
+            "group_stages_underway" => Ok(TournamentState::GroupStagesUnderway),
+            "awaiting_review" => Ok(TournamentState::AwaitingReview),
+            _ => Err(()),
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
     extern crate serde_json;
-    use tournament::{Tournament, TournamentId, TournamentType};
-
-    #[test]
-    fn test_tournament_parse() {
-        let string = r#"{
+    use chrono::*;
+    use datetime::ChallongeDateTime;
+    use serde_json::Value;
+    use std::time::Duration as StdDuration;
+    use tournament::{
+        Category, Index, PredictionMethod, Tournament, TournamentId, TournamentState,
+        TournamentStatus, TournamentType,
+    };
+
+    const TOURNAMENT: &'static str = r#"{
           "tournament": {
             "accept_attachments": false,
             "allow_participant_match_reporting": true,
@@ -783,13 +1430,236 @@ mod tests {
             "group_stages_were_started": false
           }
         }"#;
+
+    fn tournament(id: u64, name: &str, url: &str) -> Tournament {
+        let mut t = Tournament::decode(&serde_json::from_str(TOURNAMENT).unwrap()).unwrap();
+        t.id = TournamentId::Id(id);
+        t.name = name.to_owned();
+        t.url = url.to_owned();
+        t
+    }
+
+    #[test]
+    fn test_tournament_search() {
+        let index = Index(vec![
+            tournament(1, "Weekly Smash #1", "weekly_smash_1"),
+            tournament(2, "Monthly Chess", "chess_monthly"),
+            tournament(3, "Friday Fights", "weekly_fights"),
+        ]);
+        let found: Vec<&Tournament> = index.search("WEEKLY");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].id, TournamentId::Id(1));
+        assert_eq!(found[1].id, TournamentId::Id(3));
+        assert!(index.search("tennis").is_empty());
+    }
+
+    #[test]
+    fn test_group_by_state() {
+        let mut pending = tournament(1, "Pending", "pending");
+        pending.state = TournamentStatus::Pending;
+        let mut swiss = tournament(2, "Swiss", "swiss");
+        swiss.tournament_type = TournamentType::Swiss;
+        let index = Index(vec![tournament(3, "Underway", "underway"), swiss, pending]);
+
+        let groups = index.group_by_state();
+        let states: Vec<&TournamentStatus> = groups.keys().collect();
+        assert_eq!(
+            states,
+            vec![&TournamentStatus::Pending, &TournamentStatus::Underway]
+        );
+        assert_eq!(groups[&TournamentStatus::Underway].len(), 2);
+
+        let summary = index.summary();
+        assert_eq!(summary.total, 3);
+        assert_eq!(summary.by_state[&TournamentStatus::Pending], 1);
+        assert_eq!(summary.by_type[&TournamentType::SingleElimination], 2);
+        assert_eq!(summary.by_type[&TournamentType::Swiss], 1);
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let states = vec![
+            TournamentState::All,
+            TournamentState::Pending,
+            TournamentState::InProgress,
+            TournamentState::Ended,
+            TournamentState::AcceptingPredictions,
+            TournamentState::CheckingIn,
+            TournamentState::CheckedIn,
+            TournamentState::GroupStagesUnderway,
+            TournamentState::AwaitingReview,
+        ];
+        for state in states {
+            assert_eq!(state.to_string().parse::<TournamentState>(), Ok(state));
+        }
+        assert!("underway".parse::<TournamentState>().is_err());
+        assert_eq!(
+            " In-Progress ".parse::<TournamentState>(),
+            Ok(TournamentState::InProgress)
+        );
+        for (s, t) in vec![
+            ("DE", TournamentType::DoubleElimination),
+            ("Single-Elimination", TournamentType::SingleElimination),
+            ("round robin", TournamentType::RoundRobin),
+            ("RR", TournamentType::RoundRobin),
+            ("Swiss System", TournamentType::Swiss),
+        ] {
+            assert_eq!(s.parse::<TournamentType>(), Ok(t));
+        }
+        assert!("elimination".parse::<TournamentType>().is_err());
+        assert_eq!(
+            "Awaiting Review".parse::<TournamentStatus>(),
+            Ok(TournamentStatus::AwaitingReview)
+        );
+        assert_eq!(
+            "Halted".parse::<TournamentStatus>(),
+            Ok(TournamentStatus::Other("Halted".to_owned()))
+        );
+
+        let statuses = vec![
+            TournamentStatus::Pending,
+            TournamentStatus::AcceptingPredictions,
+            TournamentStatus::CheckingIn,
+            TournamentStatus::CheckedIn,
+            TournamentStatus::GroupStagesUnderway,
+            TournamentStatus::GroupStagesFinalized,
+            TournamentStatus::Underway,
+            TournamentStatus::AwaitingReview,
+            TournamentStatus::Complete,
+        ];
+        for status in &statuses {
+            assert_eq!(status.to_string().parse(), Ok(status.clone()));
+        }
+        let mut sorted = statuses.clone();
+        sorted.sort();
+        assert_eq!(sorted, statuses);
+        assert_eq!(
+            "postponed".parse(),
+            Ok(TournamentStatus::Other("postponed".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_tournament_updated_since() {
+        let mut old = tournament(1, "Old", "old");
+        old.updated_at = DateTime::parse_from_rfc3339("2015-01-01T00:00:00-05:00")
+            .unwrap()
+            .into();
+        let mut recent = tournament(2, "Recent", "recent");
+        recent.updated_at = DateTime::parse_from_rfc3339("2015-02-01T00:00:00-05:00")
+            .unwrap()
+            .into();
+        let mut latest = tournament(3, "Latest", "latest");
+        latest.updated_at = DateTime::parse_from_rfc3339("2015-03-01T00:00:00+03:00")
+            .unwrap()
+            .into();
+        let index = Index(vec![old, recent, latest]);
+
+        let since = DateTime::parse_from_rfc3339("2015-01-15T00:00:00Z")
+            .unwrap()
+            .with_timezone(&UTC);
+        let found = index.updated_since(&since);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].id, TournamentId::Id(3));
+        assert_eq!(found[1].id, TournamentId::Id(2));
+    }
+
+    #[test]
+    fn test_newer_keys_are_optional() {
+        let mut json: Value = serde_json::from_str(TOURNAMENT).unwrap();
+        if let Value::Object(ref mut map) = json {
+            if let Some(&mut Value::Object(ref mut t)) = map.get_mut("tournament") {
+                for key in &[
+                    "category",
+                    "signup_cap",
+                    "start_at",
+                    "predictions_opened_at",
+                ] {
+                    t.remove(*key);
+                }
+            }
+        }
+        let t = Tournament::decode(&json).unwrap();
+        assert_eq!(t.category, None);
+        assert_eq!(t.signup_cap, None);
+        assert_eq!(t.start_at, None);
+        assert_eq!(t.predictions_opened_at, None);
+
+        if let Value::Object(ref mut map) = json {
+            if let Some(&mut Value::Object(ref mut t)) = map.get_mut("tournament") {
+                t.insert(
+                    "category".to_owned(),
+                    Value::String(" Fighting ".to_owned()),
+                );
+            }
+        }
+        let t = Tournament::decode(&json).unwrap();
+        assert_eq!(t.category, Some(Category::new("Fighting")));
+        assert_eq!(
+            t.game().category.map(|c| c.to_string()),
+            Some("Fighting".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_typed_accessors() {
+        let mut t = tournament(1, "Swiss", "swiss");
+        assert_eq!(t.swiss_round_count(), None);
+        assert_eq!(t.check_in_window(), None);
+        assert_eq!(t.check_in_opens_at(), None);
+
+        t.swiss_rounds = 5;
+        t.check_in_duration = Some(30);
+        t.start_at = Some(ChallongeDateTime::parse("2015-01-19T17:00:00-05:00").unwrap());
+        assert_eq!(t.swiss_round_count().map(|r| r.get()), Some(5));
+        assert_eq!(t.check_in_window(), Some(StdDuration::from_secs(1800)));
+        assert_eq!(
+            t.check_in_opens_at(),
+            Some(ChallongeDateTime::parse("2015-01-19T16:30:00-05:00").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_predictions_window() {
+        let mut t = tournament(1, "Pick'em", "pickem");
+        let now = DateTime::parse_from_rfc3339("2015-01-19T16:00:00-05:00")
+            .unwrap()
+            .with_timezone(&UTC);
+        assert!(!t.predictions_open(&now));
+
+        t.accepting_predictions = true;
+        t.prediction_method = 1;
+        t.predictions_opened_at = Some(
+            DateTime::parse_from_rfc3339("2015-01-18T12:00:00-05:00")
+                .unwrap()
+                .into(),
+        );
+        assert!(t.predictions_open(&now));
+        assert_eq!(
+            t.predictions_time_left(&now),
+            Some(Duration::minutes(57) + Duration::seconds(17))
+        );
+
+        let after_start = now + Duration::hours(1);
+        assert!(!t.predictions_open(&after_start));
+        assert_eq!(t.predictions_time_left(&after_start), None);
+
+        for code in 0..4 {
+            assert_eq!(PredictionMethod::from(code).code(), code);
+        }
+    }
+
+    #[test]
+    fn test_tournament_parse() {
+        let string = TOURNAMENT;
         let json_r = serde_json::from_str(string);
         assert!(json_r.is_ok());
         let json = json_r.unwrap();
-        if let Ok(t) = Tournament::decode(json) {
+        if let Ok(t) = Tournament::decode(&json) {
             assert_eq!(t.accept_attachments, false);
             assert_eq!(t.allow_participant_match_reporting, true);
             assert_eq!(t.anonymous_voting, false);
+            assert_eq!(t.category, None);
             // assert_eq!(t.created_at, DateTime<);
             assert_eq!(t.created_by_api, false);
             assert_eq!(t.description, "sample description");
@@ -812,6 +1682,8 @@ mod tests {
             assert_eq!(t.open_signup, false);
             assert_eq!(t.participants_count, 4);
             assert_eq!(t.prediction_method, 0);
+            assert_eq!(t.prediction_scoring(), PredictionMethod::Disabled);
+            assert_eq!(t.predictions_opened_at, None);
             assert_eq!(t.private, false);
             assert_eq!(t.progress_meter, 0);
             assert_eq!(t.swiss_points.bye, Some(1.0f64));
@@ -827,13 +1699,18 @@ mod tests {
             assert_eq!(t.round_robin_points.match_win, 1.0f64);
             assert_eq!(t.sequential_pairings, false);
             assert_eq!(t.show_rounds, true);
+            assert_eq!(t.signup_cap, None);
+            assert_eq!(t.start_at, None);
+            assert_eq!(t.remaining_slots(), None);
             // assert_eq!(t.started_at, DateTime<);
             assert_eq!(t.swiss_rounds, 0);
             assert_eq!(t.teams, false);
+            assert_eq!(t.state, TournamentStatus::Underway);
             assert_eq!(t.tournament_type, TournamentType::SingleElimination);
             // assert_eq!(t.updated_at, DateTime<);
             assert_eq!(t.url, "sample_tournament_1");
             assert_eq!(t.description_source, "sample description source");
+            assert_eq!(t.description_source_or_text(), "sample description source");
             assert_eq!(
                 t.full_challonge_url,
                 "http://challonge.com/sample_tournament_1"
@@ -849,6 +1726,10 @@ mod tests {
             assert_eq!(t.participants_swappable, false);
             assert_eq!(t.team_convertable, false);
             assert_eq!(t.group_stages_were_started, false);
+            let game = t.game();
+            assert_eq!(game.id, Some(600));
+            assert_eq!(game.name, Some("Table Tennis".to_owned()));
+            assert_eq!(game.category, None);
         } else {
             assert!(false);
         }
@@ -0,0 +1,66 @@
+//! Spans of API calls for the `tracing` feature.
+
+use std::time::Duration;
+use tracing::field::Empty;
+use tracing::Span;
+
+use stats;
+use transport::Method;
+
+/// Creates the span of an API call. The status and the duration are recorded once it returns.
+pub fn call_span(method: Method, url: &str) -> Span {
+    let span = ::tracing::info_span!(
+        "challonge_call",
+        endpoint = %stats::endpoint(method, url),
+        tournament = Empty,
+        status = Empty,
+        duration_ms = Empty
+    );
+    if let Some(id) = tournament(url) {
+        span.record("tournament", &id);
+    }
+    span
+}
+
+/// Records the outcome of an API call, `status` is `None` if no response was received.
+pub fn record(span: &Span, status: Option<u16>, latency: Duration) {
+    if let Some(status) = status {
+        span.record("status", &status);
+    }
+    span.record(
+        "duration_ms",
+        &(latency.as_secs() * 1000 + u64::from(latency.subsec_millis())),
+    );
+}
+
+/// Returns the tournament identifier of a request url, e.g. `10230` or `subdomain-url`.
+fn tournament(url: &str) -> Option<&str> {
+    let path = url.split('?').next().unwrap_or("");
+    let mut segments = path.split('/').skip_while(|s| *s != "tournaments").skip(1);
+    segments
+        .next()
+        .map(|s| s.trim_end_matches(".json"))
+        .and_then(|s| if s.is_empty() { None } else { Some(s) })
+}
+
+#[cfg(test)]
+mod tests {
+    use trace::tournament;
+
+    #[test]
+    fn test_tournament() {
+        let base = "https://api.challonge.com/v1";
+        assert_eq!(
+            tournament(&format!(
+                "{}/tournaments/10230.json?include_matches=1",
+                base
+            )),
+            Some("10230")
+        );
+        assert_eq!(
+            tournament(&format!("{}/tournaments/sub-cup/participants/7.json", base)),
+            Some("sub-cup")
+        );
+        assert_eq!(tournament(&format!("{}/tournaments.json", base)), None);
+    }
+}
@@ -0,0 +1,185 @@
+//! Compensation of multi-step operations.
+//!
+//! Challonge has no transactions, so composite operations (e.g. creating a season) record
+//! every step they perform in a `Transaction`. When a later step fails, the recorded steps
+//! are compensated in reverse order: created tournaments are deleted, added participants removed.
+
+use error::Error;
+use participants::{Participant, ParticipantCreate, ParticipantId};
+use tournament::{Tournament, TournamentCreate, TournamentId};
+use Challonge;
+
+/// A performed step which can be compensated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// A tournament was created, it is compensated by deleting it
+    CreatedTournament(TournamentId),
+
+    /// A participant was added to a tournament, it is compensated by removing the participant
+    AddedParticipant(TournamentId, ParticipantId),
+}
+
+/// What was rolled back after a failure.
+#[derive(Debug)]
+pub struct RollbackReport {
+    /// Steps which were compensated, in the order of compensation
+    pub compensated: Vec<Step>,
+
+    /// Steps which could not be compensated and must be cleaned up by hand
+    pub failed: Vec<(Step, Error)>,
+}
+impl RollbackReport {
+    /// Returns `true` if every step was compensated.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Records steps of a multi-step operation.
+pub struct Transaction<'a> {
+    client: &'a Challonge,
+    steps: Vec<Step>,
+}
+impl<'a> Transaction<'a> {
+    /// Starts recording steps performed with `client`.
+    pub fn new(client: &'a Challonge) -> Transaction<'a> {
+        Transaction {
+            client: client,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Returns the client of the transaction.
+    pub fn client(&self) -> &'a Challonge {
+        self.client
+    }
+
+    /// Returns the steps performed so far.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Records a step performed outside of the transaction methods.
+    pub fn record(&mut self, step: Step) {
+        self.steps.push(step);
+    }
+
+    /// Creates a tournament and records it.
+    pub fn create_tournament(
+        &mut self,
+        tournament: &TournamentCreate,
+    ) -> Result<Tournament, Error> {
+        let created = try!(self.client.create_tournament(tournament));
+        self.record(Step::CreatedTournament(created.id.clone()));
+        Ok(created)
+    }
+
+    /// Adds a participant to a tournament and records it.
+    pub fn create_participant(
+        &mut self,
+        id: &TournamentId,
+        participant: &ParticipantCreate,
+    ) -> Result<Participant, Error> {
+        let created = try!(self.client.create_participant(id, participant));
+        self.record(Step::AddedParticipant(id.clone(), created.id.clone()));
+        Ok(created)
+    }
+
+    /// Finishes the transaction keeping every step.
+    pub fn commit(self) -> Vec<Step> {
+        self.steps
+    }
+
+    /// Compensates every recorded step, the last one first.
+    /// Participants of a tournament which is deleted anyway are not removed one by one.
+    pub fn rollback(self) -> RollbackReport {
+        let mut report = RollbackReport {
+            compensated: Vec::new(),
+            failed: Vec::new(),
+        };
+        let deleted: Vec<TournamentId> = self
+            .steps
+            .iter()
+            .filter_map(|s| match *s {
+                Step::CreatedTournament(ref id) => Some(id.clone()),
+                _ => None,
+            })
+            .collect();
+        for step in self.steps.into_iter().rev() {
+            let result = match step {
+                Step::CreatedTournament(ref id) => self.client.delete_tournament(id).map(|_| ()),
+                Step::AddedParticipant(ref id, _) if deleted.contains(id) => Ok(()),
+                Step::AddedParticipant(ref id, ref participant) => {
+                    self.client.delete_participant(id, participant)
+                }
+            };
+            match result {
+                Ok(()) => report.compensated.push(step),
+                Err(e) => report.failed.push((step, e)),
+            }
+        }
+        report
+    }
+}
+
+/// Runs `operation` in a transaction. If it fails, the performed steps are rolled back
+/// and `Error::RolledBack` carries the original error with the rollback report.
+/// # Example
+/// ```ignore
+/// extern crate challonge;
+///
+/// use self::challonge::{Challonge, ParticipantCreate, TournamentCreate};
+/// use self::challonge::transaction;
+///
+/// let c = Challonge::builder()
+///     .credentials("myusername", "myapikey")
+///     .build()
+///     .unwrap();
+/// let mut tc = TournamentCreate::new();
+/// tc.name("Quick cup").url("quick_cup");
+/// let mut pc = ParticipantCreate::new();
+/// pc.name("Alice");
+/// let tournament = transaction::run(&c, |tx| {
+///     let t = try!(tx.create_tournament(&tc));
+///     try!(tx.create_participant(&t.id, &pc));
+///     Ok(t)
+/// });
+/// ```
+pub fn run<'a, T, F>(client: &'a Challonge, operation: F) -> Result<T, Error>
+where
+    F: FnOnce(&mut Transaction<'a>) -> Result<T, Error>,
+{
+    let mut tx = Transaction::new(client);
+    match operation(&mut tx) {
+        Ok(value) => Ok(value),
+        Err(e) => Err(Error::RolledBack {
+            cause: Box::new(e),
+            report: tx.rollback(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use participants::ParticipantId;
+    use tournament::TournamentId;
+    use transaction::{Step, Transaction};
+    use Challonge;
+
+    #[test]
+    fn test_transaction_records_steps() {
+        let c = Challonge::builder()
+            .credentials("user", "key")
+            .build()
+            .unwrap();
+        let mut tx = Transaction::new(&c);
+        tx.record(Step::CreatedTournament(TournamentId::Id(1)));
+        tx.record(Step::AddedParticipant(
+            TournamentId::Id(1),
+            ParticipantId(2),
+        ));
+        assert_eq!(tx.steps().len(), 2);
+        let steps = tx.commit();
+        assert_eq!(steps[0], Step::CreatedTournament(TournamentId::Id(1)));
+    }
+}
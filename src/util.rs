@@ -2,26 +2,142 @@ use error::Error;
 use serde_json::Value;
 use std::collections::BTreeMap;
 
-pub fn decode_array<T, F: Fn(Value) -> Result<T, Error>>(
-    value: Value,
+pub fn decode_array<T, F: Fn(&Value) -> Result<T, Error>>(
+    value: &Value,
     f: F,
 ) -> Result<Vec<T>, Error> {
-    match value {
-        Value::Array(arr) => arr.into_iter().map(f).collect(),
-        _ => Err(Error::Decode("Error decoding object", value)),
+    match *value {
+        Value::Array(ref arr) => arr.iter().map(f).collect(),
+        _ => Err(Error::Decode("Error decoding object", value.clone())),
     }
 }
 
-pub fn into_map(value: Value) -> Result<BTreeMap<String, Value>, Error> {
-    match value {
-        Value::Object(m) => Ok(m),
-        value => Err(Error::Decode("Expected object", value)),
+pub fn as_map(value: &Value) -> Result<&BTreeMap<String, Value>, Error> {
+    match *value {
+        Value::Object(ref m) => Ok(m),
+        _ => Err(Error::Decode("Expected object", value.clone())),
     }
 }
 
-pub fn remove(map: &mut BTreeMap<String, Value>, key: &str) -> Result<Value, Error> {
-    map.remove(key).ok_or(Error::Decode(
+pub fn field<'a>(map: &'a BTreeMap<String, Value>, key: &str) -> Result<&'a Value, Error> {
+    map.get(key).ok_or(Error::Decode(
         "Unexpected absent key",
         Value::String(key.into()),
     ))
 }
+
+const BASE64_CHARS: &'static [u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn base64(input: &[u8]) -> String {
+    let mut out = String::with_capacity((input.len() + 2) / 3 * 4);
+    for chunk in input.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = ((b[0] as usize) << 16) | ((b[1] as usize) << 8) | b[2] as usize;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_CHARS[(n >> (18 - 6 * i)) & 0x3f] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Normalizes an enum name for lenient parsing: trimmed, lowercase, with words joined by `_`,
+/// so `Double-Elimination`, `double elimination` and `DOUBLE_ELIMINATION` are the same.
+pub fn enum_key(s: &str) -> String {
+    s.split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<String>>()
+        .join("_")
+}
+
+/// Converts rails-style form parameters into a json object:
+/// `tournament[name]` becomes `{"tournament": {"name": ..}}` and
+/// `participant[][name]` becomes `{"participant": [{"name": ..}]}`.
+/// A new array element is started when its field repeats.
+pub fn pairs_to_json<K: AsRef<str>>(params: &[(K, String)]) -> Value {
+    let mut root = BTreeMap::new();
+    for &(ref key, ref value) in params {
+        let key = key.as_ref();
+        let value = Value::String(value.clone());
+        let (name, rest) = match key.find('[') {
+            Some(i) => (&key[..i], &key[i..]),
+            None => {
+                root.insert(key.to_owned(), value);
+                continue;
+            }
+        };
+        let is_array = rest.starts_with("[]");
+        let field = rest
+            .trim_start_matches("[]")
+            .trim_matches(|c| c == '[' || c == ']');
+        if is_array {
+            let entry = root
+                .entry(name.to_owned())
+                .or_insert_with(|| Value::Array(Vec::new()));
+            if let Value::Array(ref mut items) = *entry {
+                let start_new = match items.last() {
+                    Some(&Value::Object(ref m)) => m.contains_key(field),
+                    _ => true,
+                };
+                if start_new {
+                    items.push(Value::Object(BTreeMap::new()));
+                }
+                if let Some(&mut Value::Object(ref mut m)) = items.last_mut() {
+                    m.insert(field.to_owned(), value);
+                }
+            }
+        } else {
+            let entry = root
+                .entry(name.to_owned())
+                .or_insert_with(|| Value::Object(BTreeMap::new()));
+            if let Value::Object(ref mut m) = *entry {
+                m.insert(field.to_owned(), value);
+            }
+        }
+    }
+    Value::Object(root)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use util::{base64, pairs_to_json};
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b"user:pass"), "dXNlcjpwYXNz");
+        assert_eq!(base64(b"user:key"), "dXNlcjprZXk=");
+        assert_eq!(base64(b"ab"), "YWI=");
+        assert_eq!(base64(b""), "");
+    }
+
+    #[test]
+    fn test_pairs_to_json() {
+        let params = vec![
+            ("participant[][name]", "A".to_owned()),
+            ("participant[][seed]", "1".to_owned()),
+            ("participant[][name]", "B".to_owned()),
+            ("tournament[name]", "Cup".to_owned()),
+            ("include_matches", "1".to_owned()),
+        ];
+        let expected: serde_json::Value = serde_json::from_str(
+            r#"{
+              "include_matches": "1",
+              "participant": [{"name": "A", "seed": "1"}, {"name": "B"}],
+              "tournament": {"name": "Cup"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(pairs_to_json(&params), expected);
+    }
+}
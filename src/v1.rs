@@ -0,0 +1,65 @@
+//! The API surface of challonge 0.4.
+//!
+//! Code written against 0.4 can import from this module while it migrates to the current API
+//! piece by piece: the type names are the same, and the entry points which have been replaced
+//! are kept here with deprecation notes naming their replacements.
+//!
+//! The types themselves changed in 0.5, code using them needs these updates:
+//!
+//! * `Error::Status` holds the status code as a `u16` instead of a `hyper` `StatusCode`, along
+//!   with the json body and its `ApiErrorCode`s; build it with `Error::status`.
+//! * `Error::Hyper` exists only with the `hyper` feature.
+//! * `Player::id` of a match is an `Option<ParticipantId>`, `None` until the player is known.
+//! * Timestamps are `ChallongeDateTime`, which derefs to the former `DateTime<FixedOffset>` and
+//!   converts into it with `From`.
+//! * `decode` functions take a `&Value` instead of a `Value`.
+//! * `Challonge::delete_tournament` returns the deleted `Tournament` instead of `()`.
+//!
+//! ```ignore
+//! extern crate challonge;
+//!
+//! use self::challonge::v1::{self, TournamentId, TournamentIncludes};
+//!
+//! let c = v1::new("myusername", "myapikey");
+//! let t = c.get_tournament(&TournamentId::Id(2669881), &TournamentIncludes::All);
+//! ```
+
+pub use attachments::{Attachment, AttachmentCreate, AttachmentId, Index as AttachmentIndex};
+pub use error::Error;
+pub use matches::{
+    Index as MatchIndex, Match, MatchId, MatchScore, MatchScores, MatchState, MatchUpdate, Player,
+};
+pub use participants::{Index as ParticipantIndex, Participant, ParticipantCreate, ParticipantId};
+pub use tournament::{
+    Index as TournamentIndex, Tournament, TournamentCreate, TournamentId, TournamentIncludes,
+    TournamentState, TournamentType,
+};
+pub use Challonge;
+
+use transport::{self, TransportOptions, UnavailableTransport};
+
+/// Create new connection to Challonge, as `Challonge::new` did in 0.4.
+///
+/// Like in 0.4 it does not fail: if the HTTP transport can not be set up, e.g. because of TLS,
+/// every call of the client returns `Error::Transport` with the reason.
+#[deprecated(
+    since = "0.5.0",
+    note = "use `Challonge::builder().credentials(user_name, api_key).build()` instead"
+)]
+pub fn new<S: Into<String>>(user_name: S, api_key: S) -> Challonge {
+    let transport =
+        transport::configured_transport(&TransportOptions::default()).unwrap_or_else(|e| {
+            Box::new(UnavailableTransport::new(format!(
+                "Could not set up the HTTP transport: {:?}",
+                e
+            )))
+        });
+    match Challonge::builder()
+        .credentials(user_name, api_key)
+        .transport(transport)
+        .build()
+    {
+        Ok(client) => client,
+        Err(_) => unreachable!("the credentials and the transport are set"),
+    }
+}
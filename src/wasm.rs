@@ -0,0 +1,270 @@
+//! Client for WebAssembly in the browser (`wasm` feature).
+//!
+//! `WasmChallonge` sends the requests of a `Challonge` client with the fetch API of the browser
+//! and returns futures to be driven by `wasm_bindgen_futures::spawn_local`. Build for
+//! `wasm32-unknown-unknown` without the default features, which pull in a native transport:
+//!
+//! ```toml
+//! challonge = { version = "0.4", default-features = false, features = ["wasm"] }
+//! ```
+//!
+//! The API does not allow cross-origin requests, so point the client at a proxy served from the
+//! origin of the page with `ClientBuilder::base_url`, given as an absolute url. Browsers do not
+//! let scripts set the `User-Agent` header, the one of the client is dropped.
+//!
+//! ```ignore
+//! extern crate challonge;
+//!
+//! use self::challonge::{Challonge, TournamentId, TournamentIncludes, WasmChallonge};
+//!
+//! let c = WasmChallonge::new(
+//!     Challonge::builder()
+//!         .credentials("myusername", "myapikey")
+//!         .base_url("https://bracket.example.com/challonge/v1")
+//!         .build()
+//!         .unwrap(),
+//! );
+//! wasm_bindgen_futures::spawn_local(async move {
+//!     let t = c
+//!         .get_tournament(&TournamentId::Id(2669881), &TournamentIncludes::All)
+//!         .await;
+//! });
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use js_sys::{self, Array, Uint8Array};
+use serde_json::Value;
+use url;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{self, RequestInit};
+
+use error::Error;
+use matches::{Index as MatchIndex, Match, MatchId, MatchIndexOptions, MatchState};
+use participants::{Index as ParticipantIndex, ParticipantId, ParticipantIndexOptions};
+use tournament::{Tournament, TournamentId, TournamentIncludes};
+use transport::{Method, Request, Response};
+use {decode_response, index_url, Challonge};
+
+enum State {
+    Failed(Option<Error>),
+    Sending(JsFuture),
+    Reading(JsFuture, u16, Vec<(String, String)>),
+}
+
+/// Result of a call of `WasmChallonge`.
+pub struct Fetch<T> {
+    client: Challonge,
+    request: Request,
+    state: State,
+    decode: fn(&Value) -> Result<T, Error>,
+}
+impl<T> Future for Fetch<T> {
+    type Output = Result<T, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<T, Error>> {
+        let this = self.get_mut();
+        loop {
+            let next = match this.state {
+                State::Failed(ref mut e) => {
+                    return Poll::Ready(Err(e
+                        .take()
+                        .unwrap_or(Error::Api("Polled after completion"))))
+                }
+                State::Sending(ref mut f) => match Pin::new(f).poll(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Err(e)) => State::Failed(Some(js_error(e))),
+                    Poll::Ready(Ok(value)) => {
+                        let response: web_sys::Response = value.unchecked_into();
+                        let headers = response_headers(&response);
+                        match response.array_buffer() {
+                            Ok(body) => {
+                                State::Reading(JsFuture::from(body), response.status(), headers)
+                            }
+                            Err(e) => State::Failed(Some(js_error(e))),
+                        }
+                    }
+                },
+                State::Reading(ref mut f, status, ref mut headers) => match Pin::new(f).poll(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Err(e)) => State::Failed(Some(js_error(e))),
+                    Poll::Ready(Ok(body)) => {
+                        let response = Response {
+                            status: status,
+                            headers: headers.split_off(0),
+                            body: Uint8Array::new(&body).to_vec(),
+                        };
+                        let decode = this.decode;
+                        let result = this
+                            .client
+                            .check_response(&this.request, response)
+                            .and_then(|r| decode_response(&r, decode));
+                        this.state = State::Failed(None);
+                        return Poll::Ready(result);
+                    }
+                },
+            };
+            this.state = next;
+        }
+    }
+}
+
+/// Client for the Challonge REST API using the fetch API of the browser.
+#[derive(Debug, Clone)]
+pub struct WasmChallonge {
+    client: Challonge,
+}
+impl WasmChallonge {
+    /// Wraps a client. Its transport, rate limiter and statistics are not used.
+    pub fn new(client: Challonge) -> WasmChallonge {
+        WasmChallonge { client: client }
+    }
+
+    /// Retrieve a single tournament record created with your account.
+    pub fn get_tournament(
+        &self,
+        id: &TournamentId,
+        includes: &TournamentIncludes,
+    ) -> Fetch<Tournament> {
+        let mut url = url::Url::parse(&format!(
+            "{}/tournaments/{}.json",
+            self.client.base_url,
+            self.client.tournament_path(id)
+        ))
+        .unwrap();
+        Challonge::add_tournament_includes(&mut url, includes);
+        self.get(url.as_str(), Tournament::decode)
+    }
+
+    /// Retrieve a tournament's participant list.
+    pub fn participant_index(&self, id: &TournamentId) -> Fetch<ParticipantIndex> {
+        self.participant_index_with(id, &ParticipantIndexOptions::new())
+    }
+
+    /// Retrieve a tournament's participant list matching the options.
+    pub fn participant_index_with(
+        &self,
+        id: &TournamentId,
+        options: &ParticipantIndexOptions,
+    ) -> Fetch<ParticipantIndex> {
+        let url = index_url(
+            &format!(
+                "{}/tournaments/{}/participants.json",
+                self.client.base_url,
+                self.client.tournament_path(id)
+            ),
+            options,
+        );
+        self.get(url.as_str(), ParticipantIndex::decode)
+    }
+
+    /// Retrieve a tournament's match list.
+    pub fn match_index(
+        &self,
+        id: &TournamentId,
+        state: Option<MatchState>,
+        participant_id: Option<ParticipantId>,
+    ) -> Fetch<MatchIndex> {
+        let options = MatchIndexOptions {
+            state: state,
+            participant_id: participant_id,
+        };
+        self.match_index_with(id, &options)
+    }
+
+    /// Retrieve a tournament's match list matching the options.
+    pub fn match_index_with(
+        &self,
+        id: &TournamentId,
+        options: &MatchIndexOptions,
+    ) -> Fetch<MatchIndex> {
+        let url = index_url(
+            &format!(
+                "{}/tournaments/{}/matches.json",
+                self.client.base_url,
+                self.client.tournament_path(id)
+            ),
+            options,
+        );
+        self.get(url.as_str(), MatchIndex::decode)
+    }
+
+    /// Retrieve a single match record for a tournament.
+    pub fn get_match(
+        &self,
+        id: &TournamentId,
+        match_id: &MatchId,
+        include_attachments: bool,
+    ) -> Fetch<Match> {
+        let mut url = url::Url::parse(&format!(
+            "{}/tournaments/{}/matches/{}.json",
+            self.client.base_url,
+            self.client.tournament_path(id),
+            match_id.0
+        ))
+        .unwrap();
+        url.query_pairs_mut().append_pair(
+            "include_attachments",
+            &(include_attachments as i64).to_string(),
+        );
+        self.get(url.as_str(), Match::decode)
+    }
+
+    fn get<T>(&self, url: &str, decode: fn(&Value) -> Result<T, Error>) -> Fetch<T> {
+        let (request, state) = match self.client.prepare_request(Method::Get, url, None) {
+            Ok(request) => {
+                let state = match fetch(&request) {
+                    Ok(f) => State::Sending(f),
+                    Err(e) => State::Failed(Some(e)),
+                };
+                (request, state)
+            }
+            Err(e) => (Request::new(Method::Get, url), State::Failed(Some(e))),
+        };
+        Fetch {
+            client: self.client.clone(),
+            request: request,
+            state: state,
+            decode: decode,
+        }
+    }
+}
+
+/// Starts sending the request with the fetch API of the window.
+fn fetch(request: &Request) -> Result<JsFuture, Error> {
+    let mut init = RequestInit::new();
+    init.method(request.method.as_str());
+    if let Some(ref body) = request.body {
+        init.body(Some(&JsValue::from_str(body)));
+    }
+    let r = try!(web_sys::Request::new_with_str_and_init(&request.url, &init).map_err(js_error));
+    for &(ref name, ref value) in request
+        .headers
+        .iter()
+        .filter(|h| !h.0.eq_ignore_ascii_case("User-Agent"))
+    {
+        try!(r.headers().set(name, value).map_err(js_error));
+    }
+    let window = try!(web_sys::window().ok_or(Error::Transport("No window".to_owned())));
+    Ok(JsFuture::from(window.fetch_with_request(&r)))
+}
+
+fn response_headers(response: &web_sys::Response) -> Vec<(String, String)> {
+    let mut headers = Vec::new();
+    if let Ok(Some(entries)) = js_sys::try_iter(response.headers().as_ref()) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let pair = Array::from(&entry);
+            if let (Some(name), Some(value)) = (pair.get(0).as_string(), pair.get(1).as_string()) {
+                headers.push((name, value));
+            }
+        }
+    }
+    headers
+}
+
+fn js_error(e: JsValue) -> Error {
+    Error::Transport(e.as_string().unwrap_or_else(|| format!("{:?}", e)))
+}
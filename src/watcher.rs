@@ -0,0 +1,619 @@
+//! Polling watcher which emits events on tournament changes.
+
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use rand::{self, Rng};
+
+use datetime::ChallongeDateTime;
+use error::Error;
+use journal::{JournalEntry, JournalWriter};
+use leader::LeaderLock;
+use matches::{Match, MatchState};
+use participants::{Participant, ParticipantId};
+use tournament::{Tournament, TournamentId, TournamentStatus};
+use Challonge;
+
+/// Default polling interval of the full watcher.
+pub const FULL_INTERVAL_SECS: u64 = 30;
+
+/// Default polling interval of the participants-only watcher.
+pub const PARTICIPANTS_INTERVAL_SECS: u64 = 10;
+
+/// An event emitted by the `Watcher`.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// Tournament attributes were updated.
+    TournamentUpdated(Tournament),
+
+    /// A new participant has signed up.
+    SignupReceived(Participant),
+
+    /// A participant was removed from the tournament.
+    ParticipantRemoved(ParticipantId),
+
+    /// Number of active participants reached the signup cap of the tournament.
+    SignupCapReached,
+
+    /// A match was created or updated.
+    MatchUpdated(Match),
+}
+
+/// Defines what the watcher polls.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchMode {
+    /// Poll tournament, participants and matches.
+    Full,
+
+    /// Poll participants only. Useful for open-signup tournaments.
+    ParticipantsOnly,
+}
+
+/// Polling intervals chosen by the progress of the tournament.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveIntervals {
+    /// Interval while matches are underway
+    pub underway: StdDuration,
+
+    /// Interval before the tournament starts
+    pub pending: StdDuration,
+
+    /// Interval when the tournament is complete or awaits review
+    pub finished: StdDuration,
+}
+impl AdaptiveIntervals {
+    /// Creates intervals of 15 seconds while underway, a minute while pending and 5 minutes when finished.
+    pub fn new() -> AdaptiveIntervals {
+        AdaptiveIntervals {
+            underway: StdDuration::from_secs(15),
+            pending: StdDuration::from_secs(60),
+            finished: StdDuration::from_secs(300),
+        }
+    }
+
+    builder!(underway, StdDuration);
+    builder!(pending, StdDuration);
+    builder!(finished, StdDuration);
+}
+impl Default for AdaptiveIntervals {
+    fn default() -> AdaptiveIntervals {
+        AdaptiveIntervals::new()
+    }
+}
+
+/// Backoff of the polling interval while a tournament is idle.
+///
+/// After `after` polls in a row without events, every further idle poll multiplies the interval
+/// by `factor`, up to `max`. The first change snaps the watcher back to its normal interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IdleBackoff {
+    /// Number of polls without events before backing off
+    pub after: u32,
+
+    /// Growth of the interval with every further idle poll
+    pub factor: f64,
+
+    /// Longest interval
+    pub max: StdDuration,
+}
+impl IdleBackoff {
+    /// Creates a backoff doubling the interval after 10 idle polls, up to 10 minutes.
+    pub fn new() -> IdleBackoff {
+        IdleBackoff {
+            after: 10,
+            factor: 2.0,
+            max: StdDuration::from_secs(600),
+        }
+    }
+
+    builder!(after, u32);
+    builder!(factor, f64);
+    builder!(max, StdDuration);
+
+    /// Returns the interval after `idle_polls` polls without events, given the normal interval.
+    /// An interval already longer than `max` is kept.
+    pub fn interval(&self, base: StdDuration, idle_polls: u32) -> StdDuration {
+        if idle_polls < self.after || base >= self.max {
+            return base;
+        }
+        let steps = (idle_polls - self.after + 1).min(64) as i32;
+        let millis = (base.as_secs() * 1000 + u64::from(base.subsec_nanos() / 1_000_000)) as f64;
+        let scaled = millis * self.factor.max(1.0).powi(steps);
+        let max = self.max.as_secs() * 1000 + u64::from(self.max.subsec_nanos() / 1_000_000);
+        StdDuration::from_millis(scaled.min(max as f64) as u64)
+    }
+}
+impl Default for IdleBackoff {
+    fn default() -> IdleBackoff {
+        IdleBackoff::new()
+    }
+}
+
+/// Pauses and resumes a running watcher from another thread.
+#[derive(Debug, Clone, Default)]
+pub struct WatcherControl {
+    paused: Arc<AtomicBool>,
+}
+impl WatcherControl {
+    /// Stops polling until `resume` is called. A poll in progress is finished.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// Resumes polling.
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if the watcher is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+}
+
+/// Polls a tournament and emits events on changes.
+///
+/// The first poll only records the current state of the tournament, events are emitted by subsequent polls.
+pub struct Watcher<'a> {
+    client: &'a Challonge,
+    id: TournamentId,
+    mode: WatchMode,
+    interval: StdDuration,
+    primed: bool,
+    signup_cap: Option<u64>,
+    cap_reached: bool,
+    tournament_updated_at: Option<ChallongeDateTime>,
+    participants: BTreeSet<u64>,
+    matches: HashMap<u64, ChallongeDateTime>,
+    journal: Option<JournalWriter<Box<dyn Write + 'a>>>,
+    control: WatcherControl,
+    adaptive: Option<AdaptiveIntervals>,
+    idle: Option<IdleBackoff>,
+    idle_polls: u32,
+    status: Option<TournamentStatus>,
+    matches_open: bool,
+    leader: Option<(Arc<dyn LeaderLock>, String)>,
+}
+impl<'a> Watcher<'a> {
+    /// Creates a watcher which polls tournament, participants and matches.
+    pub fn new(client: &'a Challonge, id: TournamentId) -> Watcher<'a> {
+        Watcher::with_mode(
+            client,
+            id,
+            WatchMode::Full,
+            StdDuration::from_secs(FULL_INTERVAL_SECS),
+        )
+    }
+
+    /// Creates a lightweight watcher which polls only participants of the tournament.
+    /// The tournament itself is fetched only once to learn its signup cap.
+    pub fn participants_only(client: &'a Challonge, id: TournamentId) -> Watcher<'a> {
+        Watcher::with_mode(
+            client,
+            id,
+            WatchMode::ParticipantsOnly,
+            StdDuration::from_secs(PARTICIPANTS_INTERVAL_SECS),
+        )
+    }
+
+    fn with_mode(
+        client: &'a Challonge,
+        id: TournamentId,
+        mode: WatchMode,
+        interval: StdDuration,
+    ) -> Watcher<'a> {
+        Watcher {
+            client: client,
+            id: id,
+            mode: mode,
+            interval: interval,
+            primed: false,
+            signup_cap: None,
+            cap_reached: false,
+            tournament_updated_at: None,
+            participants: BTreeSet::new(),
+            matches: HashMap::new(),
+            journal: None,
+            control: WatcherControl::default(),
+            adaptive: None,
+            idle: None,
+            idle_polls: 0,
+            status: None,
+            matches_open: false,
+            leader: None,
+        }
+    }
+
+    /// Sets the polling interval.
+    pub fn interval(&mut self, interval: StdDuration) -> &mut Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Records every emitted event to a JSONL journal, see the `journal` module.
+    pub fn journal<W: Write + 'a>(&mut self, writer: W) -> &mut Self {
+        self.journal = Some(JournalWriter::new(Box::new(writer)));
+        self
+    }
+
+    /// Adapts the polling interval to the progress of the tournament instead of using a fixed `interval`.
+    /// The participants-only watcher keeps the interval of the state seen by its first poll.
+    pub fn adaptive(&mut self, intervals: AdaptiveIntervals) -> &mut Self {
+        self.adaptive = Some(intervals);
+        self
+    }
+
+    /// Backs off polling while the tournament does not change, e.g. overnight, see `IdleBackoff`.
+    pub fn idle_backoff(&mut self, backoff: IdleBackoff) -> &mut Self {
+        self.idle = Some(backoff);
+        self
+    }
+
+    /// Returns the interval to wait before the next poll.
+    pub fn current_interval(&self) -> StdDuration {
+        let base = self.base_interval();
+        match self.idle {
+            Some(idle) => idle.interval(base, self.idle_polls),
+            None => base,
+        }
+    }
+
+    fn base_interval(&self) -> StdDuration {
+        let intervals = match self.adaptive {
+            Some(intervals) => intervals,
+            None => return self.interval,
+        };
+        if self.matches_open {
+            return intervals.underway;
+        }
+        match self.status {
+            Some(TournamentStatus::Underway)
+            | Some(TournamentStatus::GroupStagesUnderway)
+            | Some(TournamentStatus::GroupStagesFinalized) => intervals.underway,
+            Some(TournamentStatus::AwaitingReview) | Some(TournamentStatus::Complete) => {
+                intervals.finished
+            }
+            _ => intervals.pending,
+        }
+    }
+
+    /// Lets `run` poll only while `holder` holds the lock of the tournament, so a single
+    /// instance emits events when several watch the same tournament. `holder` must be unique per instance.
+    /// The lock expires after three intervals without a poll.
+    pub fn with_leader_lock(&mut self, lock: Arc<dyn LeaderLock>, holder: &str) -> &mut Self {
+        self.leader = Some((lock, holder.to_owned()));
+        self
+    }
+
+    /// Returns a handle which pauses and resumes `run` from another thread.
+    pub fn control(&self) -> WatcherControl {
+        self.control.clone()
+    }
+
+    /// Pauses the watcher, `run` stops polling until it is resumed.
+    pub fn pause(&self) {
+        self.control.pause()
+    }
+
+    /// Resumes a paused watcher.
+    pub fn resume(&self) {
+        self.control.resume()
+    }
+
+    /// Returns the mode of the watcher.
+    pub fn mode(&self) -> &WatchMode {
+        &self.mode
+    }
+
+    /// Polls the tournament once and returns events for changes since the previous poll.
+    pub fn poll(&mut self) -> Result<Vec<Event>, Error> {
+        let mut events = Vec::new();
+        let participants = if self.mode == WatchMode::Full || !self.primed {
+            let (t, participants) = try!(self.client.get_tournament_with_participants(&self.id));
+            self.on_tournament(t, &mut events);
+            participants
+        } else {
+            try!(self.client.participant_index(&self.id))
+        };
+        self.on_participants(participants.0, &mut events);
+
+        if self.mode == WatchMode::Full {
+            let matches = try!(self.client.match_index(&self.id, None, None));
+            self.on_matches(matches.0, &mut events);
+        }
+
+        if !self.primed || !events.is_empty() {
+            self.idle_polls = 0;
+        } else {
+            self.idle_polls = self.idle_polls.saturating_add(1);
+        }
+        self.primed = true;
+        if let Some(journal) = self.journal.as_mut() {
+            let at = ChallongeDateTime::from(self.client.clock().now());
+            for event in &events {
+                try!(journal.write(&JournalEntry {
+                    at: at,
+                    tournament: self.id.clone(),
+                    event: event.clone(),
+                }));
+            }
+        }
+        Ok(events)
+    }
+
+    /// Polls the tournament in a loop sending events to `sender`.
+    /// Maintenance windows, rate limiting and an open circuit breaker of the client are waited out.
+    /// Returns when the receiving end of the channel hangs up or another error occurs.
+    /// While the watcher is paused, it sleeps without polling.
+    /// With a leader lock, it polls only while holding the lock and releases it when returning.
+    pub fn run(&mut self, sender: Sender<Event>) -> Result<(), Error> {
+        let result = self.run_loop(sender);
+        if let Some((ref lock, ref holder)) = self.leader {
+            if let Err(e) = lock.release(&self.lock_key(), holder) {
+                warn!("Could not release the watcher lock: {:?}", e);
+            }
+        }
+        result
+    }
+
+    fn run_loop(&mut self, sender: Sender<Event>) -> Result<(), Error> {
+        loop {
+            if self.control.is_paused() {
+                self.client.clock().sleep(self.current_interval());
+                continue;
+            }
+            if !try!(self.lead()) {
+                // the state is stale when taking over, so the next poll primes again
+                self.primed = false;
+                let interval = self.current_interval();
+                let jitter = rand::thread_rng().gen_range(0, interval.as_secs() * 500 + 1);
+                self.client
+                    .clock()
+                    .sleep(interval + StdDuration::from_millis(jitter));
+                continue;
+            }
+            let events = match self.poll() {
+                Ok(events) => events,
+                Err(e @ Error::Maintenance { .. }) => {
+                    warn!("Challonge is under maintenance, pausing the watcher");
+                    self.client
+                        .clock()
+                        .sleep(e.retry_delay().unwrap_or(self.current_interval()));
+                    continue;
+                }
+                Err(e @ Error::RateLimited { .. }) => {
+                    warn!("Challonge is rate limiting the client, pausing the watcher");
+                    self.client
+                        .clock()
+                        .sleep(e.retry_delay().unwrap_or(self.current_interval()));
+                    continue;
+                }
+                Err(Error::CircuitOpen { retry_after }) => {
+                    self.client.clock().sleep(retry_after);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            for event in events {
+                if sender.send(event).is_err() {
+                    return Ok(());
+                }
+            }
+            self.client.clock().sleep(self.current_interval());
+        }
+    }
+
+    fn lock_key(&self) -> String {
+        format!("watcher-{}", self.id)
+    }
+
+    fn lead(&self) -> Result<bool, Error> {
+        match self.leader {
+            Some((ref lock, ref holder)) => {
+                lock.try_acquire(&self.lock_key(), holder, self.current_interval() * 3)
+            }
+            None => Ok(true),
+        }
+    }
+
+    fn on_tournament(&mut self, tournament: Tournament, events: &mut Vec<Event>) {
+        self.signup_cap = tournament.signup_cap;
+        self.status = Some(tournament.state.clone());
+        let updated_at = Some(tournament.updated_at);
+        if self.primed && updated_at != self.tournament_updated_at {
+            events.push(Event::TournamentUpdated(tournament));
+        }
+        self.tournament_updated_at = updated_at;
+    }
+
+    fn on_participants(&mut self, participants: Vec<Participant>, events: &mut Vec<Event>) {
+        let mut current = BTreeSet::new();
+        let mut active = 0u64;
+        for p in participants {
+            current.insert(p.id.0);
+            if p.active && !p.on_waiting_list {
+                active += 1;
+            }
+            if self.primed && !self.participants.contains(&p.id.0) {
+                events.push(Event::SignupReceived(p));
+            }
+        }
+        if self.primed {
+            for id in self.participants.difference(&current) {
+                events.push(Event::ParticipantRemoved(ParticipantId(*id)));
+            }
+        }
+        self.participants = current;
+
+        let reached = self.signup_cap.map_or(false, |cap| active >= cap);
+        if self.primed && reached && !self.cap_reached {
+            events.push(Event::SignupCapReached);
+        }
+        self.cap_reached = reached;
+    }
+
+    fn on_matches(&mut self, matches: Vec<Match>, events: &mut Vec<Event>) {
+        self.matches_open = matches.iter().any(|m| m.state == MatchState::Open);
+        for m in matches {
+            let changed = self.matches.get(&m.id.0) != Some(&m.updated_at);
+            self.matches.insert(m.id.0, m.updated_at);
+            if self.primed && changed {
+                events.push(Event::MatchUpdated(m));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use error::Error;
+    use participants::Participant;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tournament::{TournamentId, TournamentStatus};
+    use transport::{Request, Response, Transport};
+    use watcher::{AdaptiveIntervals, Event, IdleBackoff, Watcher};
+    use Challonge;
+
+    fn participant(id: u64) -> Participant {
+        let json = include_str!("../benches/fixtures/participant.json");
+        let mut p = Participant::decode(&serde_json::from_str(json).unwrap()).unwrap();
+        p.id.0 = id;
+        p
+    }
+
+    #[test]
+    fn test_signup_events() {
+        let c = Challonge::builder()
+            .credentials("user", "key")
+            .build()
+            .unwrap();
+        let mut w = Watcher::participants_only(&c, TournamentId::Id(1086875));
+        w.signup_cap = Some(2);
+
+        let mut events = Vec::new();
+        w.on_participants(vec![participant(1)], &mut events);
+        w.primed = true;
+        assert!(events.is_empty());
+
+        w.on_participants(vec![participant(1), participant(2)], &mut events);
+        assert_eq!(events.len(), 2);
+        match events[0] {
+            Event::SignupReceived(ref p) => assert_eq!(p.id.0, 2),
+            _ => assert!(false),
+        }
+        match events[1] {
+            Event::SignupCapReached => {}
+            _ => assert!(false),
+        }
+
+        events.clear();
+        w.on_participants(vec![participant(2)], &mut events);
+        assert_eq!(events.len(), 1);
+        match events[0] {
+            Event::ParticipantRemoved(ref id) => assert_eq!(id.0, 1),
+            _ => assert!(false),
+        }
+    }
+
+    struct Recorded(Arc<Mutex<Vec<String>>>);
+
+    impl Transport for Recorded {
+        fn send(&self, request: &Request) -> Result<Response, Error> {
+            self.0.lock().unwrap().push(request.url.clone());
+            let tournament = include_str!("../benches/fixtures/tournament.json");
+            let participant = include_str!("../benches/fixtures/participant.json");
+            let body = if request.url.contains("/matches.json") {
+                format!("[{}]", include_str!("../benches/fixtures/match.json"))
+            } else if request.url.contains("/participants.json") {
+                format!("[{}]", participant)
+            } else {
+                let mut json: serde_json::Value = serde_json::from_str(tournament).unwrap();
+                if let serde_json::Value::Object(ref mut root) = json {
+                    if let Some(&mut serde_json::Value::Object(ref mut t)) =
+                        root.get_mut("tournament")
+                    {
+                        let included = serde_json::from_str(&format!("[{}]", participant));
+                        t.insert("participants".to_owned(), included.unwrap());
+                    }
+                }
+                serde_json::to_string(&json).unwrap()
+            };
+            Ok(Response {
+                status: 200,
+                headers: Vec::new(),
+                body: body.into_bytes(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_poll_reuses_included_participants() {
+        let urls = Arc::new(Mutex::new(Vec::new()));
+        let c = Challonge::builder()
+            .credentials("user", "key")
+            .transport(Recorded(urls.clone()))
+            .build()
+            .unwrap();
+
+        let mut w = Watcher::new(&c, TournamentId::Id(1086875));
+        w.poll().unwrap();
+        {
+            let urls = urls.lock().unwrap();
+            assert_eq!(urls.len(), 2);
+            assert!(urls[0].contains("include_participants=1"));
+            assert!(urls[1].contains("/matches.json"));
+        }
+        assert_eq!(w.participants.len(), 1);
+
+        // after the first poll the participants-only mode polls just the participants
+        urls.lock().unwrap().clear();
+        let mut w = Watcher::participants_only(&c, TournamentId::Id(1086875));
+        w.poll().unwrap();
+        w.poll().unwrap();
+        let urls = urls.lock().unwrap();
+        assert_eq!(urls.len(), 2);
+        assert!(urls[1].contains("/participants.json"));
+    }
+
+    #[test]
+    fn test_adaptive_interval_and_pause() {
+        let c = Challonge::builder()
+            .credentials("user", "key")
+            .build()
+            .unwrap();
+        let mut w = Watcher::new(&c, TournamentId::Id(1086875));
+        assert_eq!(w.current_interval(), Duration::from_secs(30));
+
+        w.adaptive(AdaptiveIntervals::new());
+        assert_eq!(w.current_interval(), Duration::from_secs(60));
+        w.status = Some(TournamentStatus::Underway);
+        assert_eq!(w.current_interval(), Duration::from_secs(15));
+        w.status = Some(TournamentStatus::Complete);
+        assert_eq!(w.current_interval(), Duration::from_secs(300));
+        w.matches_open = true;
+        assert_eq!(w.current_interval(), Duration::from_secs(15));
+
+        w.idle_backoff(*IdleBackoff::new().after(2).max(Duration::from_secs(100)));
+        w.idle_polls = 1;
+        assert_eq!(w.current_interval(), Duration::from_secs(15));
+        w.idle_polls = 3;
+        assert_eq!(w.current_interval(), Duration::from_secs(60));
+        w.idle_polls = 30;
+        assert_eq!(w.current_interval(), Duration::from_secs(100));
+        w.idle_polls = 0;
+        assert_eq!(w.current_interval(), Duration::from_secs(15));
+
+        let control = w.control();
+        control.pause();
+        assert!(w.control().is_paused());
+        w.resume();
+        assert!(!control.is_paused());
+    }
+}
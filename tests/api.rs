@@ -0,0 +1,105 @@
+//! Public API surface of the crate.
+//!
+//! Every item and signature downstream users rely on is pinned here, so a change which would
+//! break them between minor releases fails to compile. Changing this file is a breaking change
+//! and needs a major version bump; adding items only needs new lines.
+
+extern crate challonge;
+extern crate chrono;
+
+use challonge::error::Error;
+use challonge::{
+    AttachmentId, Challonge, ClientBuilder, Match, MatchId, MatchIndex, MatchIndexOptions,
+    MatchState, MatchUpdate, Participant, ParticipantCreate, ParticipantId, ParticipantIndex,
+    ParticipantIndexOptions, RetryPolicy, Tournament, TournamentCreate, TournamentId,
+    TournamentIncludes, TournamentIndex, TournamentIndexOptions, TournamentState, TournamentStatus,
+    TournamentType,
+};
+use chrono::date::Date;
+use chrono::offset::local::Local;
+
+#[test]
+fn test_client_surface() {
+    let _: fn() -> ClientBuilder = Challonge::builder;
+    let _: fn(&ClientBuilder) -> Result<Challonge, Error> = ClientBuilder::build;
+
+    let _: fn(
+        &Challonge,
+        &TournamentState,
+        &TournamentType,
+        &Date<Local>,
+        &Date<Local>,
+        &str,
+    ) -> Result<TournamentIndex, Error> = Challonge::tournament_index;
+    let _: fn(&Challonge, &TournamentIndexOptions) -> Result<TournamentIndex, Error> =
+        Challonge::tournament_index_with;
+    let _: fn(&Challonge, &TournamentId, &TournamentIncludes) -> Result<Tournament, Error> =
+        Challonge::get_tournament;
+    let _: fn(&Challonge, &TournamentCreate) -> Result<Tournament, Error> =
+        Challonge::create_tournament;
+    let _: fn(&Challonge, &TournamentId, &TournamentCreate) -> Result<Tournament, Error> =
+        Challonge::update_tournament;
+    let _: fn(&Challonge, &TournamentId) -> Result<Tournament, Error> =
+        Challonge::delete_tournament;
+    let _: fn(&Challonge, &TournamentId, &TournamentIncludes) -> Result<(), Error> =
+        Challonge::tournament_start;
+    let _: fn(&Challonge, &TournamentId, &TournamentIncludes) -> Result<(), Error> =
+        Challonge::tournament_finalize;
+    let _: fn(&Challonge, &TournamentId, &TournamentIncludes) -> Result<(), Error> =
+        Challonge::tournament_reset;
+
+    let _: fn(&Challonge, &TournamentId) -> Result<ParticipantIndex, Error> =
+        Challonge::participant_index;
+    let _: fn(
+        &Challonge,
+        &TournamentId,
+        &ParticipantIndexOptions,
+    ) -> Result<ParticipantIndex, Error> = Challonge::participant_index_with;
+    let _: fn(&Challonge, &TournamentId, &ParticipantCreate) -> Result<Participant, Error> =
+        Challonge::create_participant;
+    let _: fn(&Challonge, &TournamentId, &ParticipantId, bool) -> Result<Participant, Error> =
+        Challonge::get_participant;
+    let _: fn(&Challonge, &TournamentId, &ParticipantId, &ParticipantCreate) -> Result<(), Error> =
+        Challonge::update_participant;
+    let _: fn(&Challonge, &TournamentId, &ParticipantId) -> Result<(), Error> =
+        Challonge::delete_participant;
+
+    let _: fn(
+        &Challonge,
+        &TournamentId,
+        Option<MatchState>,
+        Option<ParticipantId>,
+    ) -> Result<MatchIndex, Error> = Challonge::match_index;
+    let _: fn(&Challonge, &TournamentId, &MatchIndexOptions) -> Result<MatchIndex, Error> =
+        Challonge::match_index_with;
+    let _: fn(&Challonge, &TournamentId, &MatchId, bool) -> Result<Match, Error> =
+        Challonge::get_match;
+    let _: fn(&Challonge, &TournamentId, &MatchId, &MatchUpdate) -> Result<Match, Error> =
+        Challonge::update_match;
+}
+
+#[test]
+fn test_model_surface() {
+    // ids are public newtypes, tournaments are addressed by id or by subdomain and url
+    let _ = (ParticipantId(1), MatchId(2), AttachmentId(3));
+    match TournamentId::Id(1) {
+        TournamentId::Id(_) | TournamentId::Url(_, _) => {}
+    }
+
+    // wire names of the enums are part of the API, they are stored by users
+    assert_eq!(TournamentState::InProgress.to_string(), "in_progress");
+    assert_eq!(
+        TournamentStatus::AwaitingReview.to_string(),
+        "awaiting_review"
+    );
+    assert_eq!(
+        TournamentType::DoubleElimination.to_string(),
+        "double elimination"
+    );
+    assert_eq!(MatchState::Open.to_string(), "open");
+
+    let _: RetryPolicy = RetryPolicy::default();
+    let _: MatchUpdate = MatchUpdate::new();
+    let _: TournamentCreate = TournamentCreate::new();
+    let _: ParticipantCreate = ParticipantCreate::new();
+}